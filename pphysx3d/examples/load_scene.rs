@@ -0,0 +1,22 @@
+extern crate pphysx3d;
+use pphysx3d::{renderer::Viewer, scene::PhysicsScene};
+
+fn main() {
+    //Load the scene given on the command line, or the example scene
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "examples/scene.json".to_string());
+    let scene = PhysicsScene::load(&path).unwrap();
+
+    //The viewer draws every object of the scene and steps it, with keys to pause, step and reset it
+    let mut viewer = Viewer::new("Load scene", 1000, 600, scene);
+
+    //Change the camera speed to work well with the scale of the Scene
+    let renderer = viewer.renderer_mut();
+    renderer.change_camera_speed(0.01);
+    renderer.set_background(0.5, 0.5, 0.5);
+    renderer.set_light_to_camera();
+
+    //Main loop, until the window is closed
+    viewer.run();
+}
@@ -0,0 +1,40 @@
+extern crate pphysx3d;
+use std::time::Duration;
+
+use pphysx3d::{
+    renderer::{Kiss3dRenderer, Renderer},
+    scene::scenarios::scenario,
+};
+
+/// Records the balls of `demo_many` to the GIF given as argument, like `Balls.gif` in the README.
+/// With a directory as second argument every frame is also written there as a PNG image.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or("Balls.gif".to_string());
+
+    //Initialize renderer & scene
+    let mut renderer = Kiss3dRenderer::new_offscreen("Recording");
+    let mut scene = scenario("demo_many").unwrap();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.camera_position([0., 10., 0.]);
+    renderer.set_background(0.5, 0.5, 0.5);
+    renderer
+        .record_gif(&path, Duration::from_millis(42))
+        .unwrap();
+    if let Some(directory) = args.next() {
+        std::fs::create_dir_all(&directory).unwrap();
+        renderer.save_frames_to(directory);
+    }
+
+    //Draw every sixth step of 7 ms, about 24 frames per second
+    for _ in 0..150 {
+        renderer.draw(scene.objects()).unwrap();
+        for _ in 0..6 {
+            scene.update(0.007);
+        }
+    }
+    renderer.finish_gif();
+    println!("Wrote {} frames to {}", renderer.frame_count(), path);
+}
@@ -0,0 +1,30 @@
+extern crate pphysx3d;
+use pphysx3d::{
+    renderer::{software::SoftwareRenderer, Renderer},
+    scene::scenarios::scenario,
+};
+
+/// Renders the demo scene without a window, to PNG images in the directory given as argument
+fn main() {
+    let directory = std::env::args().nth(1).unwrap_or("frames".to_string());
+    std::fs::create_dir_all(&directory).unwrap();
+
+    //Initialize renderer & scene
+    let mut renderer = SoftwareRenderer::new(320, 240);
+    let mut scene = scenario("demo").unwrap();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.look_at([0.0, 2.0, -1.5], [0.0, 0.0, 8.0]);
+    renderer.set_background(0.5, 0.5, 0.5);
+    renderer.save_frames_to(&directory);
+
+    //Draw every fourth step, about 30 frames per second
+    for _ in 0..120 {
+        renderer.draw(scene.objects()).unwrap();
+        for _ in 0..4 {
+            scene.update(0.007);
+        }
+    }
+    println!("Wrote {} frames to {}", renderer.frame_count(), directory);
+}
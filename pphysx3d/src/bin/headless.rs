@@ -0,0 +1,136 @@
+//! Runs a scene without opening a window and writes the trajectories of its objects.
+//!
+//! `cargo run --bin headless -- --scenario demo --steps 500 --dt 0.007 --format jsonl --output demo.jsonl`
+extern crate pphysx3d;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    process,
+};
+
+use pphysx3d::scene::{
+    scenarios::{scenario, SCENARIOS},
+    trajectory::{TrajectoryFormat, TrajectoryWriter},
+    PhysicsScene,
+};
+
+const USAGE: &str = "Usage: headless [--scenario NAME | --scene FILE] [--steps N] [--dt SECONDS] [--format csv|jsonl] [--output FILE]
+
+Steps a physics scene without a window and writes the pose and velocity of every object after each step.
+
+Options:
+    --scenario NAME    built-in scene to run (default spheres_plane)
+    --scene FILE       scene file saved with PhysicsScene::save (needs the serde feature)
+    --steps N          number of steps (default 1000)
+    --dt SECONDS       time step (default 0.007)
+    --format FORMAT    csv or jsonl (default csv)
+    --output FILE      where to write the trajectories (default stdout)
+    --help             show this message";
+
+/// The command line options
+struct Options {
+    scenario: String,
+    scene: Option<String>,
+    steps: usize,
+    dt: f32,
+    format: TrajectoryFormat,
+    output: Option<String>,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(message) = run(&options) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+/// Parses the arguments, `None` if the help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        scenario: "spheres_plane".to_string(),
+        scene: None,
+        steps: 1000,
+        dt: 0.007,
+        format: TrajectoryFormat::Csv,
+        output: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--scenario" => options.scenario = value()?,
+            "--scene" => options.scene = Some(value()?),
+            "--steps" => {
+                options.steps = value()?
+                    .parse()
+                    .map_err(|_| "--steps must be a whole number".to_string())?
+            }
+            "--dt" => {
+                options.dt = value()?
+                    .parse()
+                    .map_err(|_| "--dt must be a number".to_string())?;
+                if !(options.dt > 0. && options.dt.is_finite()) {
+                    return Err("--dt must be positive".to_string());
+                }
+            }
+            "--format" => options.format = value()?.parse()?,
+            "--output" => options.output = Some(value()?),
+            "--help" | "-h" => return Ok(None),
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Builds the scene and writes its trajectories, starting with the initial state as frame 0
+fn run(options: &Options) -> Result<(), String> {
+    let mut scene = match &options.scene {
+        Some(path) => load(path)?,
+        None => scenario(&options.scenario).ok_or(format!(
+            "unknown scenario '{}', expected one of {}",
+            options.scenario,
+            SCENARIOS.join(", ")
+        ))?,
+    };
+    let output: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(
+            File::create(path).map_err(|error| format!("couldn't create {}: {}", path, error))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+
+    let mut writer = TrajectoryWriter::new(BufWriter::new(output), options.format);
+    let write_error = |error: io::Error| format!("couldn't write the trajectories: {}", error);
+    writer.write_frame(0, 0., &scene).map_err(write_error)?;
+    for frame in 1..=options.steps {
+        scene.update(options.dt);
+        writer
+            .write_frame(frame, frame as f32 * options.dt, &scene)
+            .map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)
+}
+
+#[cfg(feature = "serde")]
+fn load(path: &str) -> Result<PhysicsScene, String> {
+    PhysicsScene::load(path).map_err(|error| format!("couldn't load {}: {}", path, error))
+}
+
+#[cfg(not(feature = "serde"))]
+fn load(path: &str) -> Result<PhysicsScene, String> {
+    Err(format!(
+        "can't load {}, scene files need the serde feature (cargo run --features serde --bin headless)",
+        path
+    ))
+}
@@ -0,0 +1,176 @@
+use nalgebra::Point3;
+
+use crate::shapes::{
+    bounding_volume::{BoundingVolume, AABB},
+    ray::Ray,
+};
+
+/// What a node in the `Bvh` holds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BvhNodeKind {
+    /// A leaf with the index of the object it bounds
+    Leaf(usize),
+    /// An internal node with the indices of its two child nodes
+    Internal(usize, usize),
+}
+
+/// A node in the `Bvh`, its AABB contains the AABBs of all nodes below it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BvhNode {
+    pub aabb: AABB,
+    pub kind: BvhNodeKind,
+}
+
+/// Bounding Volume Hierarchy (binary AABB tree) over a set of indexed AABBs.
+/// Used by the broad phase and the scene queries to avoid testing every object.
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    // The root is at index 0 if the tree isn't empty
+    nodes: Vec<BvhNode>,
+}
+
+impl Bvh {
+    /// Creates an empty tree
+    pub fn new() -> Bvh {
+        Bvh { nodes: Vec::new() }
+    }
+
+    /// Builds a tree top-down from `(index, AABB)` pairs, splitting at the median along the longest axis
+    pub fn build(mut leaves: Vec<(usize, AABB)>) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * leaves.len()),
+        };
+        if !leaves.is_empty() {
+            bvh.build_node(&mut leaves);
+        }
+        bvh
+    }
+
+    /// Recursively builds the node for `leaves` and returns its index
+    fn build_node(&mut self, leaves: &mut [(usize, AABB)]) -> usize {
+        let aabb = leaves[1..]
+            .iter()
+            .fold(leaves[0].1, |aabb, (_, other)| aabb.merged(other));
+        let node_index = self.nodes.len();
+
+        if leaves.len() == 1 {
+            self.nodes.push(BvhNode {
+                aabb,
+                kind: BvhNodeKind::Leaf(leaves[0].0),
+            });
+            return node_index;
+        }
+
+        // Reserve this node's place, the children are filled in after they're built
+        self.nodes.push(BvhNode {
+            aabb,
+            kind: BvhNodeKind::Internal(0, 0),
+        });
+
+        // Split along the axis where the centers are the most spread out
+        let (mins, maxs) = leaves.iter().fold(
+            (leaves[0].1.center(), leaves[0].1.center()),
+            |(mins, maxs), (_, aabb)| (mins.inf(&aabb.center()), maxs.sup(&aabb.center())),
+        );
+        let extents = maxs - mins;
+        let axis = extents.imax();
+        // Ties are broken by index so the tree doesn't depend on the sorting algorithm
+        leaves.sort_by(|(index_a, a), (index_b, b)| {
+            a.center()[axis]
+                .partial_cmp(&b.center()[axis])
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(index_a.cmp(index_b))
+        });
+
+        let (left, right) = leaves.split_at_mut(leaves.len() / 2);
+        let left = self.build_node(left);
+        let right = self.build_node(right);
+        self.nodes[node_index].kind = BvhNodeKind::Internal(left, right);
+        node_index
+    }
+
+    /// All nodes of the tree, the root being the first one
+    pub fn nodes(&self) -> &[BvhNode] {
+        &self.nodes
+    }
+
+    /// Returns the indices of all leaves whose AABB passes `test`, pruning subtrees that don't
+    fn traverse(&self, test: impl Fn(&AABB) -> bool) -> Vec<usize> {
+        let mut result = Vec::new();
+        if self.nodes.is_empty() {
+            return result;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !test(&node.aabb) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf(index) => result.push(index),
+                BvhNodeKind::Internal(left, right) => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+        result
+    }
+
+    /// Indices of all leaves whose AABB intersects `aabb`
+    pub fn intersect_aabb(&self, aabb: &AABB) -> Vec<usize> {
+        self.traverse(|node_aabb| node_aabb.interects(aabb))
+    }
+
+    /// Indices of all leaves whose AABB contains `point`
+    pub fn intersect_point(&self, point: &Point3<f32>) -> Vec<usize> {
+        self.traverse(|node_aabb| node_aabb.contains_point(point))
+    }
+
+    /// Indices of all leaves whose AABB is hit by `ray` within `max_toi`
+    pub fn intersect_ray(&self, ray: &Ray, max_toi: f32) -> Vec<usize> {
+        self.traverse(|node_aabb| node_aabb.cast_ray(ray, max_toi).is_some())
+    }
+
+    /// All pairs of leaves with intersecting AABBs, as `(lower index, higher index)` sorted in ascending order
+    pub fn collision_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        if let Some(BvhNodeKind::Internal(left, right)) = self.nodes.first().map(|node| node.kind) {
+            self.self_pairs(left, &mut pairs);
+            self.self_pairs(right, &mut pairs);
+            self.pairs_between(left, right, &mut pairs);
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// Collects the intersecting pairs within the subtree at `node`
+    fn self_pairs(&self, node: usize, pairs: &mut Vec<(usize, usize)>) {
+        if let BvhNodeKind::Internal(left, right) = self.nodes[node].kind {
+            self.self_pairs(left, pairs);
+            self.self_pairs(right, pairs);
+            self.pairs_between(left, right, pairs);
+        }
+    }
+
+    /// Collects the intersecting pairs with one leaf below `node_a` and the other below `node_b`
+    fn pairs_between(&self, node_a: usize, node_b: usize, pairs: &mut Vec<(usize, usize)>) {
+        let (a, b) = (&self.nodes[node_a], &self.nodes[node_b]);
+        if !a.aabb.interects(&b.aabb) {
+            return;
+        }
+        match (a.kind, b.kind) {
+            (BvhNodeKind::Leaf(index_a), BvhNodeKind::Leaf(index_b)) => {
+                pairs.push((index_a.min(index_b), index_a.max(index_b)));
+            }
+            (BvhNodeKind::Internal(left, right), _) => {
+                self.pairs_between(left, node_b, pairs);
+                self.pairs_between(right, node_b, pairs);
+            }
+            (_, BvhNodeKind::Internal(left, right)) => {
+                self.pairs_between(node_a, left, pairs);
+                self.pairs_between(node_a, right, pairs);
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+
+use crate::shapes::{
+    capsule::Capsule, plane::Plane, shape::Shape, sphere::Sphere, utils::closest_points_segments,
+};
+
+/// Separation between two shapes together with their closest points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceResult {
+    /// Signed distance between the shapes, negative when they overlap
+    pub distance: f32,
+    /// The point on the first shape closest to the second shape (deepest point when overlapping)
+    pub point_1: Point3<f32>,
+    /// The point on the second shape closest to the first shape (deepest point when overlapping)
+    pub point_2: Point3<f32>,
+    /// Direction from the first shape towards the second shape
+    pub normal: UnitVector3<f32>,
+}
+
+impl DistanceResult {
+    /// True if the shapes touch or overlap
+    pub fn is_overlapping(&self) -> bool {
+        self.distance <= 0.0
+    }
+
+    /// How deep the shapes overlap, 0 if they are separated
+    pub fn penetration_depth(&self) -> f32 {
+        (-self.distance).max(0.0)
+    }
+
+    /// The same result with the roles of the two shapes swapped
+    pub fn flipped(&self) -> DistanceResult {
+        DistanceResult {
+            distance: self.distance,
+            point_1: self.point_2,
+            point_2: self.point_1,
+            normal: -self.normal,
+        }
+    }
+}
+
+/// Computes the distance and closest points between two shapes.
+/// Planes are treated as solid half-spaces behind their normal.
+/// Returns `None` if the pair of shapes isn't supported.
+pub fn distance(
+    shape_1: &dyn Shape,
+    iso_1: &Isometry3<f32>,
+    shape_2: &dyn Shape,
+    iso_2: &Isometry3<f32>,
+) -> Option<DistanceResult> {
+    match (rounded_segment(shape_1, iso_1), rounded_segment(shape_2, iso_2)) {
+        (Some(segment_1), Some(segment_2)) => Some(rounded_segments_distance(segment_1, segment_2)),
+        (Some(segment), None) => {
+            let plane = shape_2.as_plane()?;
+            Some(rounded_segment_plane_distance(segment, plane, iso_2))
+        }
+        (None, Some(segment)) => {
+            let plane = shape_1.as_plane()?;
+            Some(rounded_segment_plane_distance(segment, plane, iso_1).flipped())
+        }
+        (None, None) => None,
+    }
+}
+
+/// Spheres and capsules are both all points within a radius of a segment (a sphere's segment being a single point).
+/// Returns the segment's end points and the radius, or `None` for other shapes.
+fn rounded_segment(
+    shape: &dyn Shape,
+    iso: &Isometry3<f32>,
+) -> Option<(Point3<f32>, Point3<f32>, f32)> {
+    if let Some(sphere) = shape.as_sphere() {
+        let center = Point3::from(iso.translation.vector);
+        Some((center, center, sphere.radius))
+    } else if let Some(capsule) = shape.as_capsule() {
+        let (a, b) = capsule.segment(iso);
+        Some((a, b, capsule.radius))
+    } else {
+        None
+    }
+}
+
+/// Distance between two rounded segments, from their closest inner points
+fn rounded_segments_distance(
+    (a_1, b_1, radius_1): (Point3<f32>, Point3<f32>, f32),
+    (a_2, b_2, radius_2): (Point3<f32>, Point3<f32>, f32),
+) -> DistanceResult {
+    let (center_1, center_2) = closest_points_segments(&a_1, &b_1, &a_2, &b_2);
+    ball_ball_distance(&center_1, radius_1, &center_2, radius_2)
+}
+
+/// Distance between a rounded segment and a plane, from the end point deepest towards the plane
+fn rounded_segment_plane_distance(
+    (a, b, radius): (Point3<f32>, Point3<f32>, f32),
+    plane: &Plane,
+    iso_p: &Isometry3<f32>,
+) -> DistanceResult {
+    let plane_normal: UnitVector3<f32> = iso_p.rotation * plane.normal();
+    let center = if plane_normal.dot(&(b - a)) < 0.0 { b } else { a };
+    ball_plane_distance(&center, radius, &plane_normal, iso_p)
+}
+
+/// Distance between two spheres given by their centers and radiuses
+fn ball_ball_distance(
+    center_1: &Point3<f32>,
+    radius_1: f32,
+    center_2: &Point3<f32>,
+    radius_2: f32,
+) -> DistanceResult {
+    let difference: Vector3<f32> = center_2 - center_1;
+    // Concentric spheres have no preferred direction, so any will do
+    let normal = UnitVector3::try_new(difference, f32::EPSILON)
+        .unwrap_or_else(|| UnitVector3::new_normalize(Vector3::y()));
+
+    DistanceResult {
+        distance: difference.norm() - radius_1 - radius_2,
+        point_1: center_1 + normal.scale(radius_1),
+        point_2: center_2 - normal.scale(radius_2),
+        normal,
+    }
+}
+
+/// Distance between a sphere given by its center and radius and a plane with world space normal `plane_normal`
+fn ball_plane_distance(
+    center: &Point3<f32>,
+    radius: f32,
+    plane_normal: &UnitVector3<f32>,
+    iso_p: &Isometry3<f32>,
+) -> DistanceResult {
+    // Signed distance from the plane to the sphere center
+    let center_distance = plane_normal.dot(&(center.coords - iso_p.translation.vector));
+
+    DistanceResult {
+        distance: center_distance - radius,
+        point_1: center - plane_normal.scale(radius),
+        point_2: center - plane_normal.scale(center_distance),
+        normal: -*plane_normal,
+    }
+}
+
+/// Distance between two spheres
+pub fn sphere_sphere_distance(
+    sphere_1: &Sphere,
+    iso_1: &Isometry3<f32>,
+    sphere_2: &Sphere,
+    iso_2: &Isometry3<f32>,
+) -> DistanceResult {
+    ball_ball_distance(
+        &Point3::from(iso_1.translation.vector),
+        sphere_1.radius,
+        &Point3::from(iso_2.translation.vector),
+        sphere_2.radius,
+    )
+}
+
+/// Distance between a sphere and a plane
+pub fn sphere_plane_distance(
+    sphere: &Sphere,
+    iso_s: &Isometry3<f32>,
+    plane: &Plane,
+    iso_p: &Isometry3<f32>,
+) -> DistanceResult {
+    let plane_normal: UnitVector3<f32> = iso_p.rotation * plane.normal();
+    ball_plane_distance(
+        &Point3::from(iso_s.translation.vector),
+        sphere.radius,
+        &plane_normal,
+        iso_p,
+    )
+}
+
+/// Distance between two capsules
+pub fn capsule_capsule_distance(
+    capsule_1: &Capsule,
+    iso_1: &Isometry3<f32>,
+    capsule_2: &Capsule,
+    iso_2: &Isometry3<f32>,
+) -> DistanceResult {
+    let (a_1, b_1) = capsule_1.segment(iso_1);
+    let (a_2, b_2) = capsule_2.segment(iso_2);
+    rounded_segments_distance((a_1, b_1, capsule_1.radius), (a_2, b_2, capsule_2.radius))
+}
@@ -1,11 +1,15 @@
-use kiss3d::nalgebra::{Isometry3, Point3, Unit, UnitVector3, Vector3};
+use nalgebra::{Isometry3, Point3, Unit, UnitVector3, Vector3};
 
-use crate::shapes::{plane::Plane, sphere::Sphere};
+use crate::shapes::{bounding_volume::AABB, plane::Plane, sphere::Sphere};
+use distance::DistanceResult;
 
+pub mod bvh;
+pub mod distance;
 mod tests;
+pub mod time_of_impact;
 
 /// Collision check for two spheres with given translation
-fn sphere_sphere(
+pub(crate) fn sphere_sphere(
     sphere_a: &Sphere,
     sphere_b: &Sphere,
     iso_a: &Isometry3<f32>,
@@ -23,26 +27,27 @@ fn sphere_sphere(
     }
 }
 
-/// Collision check for a sphere and a plane
-fn sphere_plane(
-    sphere: &Sphere,
-    plane: &Plane,
-    iso_s: &Isometry3<f32>,
-    iso_p: &Isometry3<f32>,
-) -> bool {
-    // Find the closest point (thank god for linalg)
-    let normal: UnitVector3<f32> = iso_s.rotation * plane.normal();
-    let dist_to_center = iso_p.translation.vector - iso_s.translation.vector;
-    // project the distance vector onto the normal
-    let proj: Vector3<f32> = normal.scale(normal.dot(&dist_to_center));
-    let dist_squared = proj.norm_squared();
-    if dist_squared <= sphere.radius * sphere.radius {
-        return true;
-    } else {
-        return false;
-    }
+/// Overlap check for a sphere and an axis-aligned box
+pub(crate) fn aabb_sphere(aabb: &AABB, sphere: &Sphere, iso_s: &Isometry3<f32>) -> bool {
+    // The point in the box closest to the sphere center
+    let center = Point3::from(iso_s.translation.vector);
+    let closest = center.sup(&aabb.mins()).inf(&aabb.maxs());
+    (center - closest).norm_squared() <= sphere.radius * sphere.radius
 }
-#[derive(Debug, PartialEq)]
+
+/// Overlap check for a plane, as a solid half-space behind its normal, and an axis-aligned box
+pub(crate) fn aabb_plane(aabb: &AABB, plane: &Plane, iso_p: &Isometry3<f32>) -> bool {
+    let normal: UnitVector3<f32> = iso_p.rotation * plane.normal();
+    let center = aabb.center();
+    let half_extents: Vector3<f32> = aabb.maxs() - center;
+    // Radius of the box projected onto the plane normal
+    let projected_radius = half_extents.dot(&normal.abs());
+    let distance = normal.dot(&(center.coords - iso_p.translation.vector));
+    // The plane is a solid half-space, so boxes entirely behind it overlap it too
+    distance <= projected_radius
+}
+
+#[derive(Debug, Clone, PartialEq)]
 /// Contains the necessary information to resolve a coliision
 pub struct CollisionManifold {
     pub colliding: bool,
@@ -51,6 +56,12 @@ pub struct CollisionManifold {
     pub contacts: Vec<Point3<f32>>,
 }
 
+impl Default for CollisionManifold {
+    fn default() -> Self {
+        CollisionManifold::new()
+    }
+}
+
 impl CollisionManifold {
     pub fn new() -> CollisionManifold {
         CollisionManifold {
@@ -61,6 +72,23 @@ impl CollisionManifold {
         }
     }
 
+    /// Creates the collision manifold for two shapes from the distance between them.
+    /// Like the other manifolds the normal points from the second shape towards the first one.
+    pub fn from_distance(result: &DistanceResult) -> CollisionManifold {
+        let mut manifold = CollisionManifold::new();
+        if !result.is_overlapping() {
+            return manifold;
+        }
+        manifold.colliding = true;
+        manifold.normal = -result.normal;
+        manifold.depth = result.penetration_depth();
+        // Halfway between the deepest points of both shapes
+        manifold
+            .contacts
+            .push(Point3::from((result.point_1.coords + result.point_2.coords) / 2.0));
+        manifold
+    }
+
     /// Calculates the collision manifold between two spheres
     pub fn sphere_sphere(
         sphere_a: &Sphere,
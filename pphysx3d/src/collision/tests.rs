@@ -1,8 +1,11 @@
 use crate::{
-    collision::{sphere_plane, sphere_sphere, CollisionManifold},
-    shapes::{plane::Plane, sphere::Sphere},
+    collision::{
+        bvh::Bvh, distance::distance, sphere_sphere, time_of_impact::time_of_impact,
+        CollisionManifold,
+    },
+    shapes::{bounding_volume::AABB, capsule::Capsule, plane::Plane, sphere::Sphere},
 };
-use kiss3d::nalgebra::{
+use nalgebra::{
     Isometry3, Point, Point3, Rotation3, Translation3, UnitQuaternion, UnitVector3, Vector3,
 };
 
@@ -46,41 +49,6 @@ fn sphere_manifold() {
     assert_eq!(facit, result);
 }
 
-#[test]
-fn sphere_plane_collision_check() {
-    let mut sphere = Sphere::new(2.1f32);
-    let plane = Plane::new(UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
-    let iso_s = Isometry3::from_parts(
-        Translation3::new(0f32, 0f32, 0f32),
-        UnitQuaternion::new(Vector3::y() * std::f32::consts::FRAC_PI_2),
-    );
-    let mut iso_p = Isometry3::from_parts(
-        Translation3::new(0f32, 2f32, 0f32),
-        UnitQuaternion::new(Vector3::y() * std::f32::consts::TAU),
-    );
-    let result = sphere_plane(&sphere, &plane, &iso_s, &iso_p);
-    assert_eq!(result, true);
-
-    sphere.radius = 1.9;
-    let result = sphere_plane(&sphere, &plane, &iso_s, &iso_p);
-    assert_eq!(result, false);
-
-    iso_p = Isometry3::from_parts(
-        Translation3::new(0f32, 0f32, 0f32),
-        UnitQuaternion::new(Vector3::y() * std::f32::consts::FRAC_2_PI),
-    );
-
-    let result = sphere_plane(&sphere, &plane, &iso_s, &iso_p);
-    assert_eq!(result, true);
-
-    iso_p = Isometry3::from_parts(
-        Translation3::new(0f32, 2f32, 0f32),
-        UnitQuaternion::new(Vector3::y() * std::f32::consts::PI),
-    );
-    let result = sphere_plane(&sphere, &plane, &iso_s, &iso_p);
-    assert_eq!(result, false);
-}
-
 #[test]
 fn sphere_plane_manifold() {
     let sphere = Sphere::new(2.1f32);
@@ -105,3 +73,202 @@ fn sphere_plane_manifold() {
     };
     assert_eq!(test, result);
 }
+
+#[test]
+fn bvh_collision_pairs() {
+    // A row of unit boxes where only neighbours touch
+    let leaves = (0..8)
+        .map(|i| {
+            let x = i as f32 * 1.5;
+            (
+                i,
+                AABB::new(Point3::new(x, 0., 0.), Point3::new(x + 1.6, 1., 1.)),
+            )
+        })
+        .collect();
+    let bvh = Bvh::build(leaves);
+    let pairs = bvh.collision_pairs();
+    let expected: Vec<(usize, usize)> = (0..7).map(|i| (i, i + 1)).collect();
+    assert_eq!(pairs, expected);
+
+    let mut hits = bvh.intersect_point(&Point3::new(3.05, 0.5, 0.5));
+    hits.sort_unstable();
+    assert_eq!(hits, vec![1, 2]);
+
+    assert!(Bvh::build(vec![]).collision_pairs().is_empty());
+}
+
+#[test]
+fn sphere_sphere_time_of_impact() {
+    let sphere_1 = Sphere::new(1.0);
+    let sphere_2 = Sphere::new(0.5);
+    let iso_1 = Isometry3::translation(0.0, 0.0, 0.0);
+    let iso_2 = Isometry3::translation(5.0, 0.0, 0.0);
+
+    // Both moving towards each other, closing 3.5 units at 1 + 1 units per second
+    let hit = time_of_impact(
+        &sphere_1,
+        &iso_1,
+        &Vector3::new(1.0, 0.0, 0.0),
+        &sphere_2,
+        &iso_2,
+        &Vector3::new(-1.0, 0.0, 0.0),
+        10.0,
+    )
+    .unwrap();
+    assert_eq!(hit.toi, 1.75);
+    assert_eq!(hit.contact_point, Point3::new(2.75, 0.0, 0.0));
+    assert_eq!(hit.normal, UnitVector3::new_normalize(Vector3::new(-1.0, 0.0, 0.0)));
+
+    // Not reached in time
+    let miss = time_of_impact(
+        &sphere_1,
+        &iso_1,
+        &Vector3::new(1.0, 0.0, 0.0),
+        &sphere_2,
+        &iso_2,
+        &Vector3::zeros(),
+        3.0,
+    );
+    assert_eq!(miss, None);
+
+    // Moving away
+    let miss = time_of_impact(
+        &sphere_1,
+        &iso_1,
+        &Vector3::new(-1.0, 0.0, 0.0),
+        &sphere_2,
+        &iso_2,
+        &Vector3::zeros(),
+        10.0,
+    );
+    assert_eq!(miss, None);
+}
+
+#[test]
+fn sphere_plane_time_of_impact() {
+    let sphere = Sphere::new(1.0);
+    let plane = Plane::new(UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+    let iso_s = Isometry3::translation(0.0, 5.0, 0.0);
+    let iso_p = Isometry3::translation(0.0, 0.0, 0.0);
+    let falling = Vector3::new(1.0, -2.0, 0.0);
+
+    let hit = time_of_impact(&sphere, &iso_s, &falling, &plane, &iso_p, &Vector3::zeros(), 10.0)
+        .unwrap();
+    assert_eq!(hit.toi, 2.0);
+    assert_eq!(hit.contact_point, Point3::new(2.0, 0.0, 0.0));
+    assert_eq!(hit.normal, UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+
+    // The plane rising towards a still sphere hits the same place, seen from the plane
+    let rising = -falling;
+    let hit = time_of_impact(&plane, &iso_p, &rising, &sphere, &iso_s, &Vector3::zeros(), 10.0)
+        .unwrap();
+    assert_eq!(hit.toi, 2.0);
+    assert_eq!(hit.contact_point, Point3::new(0.0, 4.0, 0.0));
+    assert_eq!(hit.normal, UnitVector3::new_normalize(Vector3::new(0.0, -1.0, 0.0)));
+}
+
+#[test]
+fn sphere_sphere_distance() {
+    let sphere_1 = Sphere::new(1.0);
+    let sphere_2 = Sphere::new(2.0);
+    let iso_1 = Isometry3::translation(0.0, 0.0, 0.0);
+    let iso_2 = Isometry3::translation(0.0, 0.0, 5.0);
+
+    let result = distance(&sphere_1, &iso_1, &sphere_2, &iso_2).unwrap();
+    assert_eq!(result.distance, 2.0);
+    assert_eq!(result.point_1, Point3::new(0.0, 0.0, 1.0));
+    assert_eq!(result.point_2, Point3::new(0.0, 0.0, 3.0));
+    assert_eq!(result.normal, UnitVector3::new_normalize(Vector3::new(0.0, 0.0, 1.0)));
+    assert!(!result.is_overlapping());
+
+    let iso_2 = Isometry3::translation(0.0, 0.0, 2.5);
+    let result = distance(&sphere_1, &iso_1, &sphere_2, &iso_2).unwrap();
+    assert!(result.is_overlapping());
+    assert_eq!(result.penetration_depth(), 0.5);
+}
+
+#[test]
+fn sphere_plane_distance() {
+    let sphere = Sphere::new(1.0);
+    let plane = Plane::new(UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+    let iso_s = Isometry3::translation(3.0, 4.0, 0.0);
+    let iso_p = Isometry3::translation(0.0, 1.0, 0.0);
+
+    let result = distance(&sphere, &iso_s, &plane, &iso_p).unwrap();
+    assert_eq!(result.distance, 2.0);
+    assert_eq!(result.point_1, Point3::new(3.0, 3.0, 0.0));
+    assert_eq!(result.point_2, Point3::new(3.0, 1.0, 0.0));
+
+    // Swapping the shapes swaps the witness points and flips the normal
+    let swapped = distance(&plane, &iso_p, &sphere, &iso_s).unwrap();
+    assert_eq!(swapped, result.flipped());
+    assert_eq!(swapped.normal, UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+
+    // Sinking below the surface
+    let iso_s = Isometry3::translation(3.0, 0.5, 0.0);
+    let result = distance(&sphere, &iso_s, &plane, &iso_p).unwrap();
+    assert_eq!(result.penetration_depth(), 1.5);
+
+    assert_eq!(distance(&plane, &iso_p, &plane, &iso_p), None);
+}
+
+#[test]
+fn capsule_distance() {
+    let capsule = Capsule::new(1.0, 0.5);
+    let upright = Isometry3::translation(0.0, 0.0, 0.0);
+
+    // A sphere beside the cylinder part
+    let sphere = Sphere::new(1.0);
+    let result = distance(&capsule, &upright, &sphere, &Isometry3::translation(3.0, 0.5, 0.0)).unwrap();
+    assert_eq!(result.distance, 1.5);
+    assert_eq!(result.point_1, Point3::new(0.5, 0.5, 0.0));
+
+    // Standing on a plane, the lower end decides
+    let plane = Plane::new(UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+    let result = distance(&capsule, &upright, &plane, &Isometry3::translation(0.0, -2.0, 0.0)).unwrap();
+    assert_eq!(result.distance, 0.5);
+    assert_eq!(result.point_1, Point3::new(0.0, -1.5, 0.0));
+
+    // Two capsules crossing each other at right angles
+    let lying = Isometry3::new(
+        Vector3::new(0.0, 0.0, 2.5),
+        Vector3::x() * std::f32::consts::FRAC_PI_2,
+    );
+    let result = distance(&capsule, &upright, &capsule, &lying).unwrap();
+    assert!((result.distance - 0.5).abs() < 1.0e-5);
+    assert!(!result.is_overlapping());
+}
+
+#[test]
+fn capsule_time_of_impact() {
+    let capsule = Capsule::new(1.0, 0.5);
+    let sphere = Sphere::new(0.5);
+    let iso_c = Isometry3::translation(0.0, 0.0, 0.0);
+    let iso_s = Isometry3::translation(4.0, 0.8, 0.0);
+
+    // Solved by conservative advancement
+    let hit = time_of_impact(
+        &capsule,
+        &iso_c,
+        &Vector3::new(1.0, 0.0, 0.0),
+        &sphere,
+        &iso_s,
+        &Vector3::zeros(),
+        10.0,
+    )
+    .unwrap();
+    assert!((hit.toi - 3.0).abs() < 1.0e-3);
+    assert!((hit.contact_point - Point3::new(3.5, 0.8, 0.0)).norm() < 1.0e-3);
+
+    let miss = time_of_impact(
+        &capsule,
+        &iso_c,
+        &Vector3::new(0.0, 0.0, 1.0),
+        &sphere,
+        &iso_s,
+        &Vector3::zeros(),
+        10.0,
+    );
+    assert_eq!(miss, None);
+}
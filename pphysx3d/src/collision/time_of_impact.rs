@@ -0,0 +1,183 @@
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+
+use super::distance::{distance, DistanceResult};
+use crate::shapes::{plane::Plane, ray::Ray, raycast::RayCast, shape::Shape, sphere::Sphere};
+
+/// Distance at which conservative advancement considers two shapes touching
+const TOI_TOLERANCE: f32 = 1.0e-4;
+/// Maximum number of steps taken by conservative advancement before giving up on converging
+const TOI_MAX_ITERATIONS: usize = 64;
+
+/// The first contact found when sweeping one shape against another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeCastHit {
+    /// Time until impact, the shapes have moved `velocity * toi` when they touch
+    pub toi: f32,
+    /// Contact point in world space at the time of impact
+    pub contact_point: Point3<f32>,
+    /// Contact normal pointing from the second shape towards the first one
+    pub normal: UnitVector3<f32>,
+}
+
+/// Computes the first time of impact between two shapes moving with constant linear velocities.
+/// Spheres against spheres and planes are solved exactly, other pairs supported by `distance` by conservative advancement.
+/// Returns `None` if they don't touch within `max_toi` or if the pair of shapes isn't supported.
+/// Shapes that already overlap give a time of impact of 0.
+pub fn time_of_impact(
+    shape_1: &dyn Shape,
+    iso_1: &Isometry3<f32>,
+    velocity_1: &Vector3<f32>,
+    shape_2: &dyn Shape,
+    iso_2: &Isometry3<f32>,
+    velocity_2: &Vector3<f32>,
+    max_toi: f32,
+) -> Option<ShapeCastHit> {
+    // Work in the frame of the second shape, where only the first one moves
+    let relative_velocity = velocity_1 - velocity_2;
+
+    let hit = if let (Some(sphere_1), Some(sphere_2)) = (shape_1.as_sphere(), shape_2.as_sphere()) {
+        sphere_sphere_toi(
+            sphere_1,
+            iso_1,
+            sphere_2,
+            iso_2,
+            &relative_velocity,
+            max_toi,
+        )
+    } else if let (Some(sphere), Some(plane)) = (shape_1.as_sphere(), shape_2.as_plane()) {
+        sphere_plane_toi(sphere, iso_1, plane, iso_2, &relative_velocity, max_toi)
+    } else if let (Some(plane), Some(sphere)) = (shape_1.as_plane(), shape_2.as_sphere()) {
+        // Same as a sphere moving the opposite way towards the plane, seen from the other side
+        sphere_plane_toi(sphere, iso_2, plane, iso_1, &-relative_velocity, max_toi).map(|hit| {
+            ShapeCastHit {
+                normal: -hit.normal,
+                // The contact point was computed with the plane standing still, move it to the sphere's frame
+                contact_point: hit.contact_point + relative_velocity * hit.toi,
+                ..hit
+            }
+        })
+    } else {
+        conservative_advancement(shape_1, iso_1, shape_2, iso_2, &relative_velocity, max_toi)
+    }?;
+
+    // Move the contact point back out of the second shape's frame
+    Some(ShapeCastHit {
+        contact_point: hit.contact_point + velocity_2 * hit.toi,
+        ..hit
+    })
+}
+
+/// Time of impact of a sphere moving with `velocity` towards a still sphere
+fn sphere_sphere_toi(
+    sphere_1: &Sphere,
+    iso_1: &Isometry3<f32>,
+    sphere_2: &Sphere,
+    iso_2: &Isometry3<f32>,
+    velocity: &Vector3<f32>,
+    max_toi: f32,
+) -> Option<ShapeCastHit> {
+    let center_1 = Point3::from(iso_1.translation.vector);
+    let center_2 = Point3::from(iso_2.translation.vector);
+    let radiuses = sphere_1.radius + sphere_2.radius;
+    let distance: Vector3<f32> = center_1 - center_2;
+
+    // Already overlapping
+    if distance.norm_squared() <= radiuses * radiuses {
+        let normal = UnitVector3::try_new(distance, f32::EPSILON)
+            .unwrap_or_else(|| UnitVector3::new_normalize(Vector3::y()));
+        return Some(ShapeCastHit {
+            toi: 0.0,
+            contact_point: center_2 + normal.scale(sphere_2.radius),
+            normal,
+        });
+    }
+
+    // Cast the center of the first sphere against the second sphere inflated by the first radius
+    let speed = velocity.norm();
+    if speed <= f32::EPSILON {
+        return None;
+    }
+    let ray = Ray::new(center_1, UnitVector3::new_normalize(*velocity));
+    let result = Sphere::new(radiuses).ray_cast(iso_2, &ray);
+    let toi = result.distance / speed;
+    if !result.hit || toi > max_toi {
+        return None;
+    }
+    Some(ShapeCastHit {
+        toi,
+        contact_point: center_2 + result.normal.scale(sphere_2.radius),
+        normal: result.normal,
+    })
+}
+
+/// Time of impact of a sphere moving with `velocity` towards the front of a still plane
+fn sphere_plane_toi(
+    sphere: &Sphere,
+    iso_s: &Isometry3<f32>,
+    plane: &Plane,
+    iso_p: &Isometry3<f32>,
+    velocity: &Vector3<f32>,
+    max_toi: f32,
+) -> Option<ShapeCastHit> {
+    let normal: UnitVector3<f32> = iso_p.rotation * plane.normal();
+    let center = Point3::from(iso_s.translation.vector);
+    // Signed distance from the plane to the sphere's surface
+    let distance = normal.dot(&(center.coords - iso_p.translation.vector)) - sphere.radius;
+
+    let toi = if distance <= 0.0 {
+        0.0
+    } else {
+        let approach_speed = -normal.dot(velocity);
+        if approach_speed <= f32::EPSILON {
+            return None;
+        }
+        distance / approach_speed
+    };
+    if toi > max_toi {
+        return None;
+    }
+    Some(ShapeCastHit {
+        toi,
+        contact_point: center + velocity * toi - normal.scale(sphere.radius),
+        normal,
+    })
+}
+
+/// Time of impact of any shape pair supported by `distance`, the first shape moving with `velocity` and the second standing still.
+/// Repeatedly moves the first shape forward by the time it certainly needs to close the current distance.
+fn conservative_advancement(
+    shape_1: &dyn Shape,
+    iso_1: &Isometry3<f32>,
+    shape_2: &dyn Shape,
+    iso_2: &Isometry3<f32>,
+    velocity: &Vector3<f32>,
+    max_toi: f32,
+) -> Option<ShapeCastHit> {
+    let hit = |toi: f32, result: &DistanceResult| ShapeCastHit {
+        toi,
+        contact_point: result.point_2,
+        normal: -result.normal,
+    };
+
+    let mut toi = 0.0;
+    let mut iso = *iso_1;
+    let mut result = distance(shape_1, &iso, shape_2, iso_2)?;
+    for _ in 0..TOI_MAX_ITERATIONS {
+        if result.distance <= TOI_TOLERANCE {
+            return Some(hit(toi, &result));
+        }
+        // The shapes are convex, so the distance can't shrink faster than the approach along the closest direction
+        let approach_speed = velocity.dot(&result.normal);
+        if approach_speed <= f32::EPSILON {
+            return None;
+        }
+        toi += result.distance / approach_speed;
+        if toi > max_toi {
+            return None;
+        }
+        iso.translation.vector = iso_1.translation.vector + velocity * toi;
+        result = distance(shape_1, &iso, shape_2, iso_2)?;
+    }
+    // Close enough to count as touching even if it didn't fully converge
+    Some(hit(toi, &result))
+}
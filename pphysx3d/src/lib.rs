@@ -1,6 +1,8 @@
+#[cfg(feature = "kiss3d")]
 extern crate kiss3d;
+extern crate nalgebra;
 
-mod collision;
+pub mod collision;
 pub mod renderer;
 pub mod scene;
-mod shapes;
+pub mod shapes;
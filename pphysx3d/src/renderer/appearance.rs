@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use nalgebra::{Isometry3, Point2, Point3, UnitQuaternion, UnitVector3, Vector3};
+
+use crate::shapes::shape::Shape;
+
+/// The share of each square of a grid covered by its lines
+const GRID_LINE_WIDTH: f32 = 1. / 16.;
+
+/// A pattern drawn over an object instead of its plain colour, laid out in squares `size` metres wide:
+/// flat on planes and wrapped around spheres and capsules.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Texture {
+    /// Lines in `color` between squares of the object's colour, to judge distances and depth on planes
+    Grid { size: f32, color: [u8; 3] },
+    /// Squares alternating between the object's colour and `color`
+    Checker { size: f32, color: [u8; 3] },
+    /// A copy of the image at `path` in each square. Only Kiss3dRenderer draws images,
+    /// the other renderers draw the object's colour.
+    Image { path: PathBuf, size: f32 },
+}
+
+impl Texture {
+    /// The width of the squares [m]
+    pub fn size(&self) -> f32 {
+        match self {
+            Texture::Grid { size, .. }
+            | Texture::Checker { size, .. }
+            | Texture::Image { size, .. } => *size,
+        }
+    }
+
+    /// The colour at `point` of the surface of an object with `color`, in metres as given by `surface_coordinates`
+    pub fn color_at(&self, color: [u8; 3], point: &Point2<f32>) -> [u8; 3] {
+        self.color_in_square(color, point.x / self.size(), point.y / self.size())
+    }
+
+    /// The colour at (`u`, `v`) counted in squares
+    pub(crate) fn color_in_square(&self, color: [u8; 3], u: f32, v: f32) -> [u8; 3] {
+        match self {
+            Texture::Grid {
+                color: line_color, ..
+            } => {
+                let on_line = u - u.floor() < GRID_LINE_WIDTH || v - v.floor() < GRID_LINE_WIDTH;
+                if on_line {
+                    *line_color
+                } else {
+                    color
+                }
+            }
+            Texture::Checker {
+                color: other_color, ..
+            } => {
+                if (u.floor() + v.floor()).rem_euclid(2.) == 0. {
+                    color
+                } else {
+                    *other_color
+                }
+            }
+            Texture::Image { .. } => color,
+        }
+    }
+}
+
+/// The error for a texture that can't be drawn
+pub(crate) fn check_texture(texture: &Texture) -> Result<(), String> {
+    let size = texture.size();
+    if !(size > 0. && size.is_finite()) {
+        return Err(format!(
+            "Error: The squares of a texture must be wider than 0 m, not {}",
+            size
+        ));
+    }
+    Ok(())
+}
+
+/// How a renderer shades an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMaterial {
+    /// Lit by the light, the default
+    #[default]
+    Shaded,
+    /// Coloured by the direction the surface faces, red for x, green for y and blue for z,
+    /// to see the shape without lighting
+    Normals,
+    /// Only the edges of the triangles it's made of. The SoftwareRenderer, which has no triangles, draws it shaded.
+    Wireframe,
+}
+
+/// The rotation turning the z axis to `normal`, for drawing a plane with a flat shape in the xy plane
+pub fn plane_rotation(normal: &UnitVector3<f32>) -> UnitQuaternion<f32> {
+    UnitQuaternion::rotation_between_axis(&Vector3::z_axis(), normal).unwrap_or_else(|| {
+        // The normal is the negative z axis, any half turn around an axis in the plane works
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+    })
+}
+
+/// Where `point` on the surface of `shape` at `position` is for a texture, in metres:
+/// along the plane for planes, and around and along the y axis for spheres and capsules
+pub fn surface_coordinates(
+    shape: &dyn Shape,
+    position: &Isometry3<f32>,
+    point: &Point3<f32>,
+) -> Point2<f32> {
+    let local = position.inverse_transform_point(point);
+    if let Some(plane) = shape.as_plane() {
+        let in_plane = plane_rotation(plane.normal()).inverse_transform_point(&local);
+        Point2::new(in_plane.x, in_plane.y)
+    } else if let Some(sphere) = shape.as_sphere() {
+        let latitude = (local.y / sphere.radius).clamp(-1., 1.).asin();
+        Point2::new(
+            local.x.atan2(local.z) * sphere.radius,
+            latitude * sphere.radius,
+        )
+    } else if let Some(capsule) = shape.as_capsule() {
+        Point2::new(local.x.atan2(local.z) * capsule.radius, local.y)
+    } else {
+        Point2::new(local.x, local.y)
+    }
+}
@@ -0,0 +1,73 @@
+use nalgebra::{Point3, Vector3};
+
+use crate::scene::{BodyHandle, PhysicsScene};
+
+/// How the camera of a renderer moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    /// Flies freely, moved with the keys and turned with the mouse
+    FirstPerson,
+    /// Turns around the point it looks at when dragged with the left mouse button,
+    /// moves it with the right button and zooms with the scroll wheel
+    Orbit,
+    /// Follows a body, without any input
+    Follow(FollowCamera),
+    /// Stays where it's put, without any input
+    Fixed,
+}
+
+impl CameraMode {
+    /// The mode after this one when switching through them: first person, orbit, following `selected` and fixed.
+    /// Following is skipped if nothing is selected.
+    pub fn next(&self, selected: Option<BodyHandle>) -> CameraMode {
+        match (self, selected) {
+            (CameraMode::FirstPerson, _) => CameraMode::Orbit,
+            (CameraMode::Orbit, Some(body)) => CameraMode::Follow(FollowCamera::new(body)),
+            (CameraMode::Orbit, None) | (CameraMode::Follow(_), _) => CameraMode::Fixed,
+            (CameraMode::Fixed, _) => CameraMode::FirstPerson,
+        }
+    }
+}
+
+/// A camera looking at the centre of mass of a body from a fixed offset, catching up with it smoothly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowCamera {
+    pub body: BodyHandle,
+    /// Where the camera is relative to the body's centre of mass [m]
+    pub offset: Vector3<f32>,
+    /// The time the camera takes to move about two thirds of the way to where it should be [s],
+    /// 0 follows the body rigidly
+    pub smoothing: f32,
+}
+
+impl FollowCamera {
+    /// Follow `body` from above and behind, with 0.2 s smoothing
+    pub fn new(body: BodyHandle) -> FollowCamera {
+        FollowCamera {
+            body,
+            offset: Vector3::new(0., 2., -6.),
+            smoothing: 0.2,
+        }
+    }
+
+    /// Where the camera and the point it looks at move from `eye` and `at` in `elapsed` seconds,
+    /// `None` if the body isn't in `scene`
+    pub fn follow(
+        &self,
+        scene: &PhysicsScene,
+        eye: &Point3<f32>,
+        at: &Point3<f32>,
+        elapsed: f32,
+    ) -> Option<(Point3<f32>, Point3<f32>)> {
+        let center = scene.get(self.body)?.center_of_mass();
+        let t = if self.smoothing > 0. {
+            1. - (-elapsed / self.smoothing).exp()
+        } else {
+            1.
+        };
+        Some((
+            eye + (center + self.offset - eye) * t,
+            at + (center - at) * t,
+        ))
+    }
+}
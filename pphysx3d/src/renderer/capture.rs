@@ -0,0 +1,110 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, DynamicImage, Frame, RgbImage,
+};
+
+/// The speed of the colour quantization of GIF frames, from 1 (best colours) to 30 (fastest)
+const GIF_SPEED: i32 = 10;
+
+/// Writes the frames of a renderer to PNG files and GIF animations
+pub struct FrameCapture {
+    frame_count: usize,
+    directory: Option<PathBuf>,
+    gif: Option<GifEncoder<BufWriter<File>>>,
+    gif_delay: Delay,
+}
+
+impl FrameCapture {
+    pub fn new() -> FrameCapture {
+        FrameCapture {
+            frame_count: 0,
+            directory: None,
+            gif: None,
+            gif_delay: Delay::from_numer_denom_ms(0, 1),
+        }
+    }
+
+    /// The number of frames given to `add_frame` so far, saved or not
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Write every following frame to `directory` as `frame_00000.png`, `frame_00001.png`...
+    /// numbered by `frame_count`. The directory must exist.
+    pub fn save_frames_to(&mut self, directory: impl Into<PathBuf>) {
+        self.directory = Some(directory.into());
+    }
+
+    /// Stop writing frames to a directory
+    pub fn stop_saving_frames(&mut self) {
+        self.directory = None;
+    }
+
+    /// Write every following frame to a looping GIF animation at `path`, each shown for `frame_delay`,
+    /// until `finish_gif`. Finishes the GIF being recorded, if any.
+    pub fn record_gif(
+        &mut self,
+        path: impl AsRef<Path>,
+        frame_delay: Duration,
+    ) -> Result<(), String> {
+        self.finish_gif();
+        let path = path.as_ref();
+        let error = |error: &dyn std::fmt::Display| {
+            format!("Error: Couldn't write {}: {}", path.display(), error)
+        };
+        let file = File::create(path).map_err(|e| error(&e))?;
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), GIF_SPEED);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| error(&e))?;
+        self.gif = Some(encoder);
+        self.gif_delay = Delay::from_saturating_duration(frame_delay);
+        Ok(())
+    }
+
+    /// Finish the GIF being recorded, writing its end to the file
+    pub fn finish_gif(&mut self) {
+        // The encoder writes the end of the file when dropped
+        self.gif = None;
+    }
+
+    /// Whether frames are written anywhere, so they must be given to `add_frame`
+    pub fn is_capturing(&self) -> bool {
+        self.directory.is_some() || self.gif.is_some()
+    }
+
+    /// Write `frame` wherever frames are captured, and count it
+    pub fn add_frame(&mut self, frame: &RgbImage) -> Result<(), String> {
+        if let Some(directory) = &self.directory {
+            let path = directory.join(format!("frame_{:05}.png", self.frame_count));
+            frame
+                .save(&path)
+                .map_err(|error| format!("Error: Couldn't write {}: {}", path.display(), error))?;
+        }
+        if let Some(gif) = &mut self.gif {
+            let rgba = DynamicImage::ImageRgb8(frame.clone()).into_rgba8();
+            gif.encode_frame(Frame::from_parts(rgba, 0, 0, self.gif_delay))
+                .map_err(|error| format!("Error: Couldn't write a GIF frame: {}", error))?;
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Count a frame that isn't captured
+    pub fn skip_frame(&mut self) {
+        self.frame_count += 1;
+    }
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
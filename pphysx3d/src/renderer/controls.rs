@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use crate::scene::PhysicsScene;
+
+/// The slowest and fastest the simulation can be run, as a fraction of real time
+const MIN_TIME_SCALE: f32 = 1. / 32.;
+const MAX_TIME_SCALE: f32 = 4.;
+
+/// Decides how far a viewer steps the simulation each frame: paused, one step at a time, in slow motion or faster.
+/// Slow motion steps with a shorter time step, so it also makes the simulation more accurate.
+/// Running faster keeps the time step and takes several steps per frame, so it's as accurate as normal speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationControls {
+    /// The time step at normal speed [s]
+    pub time_step: f32,
+    paused: bool,
+    time_scale: f32,
+    // A single step to take while paused
+    step_requested: bool,
+}
+
+impl SimulationControls {
+    /// Running at normal speed with `time_step`
+    pub fn new(time_step: f32) -> SimulationControls {
+        SimulationControls {
+            time_step,
+            paused: false,
+            time_scale: 1.,
+            step_requested: false,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.step_requested = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    /// Pause, and take one step at the next `next_steps`
+    pub fn step_once(&mut self) {
+        self.paused = true;
+        self.step_requested = true;
+    }
+
+    /// How fast the simulation runs, 1 is normal speed and 0.5 half of it
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Set the time scale, kept between 1/32 and 4
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+
+    /// Run half as fast
+    pub fn slower(&mut self) {
+        self.set_time_scale(self.time_scale / 2.);
+    }
+
+    /// Run twice as fast
+    pub fn faster(&mut self) {
+        self.set_time_scale(self.time_scale * 2.);
+    }
+
+    /// The time step to update the scene with this frame and how many times, `None` if it shouldn't be updated.
+    /// Above normal speed the time step stays the same and the time scale, rounded, is the number of steps.
+    pub fn next_steps(&mut self) -> Option<(f32, usize)> {
+        if self.paused && !self.step_requested {
+            return None;
+        }
+        if self.step_requested {
+            self.step_requested = false;
+            return Some((self.time_step * self.time_scale.min(1.), 1));
+        }
+        if self.time_scale > 1. {
+            Some((self.time_step, self.time_scale.round() as usize))
+        } else {
+            Some((self.time_step * self.time_scale, 1))
+        }
+    }
+
+    /// Lines of text telling the state of the controls and `scene`,
+    /// with `step_time` the time the last update took to compute
+    pub fn status(&self, scene: &PhysicsScene, step_time: Duration) -> String {
+        let state = if self.paused { "paused" } else { "running" };
+        format!(
+            "{} at {}x\n\
+             step: {:.2} ms\n\
+             bodies: {}\n\
+             contacts: {}",
+            state,
+            self.time_scale,
+            step_time.as_secs_f64() * 1000.,
+            scene.objects().len(),
+            scene.contacts().len(),
+        )
+    }
+}
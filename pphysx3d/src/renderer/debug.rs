@@ -0,0 +1,197 @@
+use nalgebra::{Point3, Vector3};
+
+use super::Renderer;
+use crate::{
+    collision::bvh::BvhNodeKind,
+    scene::{game_object::GameObject, mouse_joint::MouseJoint, BodyHandle, PhysicsScene},
+    shapes::bounding_volume::AABB,
+};
+
+const AABB_COLOR: [u8; 3] = [0, 255, 0];
+const BOUNDING_SPHERE_COLOR: [u8; 3] = [0, 160, 255];
+const CONTACT_COLOR: [u8; 3] = [255, 0, 0];
+const VELOCITY_COLOR: [u8; 3] = [255, 255, 0];
+const ANGULAR_VELOCITY_COLOR: [u8; 3] = [255, 128, 0];
+const BVH_LEAF_COLOR: [u8; 3] = [255, 0, 255];
+const BVH_NODE_COLOR: [u8; 3] = [128, 0, 255];
+const JOINT_COLOR: [u8; 3] = [255, 255, 255];
+/// Length of the drawn contact normals [m]
+const CONTACT_NORMAL_LENGTH: f32 = 0.5;
+/// Number of lines in each circle of a bounding sphere
+const CIRCLE_SEGMENTS: usize = 24;
+
+/// Which internals of a PhysicsScene to draw on top of it, everything is off by default.
+/// Infinite volumes, like the AABBs of planes, are left out.
+/// Bodies never sleep, so there is no sleep state to colour them by.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DebugOverlay {
+    /// The AABB of every collider, in green
+    pub aabbs: bool,
+    /// The bounding sphere of every collider as three circles, in light blue
+    pub bounding_spheres: bool,
+    /// The contact points and normals found in the last update, in red
+    pub contacts: bool,
+    /// The velocity of every object from its centre of mass in yellow, and its angular velocity in orange
+    pub velocities: bool,
+    /// The nodes of the tree used by the broad phase and the queries, leaves in magenta and the others in purple
+    pub bvh: bool,
+    /// The anchor of every joint given to `draw_with_joints` and the line to its target, in white
+    pub joints: bool,
+}
+
+impl DebugOverlay {
+    /// The overlay with everything turned on
+    pub fn all() -> DebugOverlay {
+        DebugOverlay {
+            aabbs: true,
+            bounding_spheres: true,
+            contacts: true,
+            velocities: true,
+            bvh: true,
+            joints: true,
+        }
+    }
+
+    /// True if anything is turned on
+    pub fn is_enabled(&self) -> bool {
+        *self != DebugOverlay::default()
+    }
+
+    /// Give `renderer` the debug primitives for the turned on parts of `scene`, they're drawn by its next `draw`
+    pub fn draw(&self, renderer: &mut dyn Renderer, scene: &PhysicsScene) {
+        self.draw_with_joints(renderer, scene, &[]);
+    }
+
+    /// Like `draw`, also drawing the `joints` pulling objects of `scene`.
+    /// Joints aren't part of the scene, so whoever applies them passes them here.
+    pub fn draw_with_joints(
+        &self,
+        renderer: &mut dyn Renderer,
+        scene: &PhysicsScene,
+        joints: &[MouseJoint],
+    ) {
+        for object in scene.objects() {
+            for collider in object.colliders() {
+                let position = collider.world_position(&object.position);
+                if self.aabbs {
+                    draw_finite_aabb(
+                        renderer,
+                        &collider.shape().compute_aabb(&position),
+                        AABB_COLOR,
+                    );
+                }
+                if self.bounding_spheres {
+                    let sphere = collider.shape().compute_bounding_sphere(&position);
+                    draw_sphere(
+                        renderer,
+                        &sphere.center(),
+                        sphere.radius(),
+                        BOUNDING_SPHERE_COLOR,
+                    );
+                }
+            }
+            // Static objects never move
+            if self.velocities && object.is_dynamic() {
+                let center = object.center_of_mass();
+                renderer.draw_arrow(&center, &object.velocity, VELOCITY_COLOR);
+                renderer.draw_arrow(&center, &object.angular_velocity, ANGULAR_VELOCITY_COLOR);
+            }
+        }
+
+        if self.contacts {
+            for (_, _, manifold) in scene.contacts() {
+                for contact in &manifold.contacts {
+                    renderer.draw_point(contact, CONTACT_COLOR);
+                    renderer.draw_arrow(
+                        contact,
+                        &manifold.normal.scale(CONTACT_NORMAL_LENGTH),
+                        CONTACT_COLOR,
+                    );
+                }
+            }
+        }
+
+        if self.bvh {
+            for node in scene.bvh().nodes() {
+                let color = match node.kind {
+                    BvhNodeKind::Leaf(_) => BVH_LEAF_COLOR,
+                    BvhNodeKind::Internal(_, _) => BVH_NODE_COLOR,
+                };
+                draw_finite_aabb(renderer, &node.aabb, color);
+            }
+        }
+
+        if self.joints {
+            for joint in joints {
+                // Joints of removed objects pull nothing
+                if let Some(anchor) = joint.anchor(scene) {
+                    renderer.draw_point(&anchor, JOINT_COLOR);
+                    renderer.draw_line(&anchor, &joint.target, JOINT_COLOR);
+                }
+            }
+        }
+    }
+}
+
+/// The properties of an object as lines of text, to show for a selected object
+pub fn object_properties(handle: BodyHandle, object: &GameObject) -> String {
+    let vector = |v: &Vector3<f32>| format!("({:.2}, {:.2}, {:.2})", v.x, v.y, v.z);
+    let (axis, angle) = object
+        .position
+        .rotation
+        .axis_angle()
+        .map_or((Vector3::y(), 0.), |(axis, angle)| {
+            (axis.into_inner(), angle)
+        });
+    format!(
+        "Object {} ({:?}, {} colliders)\n\
+         mass: {:.2} kg\n\
+         position: {}\n\
+         rotation: {:.1}° around {}\n\
+         velocity: {} m/s\n\
+         angular velocity: {} rad/s",
+        handle.0,
+        object.body_type(),
+        object.colliders().len(),
+        object.mass(),
+        vector(&object.position.translation.vector),
+        angle.to_degrees(),
+        vector(&axis),
+        vector(&object.velocity),
+        vector(&object.angular_velocity),
+    )
+}
+
+/// Planes have huge AABBs and bounding spheres that can't be drawn
+fn is_drawable(value: f32) -> bool {
+    value.abs() < f32::MAX / 4.
+}
+
+pub(crate) fn draw_finite_aabb(renderer: &mut dyn Renderer, aabb: &AABB, color: [u8; 3]) {
+    if aabb
+        .mins()
+        .iter()
+        .chain(aabb.maxs().iter())
+        .all(|c| is_drawable(*c))
+    {
+        renderer.draw_aabb(aabb, color);
+    }
+}
+
+/// A circle around each axis
+fn draw_sphere(renderer: &mut dyn Renderer, center: &Point3<f32>, radius: f32, color: [u8; 3]) {
+    if !is_drawable(radius) {
+        return;
+    }
+    let axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+    for i in 0..3 {
+        let (u, v) = (axes[(i + 1) % 3], axes[(i + 2) % 3]);
+        let point = |segment: usize| {
+            let angle = segment as f32 * std::f32::consts::TAU / CIRCLE_SEGMENTS as f32;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        };
+        for segment in 0..CIRCLE_SEGMENTS {
+            renderer.draw_line(&point(segment), &point(segment + 1), color);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use kiss3d::{
+    camera::{ArcBall, Camera, FirstPerson},
+    event::WindowEvent,
+    nalgebra::{Isometry3, Matrix4, Point3},
+    resource::ShaderUniform,
+    window::Canvas,
+};
+
+use super::camera::CameraMode;
+
+/// The distance to the point the orbit camera turns around when switching to it from the first person camera [m]
+const ORBIT_DISTANCE: f32 = 5.;
+
+/// The cameras of every mode of a Kiss3dRenderer, acting as the one of the current mode.
+/// They're all kept, so each keeps its settings when switching back and forth.
+pub(crate) struct Cameras {
+    mode: CameraMode,
+    pub first_person: FirstPerson,
+    orbit: ArcBall,
+    // The camera of the modes without input, moved from code
+    scripted: ArcBall,
+}
+
+impl Cameras {
+    /// Starting in first person mode with `first_person`
+    pub fn new(first_person: FirstPerson) -> Cameras {
+        let (eye, at) = (first_person.eye(), first_person.at());
+        let mut scripted = ArcBall::new(eye, at);
+        scripted.rebind_rotate_button(None);
+        scripted.rebind_drag_button(None);
+        scripted.rebind_reset_key(None);
+        scripted.set_dist_step(0.);
+        Cameras {
+            mode: CameraMode::FirstPerson,
+            first_person,
+            orbit: ArcBall::new(eye, at),
+            scripted,
+        }
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    /// Switch to `mode`, keeping the view of the current camera
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        let (eye, mut at) = self.view();
+        if matches!(mode, CameraMode::Orbit) && (at - eye).norm() < ORBIT_DISTANCE {
+            // The first person camera looks at a point right in front of it
+            at = eye + (at - eye).normalize() * ORBIT_DISTANCE;
+        }
+        self.mode = mode;
+        self.look_at(eye, at);
+    }
+
+    /// The position of the current camera and the point it looks at
+    pub fn view(&self) -> (Point3<f32>, Point3<f32>) {
+        match self.mode {
+            CameraMode::FirstPerson => (self.first_person.eye(), self.first_person.at()),
+            CameraMode::Orbit => (self.orbit.eye(), self.orbit.at()),
+            CameraMode::Follow(_) | CameraMode::Fixed => (self.scripted.eye(), self.scripted.at()),
+        }
+    }
+
+    /// Move the current camera to `eye`, looking at `at`
+    pub fn look_at(&mut self, eye: Point3<f32>, at: Point3<f32>) {
+        match self.mode {
+            CameraMode::FirstPerson => self.first_person.look_at(eye, at),
+            CameraMode::Orbit => self.orbit.look_at(eye, at),
+            CameraMode::Follow(_) | CameraMode::Fixed => self.scripted.look_at(eye, at),
+        }
+    }
+
+    fn active(&self) -> &dyn Camera {
+        match self.mode {
+            CameraMode::FirstPerson => &self.first_person,
+            CameraMode::Orbit => &self.orbit,
+            CameraMode::Follow(_) | CameraMode::Fixed => &self.scripted,
+        }
+    }
+
+    fn active_mut(&mut self) -> &mut dyn Camera {
+        match self.mode {
+            CameraMode::FirstPerson => &mut self.first_person,
+            CameraMode::Orbit => &mut self.orbit,
+            CameraMode::Follow(_) | CameraMode::Fixed => &mut self.scripted,
+        }
+    }
+}
+
+impl Camera for Cameras {
+    fn handle_event(&mut self, canvas: &Canvas, event: &WindowEvent) {
+        if let WindowEvent::FramebufferSize(..) = event {
+            // Every camera needs the new aspect ratio, not only the current one
+            self.first_person.handle_event(canvas, event);
+            self.orbit.handle_event(canvas, event);
+            self.scripted.handle_event(canvas, event);
+        } else {
+            self.active_mut().handle_event(canvas, event);
+        }
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.active().eye()
+    }
+
+    fn view_transform(&self) -> Isometry3<f32> {
+        self.active().view_transform()
+    }
+
+    fn transformation(&self) -> Matrix4<f32> {
+        self.active().transformation()
+    }
+
+    fn inverse_transformation(&self) -> Matrix4<f32> {
+        self.active().inverse_transformation()
+    }
+
+    fn clip_planes(&self) -> (f32, f32) {
+        self.active().clip_planes()
+    }
+
+    fn update(&mut self, canvas: &Canvas) {
+        self.active_mut().update(canvas);
+    }
+
+    fn upload(
+        &self,
+        pass: usize,
+        proj: &mut ShaderUniform<Matrix4<f32>>,
+        view: &mut ShaderUniform<Matrix4<f32>>,
+    ) {
+        self.active().upload(pass, proj, view);
+    }
+}
@@ -0,0 +1,631 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use image::RgbImage;
+use kiss3d::{
+    camera::{Camera, FirstPerson},
+    event::{Action, Key, Modifiers, MouseButton, WindowEvent},
+    nalgebra::{Point2, Point3, UnitVector3, Vector2, Vector3},
+    scene::SceneNode,
+    text::Font,
+    window::Window,
+};
+
+use super::{
+    appearance::{check_texture, plane_rotation, RenderMaterial, Texture},
+    camera::CameraMode,
+    capture::FrameCapture,
+    check_index,
+    debug::{draw_finite_aabb, object_properties, DebugOverlay},
+    kiss3d_camera::Cameras,
+    kiss3d_texture::{add_textured_plane, wrapped_texture},
+    Renderer, PLANE_SIZE,
+};
+use crate::{
+    scene::{game_object::GameObject, mouse_joint::MouseJoint, BodyHandle, PhysicsScene},
+    shapes::{ray::Ray, shape::Shape},
+};
+
+/// How far away objects can be picked [m]
+const PICK_DISTANCE: f32 = 1000.;
+const SELECTION_COLOR: [u8; 3] = [255, 255, 255];
+const JOINT_COLOR: [u8; 3] = [255, 255, 0];
+const PROPERTIES_COLOR: [u8; 3] = [255, 255, 0];
+/// Height of text drawn on the window [px]
+pub(crate) const TEXT_SIZE: f32 = 40.;
+/// The longest time the follow camera catches up for between two frames [s]
+const MAX_FOLLOW_TIME: f32 = 0.1;
+
+/// Renderer struct for using Kiss3D.
+/// Adding and removing objects must be done in sync with Scene.
+///
+/// `draw_scene` can also draw the internals of the scene with a `DebugOverlay`, toggled with the number keys:
+/// 1 AABBs, 2 bounding spheres, 3 contacts, 4 velocities, 5 the BVH, 6 the mouse joint and 0 everything.
+/// Holding control and clicking an object with the left mouse button selects it and shows its properties,
+/// dragging moves dynamic objects with a `MouseJoint` that must be applied to the scene, see `mouse_joint`.
+/// The C key switches between the camera modes: first person, orbit, following the selected object and fixed.
+///
+/// The frames drawn can be saved as PNG images and GIF animations, also without showing them with `new_offscreen`.
+pub struct Kiss3dRenderer {
+    window: Window,
+    camera: Cameras,
+    // Objects must be in same order as in Scene
+    renderables: Vec<SceneNode>,
+    // How each renderable looks, to make it the same way again when its shape is replaced
+    looks: Vec<Look>,
+    // The images of the image textures, by their path
+    images: HashMap<PathBuf, RgbImage>,
+    // The width of the squares planes are drawn as
+    plane_size: f32,
+    debug_overlay: DebugOverlay,
+    selected: Option<BodyHandle>,
+    mouse_joint: Option<MouseJoint>,
+    // How far in front of the camera the dragged point is kept
+    drag_distance: f32,
+    // The keys pressed since the last frame
+    pressed_keys: Vec<Key>,
+    // When draw_scene was last called, to move the follow camera as far as the time since
+    last_draw: Option<Instant>,
+    capture: FrameCapture,
+}
+
+impl Kiss3dRenderer {
+    /// Add basic rendering window with size and title and free moving
+    /// Camera using move_step 0.1 & wasd-keys as well as
+    /// rotation and movement holding left/right mouse button
+    pub fn new(title: &str, width: u32, height: u32) -> Kiss3dRenderer {
+        Kiss3dRenderer::with_window(Window::new_with_size(title, width, height))
+    }
+
+    /// A renderer drawing in a hidden window, for saving frames without showing them.
+    /// The frames are 800x600, the size Kiss3D gives hidden windows,
+    /// and some platforms need a display to make the window even though it isn't shown.
+    pub fn new_offscreen(title: &str) -> Kiss3dRenderer {
+        Kiss3dRenderer::with_window(Window::new_hidden(title))
+    }
+
+    fn with_window(mut window: Window) -> Kiss3dRenderer {
+        let mut camera = FirstPerson::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.3, 0.2, 1.0));
+
+        camera.set_move_step(0.1);
+        camera.rebind_left_key(Some(Key::A));
+        camera.rebind_right_key(Some(Key::D));
+        camera.rebind_up_key(Some(Key::W));
+        camera.rebind_down_key(Some(Key::S));
+
+        window.set_light(kiss3d::light::Light::StickToCamera);
+        Kiss3dRenderer {
+            window,
+            camera: Cameras::new(camera),
+            renderables: Vec::new(),
+            looks: Vec::new(),
+            images: HashMap::new(),
+            plane_size: PLANE_SIZE,
+            debug_overlay: DebugOverlay::default(),
+            selected: None,
+            mouse_joint: None,
+            drag_distance: 0.,
+            pressed_keys: Vec::new(),
+            last_draw: None,
+            capture: FrameCapture::new(),
+        }
+    }
+
+    ///Set the position of the camera
+    pub fn camera_position(&mut self, position: [f32; 3]) {
+        let (_, at) = self.camera.view();
+        self.camera.look_at(Point3::from(position), at);
+    }
+
+    ///Set the view direction of the camera
+    pub fn camera_direction(&mut self, direction: [f32; 3]) {
+        let (eye, _) = self.camera.view();
+        self.camera.look_at(eye, eye + Vector3::from(direction))
+    }
+
+    /// Move the camera to `eye`, looking at `at`. The orbit camera turns around `at`.
+    pub fn look_at(&mut self, eye: [f32; 3], at: [f32; 3]) {
+        self.camera.look_at(Point3::from(eye), Point3::from(at));
+    }
+
+    pub fn camera_mode(&self) -> CameraMode {
+        self.camera.mode()
+    }
+
+    /// Switch the camera to `mode`, starting from the current view.
+    /// The follow camera is only moved by `draw_scene`, and switches to fixed if its body is removed.
+    pub fn set_camera_mode(&mut self, mode: CameraMode) {
+        self.camera.set_mode(mode);
+    }
+
+    ///Change keybindings and speed of the first person camera
+    pub fn set_camera_movement(
+        &mut self,
+        move_step: f32,
+        up: Key,
+        down: Key,
+        left: Key,
+        right: Key,
+    ) {
+        let camera = &mut self.camera.first_person;
+        camera.unbind_movement_keys();
+        camera.set_move_step(move_step);
+        camera.rebind_left_key(Some(left));
+        camera.rebind_right_key(Some(right));
+        camera.rebind_up_key(Some(up));
+        camera.rebind_down_key(Some(down));
+    }
+    ///Change the distance the first person camera moves with a single key press
+    pub fn change_camera_speed(&mut self, move_step: f32) {
+        self.camera.first_person.set_move_step(move_step);
+    }
+
+    /// The number of frames drawn so far
+    pub fn frame_count(&self) -> usize {
+        self.capture.frame_count()
+    }
+
+    /// The last frame drawn
+    pub fn snapshot(&self) -> RgbImage {
+        self.window.snap_image()
+    }
+
+    /// Write the last frame drawn to a PNG file
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        self.snapshot()
+            .save(path)
+            .map_err(|error| format!("Error: Couldn't write {}: {}", path.display(), error))
+    }
+
+    /// Write every following frame to `directory` as `frame_00000.png`, `frame_00001.png`...
+    /// numbered by `frame_count`. The directory must exist.
+    pub fn save_frames_to(&mut self, directory: impl Into<PathBuf>) {
+        self.capture.save_frames_to(directory);
+    }
+
+    /// Stop writing frames to the directory given to `save_frames_to`
+    pub fn stop_saving_frames(&mut self) {
+        self.capture.stop_saving_frames();
+    }
+
+    /// Write every following frame to a looping GIF animation at `path`, each shown for `frame_delay`,
+    /// until `finish_gif` or the renderer is dropped. GIFs only have 256 colours, so they're a bit off.
+    pub fn record_gif(
+        &mut self,
+        path: impl AsRef<Path>,
+        frame_delay: Duration,
+    ) -> Result<(), String> {
+        self.capture.record_gif(path, frame_delay)
+    }
+
+    /// Finish the GIF started with `record_gif`
+    pub fn finish_gif(&mut self) {
+        self.capture.finish_gif();
+    }
+
+    /// Draw planes as squares `size` metres wide instead of `PLANE_SIZE`, from the next `draw`
+    pub fn set_plane_size(&mut self, size: f32) {
+        self.plane_size = size;
+        for look in &mut self.looks {
+            look.outdated = true;
+        }
+    }
+
+    ///Change the background colour
+    pub fn set_background(&mut self, r: f32, g: f32, b: f32) {
+        self.window.set_background_color(r, g, b);
+    }
+
+    ///Set the one global light source allowed by kiss3d to a point
+    pub fn set_point_light_source(&mut self, point: [f32; 3]) {
+        self.window
+            .set_light(kiss3d::light::Light::Absolute(Point3::from(point)));
+    }
+    ///Set the one global light source allowed by kiss3d to follow the camera.
+    pub fn set_light_to_camera(&mut self) {
+        self.window.set_light(kiss3d::light::Light::StickToCamera);
+    }
+
+    /// What `draw_scene` draws on top of the scene, to change it from code instead of with the keys
+    pub fn debug_overlay(&mut self) -> &mut DebugOverlay {
+        &mut self.debug_overlay
+    }
+
+    /// The object selected by clicking it, if it's still in the scene
+    pub fn selected(&self) -> Option<BodyHandle> {
+        self.selected
+    }
+
+    /// The joint dragging the selected object while the mouse button is held.
+    /// Apply it before every update: `if let Some(joint) = renderer.mouse_joint() { joint.apply(&mut scene, time_step) }`
+    pub fn mouse_joint(&self) -> Option<&MouseJoint> {
+        self.mouse_joint.as_ref()
+    }
+
+    /// The ray from the camera through the mouse cursor, `None` if the cursor isn't over the window
+    pub fn cursor_ray(&self) -> Option<Ray> {
+        let (x, y) = self.window.cursor_pos()?;
+        Some(self.ray_through(x, y))
+    }
+
+    /// The ray from the camera along the direction it looks in
+    pub fn view_ray(&self) -> Ray {
+        let (eye, at) = self.camera.view();
+        Ray::new(eye, UnitVector3::new_normalize(at - eye))
+    }
+
+    /// The size of the window in pixels, as [width, height]
+    pub fn window_size(&self) -> [u32; 2] {
+        let size = self.window.size();
+        [size.x, size.y]
+    }
+
+    /// Draw lines of `text` in the next frame, with the top left corner at `position` in pixels from the top left of the window
+    pub fn draw_text(&mut self, text: &str, position: [f32; 2], color: [u8; 3]) {
+        self.window.draw_text(
+            text,
+            &Point2::from(position),
+            TEXT_SIZE,
+            &Font::default(),
+            &Point3::from(to_float(color)),
+        );
+    }
+
+    /// The keys pressed before the last `draw_scene`, including those it handled itself
+    pub fn pressed_keys(&self) -> &[Key] {
+        &self.pressed_keys
+    }
+
+    /// Draw the objects of `scene` like `draw`, with the parts of the debug overlay that are turned on
+    /// and the selected object's box and properties
+    pub fn draw_scene(&mut self, scene: &PhysicsScene) -> Result<(), String> {
+        self.handle_events(scene);
+        self.follow(scene);
+        let (overlay, joint) = (self.debug_overlay, self.mouse_joint);
+        overlay.draw_with_joints(self, scene, joint.as_slice());
+        self.draw_selection(scene);
+        self.draw(scene.objects())
+    }
+
+    /// Toggles the debug overlay, switches the camera mode and picks and drags objects. Handled events don't reach the camera.
+    fn handle_events(&mut self, scene: &PhysicsScene) {
+        self.pressed_keys.clear();
+        for mut event in self.window.events().iter() {
+            match event.value {
+                WindowEvent::Key(Key::C, Action::Press, _) => {
+                    self.pressed_keys.push(Key::C);
+                    self.set_camera_mode(self.camera_mode().next(self.selected));
+                }
+                WindowEvent::Key(key, Action::Press, _) => {
+                    self.pressed_keys.push(key);
+                    let overlay = &mut self.debug_overlay;
+                    match key {
+                        Key::Key1 => overlay.aabbs = !overlay.aabbs,
+                        Key::Key2 => overlay.bounding_spheres = !overlay.bounding_spheres,
+                        Key::Key3 => overlay.contacts = !overlay.contacts,
+                        Key::Key4 => overlay.velocities = !overlay.velocities,
+                        Key::Key5 => overlay.bvh = !overlay.bvh,
+                        Key::Key6 => overlay.joints = !overlay.joints,
+                        Key::Key0 if overlay.is_enabled() => *overlay = DebugOverlay::default(),
+                        Key::Key0 => *overlay = DebugOverlay::all(),
+                        _ => {}
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, modifiers)
+                    if modifiers.contains(Modifiers::Control) =>
+                {
+                    event.inhibited = true;
+                    self.mouse_joint = None;
+                    self.selected = None;
+                    let ray = match self.window.cursor_pos() {
+                        Some((x, y)) => self.ray_through(x, y),
+                        None => continue,
+                    };
+                    if let Some((handle, hit)) = scene.cast_ray(&ray, PICK_DISTANCE, None) {
+                        self.selected = Some(handle);
+                        if scene.get(handle).is_some_and(|object| object.is_dynamic()) {
+                            self.mouse_joint = MouseJoint::new(scene, handle, hit.contact_point);
+                            self.drag_distance = hit.distance;
+                        }
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _)
+                    if self.mouse_joint.is_some() =>
+                {
+                    event.inhibited = true;
+                    self.mouse_joint = None;
+                }
+                WindowEvent::CursorPos(x, y, _) if self.mouse_joint.is_some() => {
+                    // Keep the camera still while dragging
+                    event.inhibited = true;
+                    let ray = self.ray_through(x, y);
+                    if let Some(joint) = &mut self.mouse_joint {
+                        joint.target = ray.point_at(self.drag_distance);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // The selected object may have been removed
+        if self
+            .selected
+            .is_some_and(|handle| scene.get(handle).is_none())
+        {
+            self.selected = None;
+            self.mouse_joint = None;
+        }
+    }
+
+    /// Move the follow camera towards its body, or keep it where it is if the body is gone
+    fn follow(&mut self, scene: &PhysicsScene) {
+        let elapsed = self
+            .last_draw
+            .map_or(0., |last_draw| last_draw.elapsed().as_secs_f32())
+            .min(MAX_FOLLOW_TIME);
+        self.last_draw = Some(Instant::now());
+        if let CameraMode::Follow(follow) = self.camera_mode() {
+            let (eye, at) = self.camera.view();
+            match follow.follow(scene, &eye, &at, elapsed) {
+                Some((eye, at)) => self.camera.look_at(eye, at),
+                None => self.set_camera_mode(CameraMode::Fixed),
+            }
+        }
+    }
+
+    /// The box around the selected object, the line it's dragged along and its properties
+    fn draw_selection(&mut self, scene: &PhysicsScene) {
+        let (handle, object) = match self.selected.and_then(|h| scene.get(h).map(|o| (h, o))) {
+            Some(selected) => selected,
+            None => return,
+        };
+        for collider in object.colliders() {
+            let aabb = collider
+                .shape()
+                .compute_aabb(&collider.world_position(&object.position));
+            draw_finite_aabb(self, &aabb, SELECTION_COLOR);
+        }
+        if let Some(joint) = self.mouse_joint {
+            if let Some(anchor) = joint.anchor(scene) {
+                self.draw_line(&anchor, &joint.target, JOINT_COLOR);
+                self.draw_point(&joint.target, JOINT_COLOR);
+            }
+        }
+        self.draw_text(
+            &object_properties(handle, object),
+            [10., 10.],
+            PROPERTIES_COLOR,
+        );
+    }
+
+    /// The ray from the camera through the point (`x`, `y`) in the window, in pixels from the top left
+    fn ray_through(&self, x: f64, y: f64) -> Ray {
+        let size = self.window.size();
+        let (origin, direction) = self.camera.unproject(
+            &Point2::new(x as f32, y as f32),
+            &Vector2::new(size.x as f32, size.y as f32),
+        );
+        Ray::new(origin, UnitVector3::new_normalize(direction))
+    }
+
+    /// One node for the object, moved with its body, holding a node for each collider at its offset
+    fn node_from_object(&mut self, object: &GameObject, look: &Look) -> SceneNode {
+        let mut node = self.window.add_group();
+        for collider in object.colliders() {
+            let mut collider_node = self.node_from_shape(&mut node, collider.shape(), look);
+            collider_node.set_local_transformation(collider.position);
+        }
+        // Textures have the colour in them
+        let [r, g, b] = match look.texture {
+            Some(_) => [1.; 3],
+            None => to_float(look.color),
+        };
+        node.set_color(r, g, b);
+        match look.material {
+            RenderMaterial::Shaded => {}
+            RenderMaterial::Normals => node.set_material_with_name("normals"),
+            RenderMaterial::Wireframe => {
+                node.set_surface_rendering_activation(false);
+                node.set_lines_width(1.);
+            }
+        }
+        node
+    }
+
+    ///Match a Shape with a Scene Node to render, added as a child of `parent`
+    fn node_from_shape(&self, parent: &mut SceneNode, shape: &dyn Shape, look: &Look) -> SceneNode {
+        let image = match &look.texture {
+            Some(Texture::Image { path, .. }) => self.images.get(path),
+            _ => None,
+        };
+        if let Some(sphere) = shape.as_sphere() {
+            let mut node = parent.add_sphere(sphere.radius);
+            if let Some(texture) = &look.texture {
+                let (around, along) = (TAU * sphere.radius, PI * sphere.radius);
+                node.set_texture(wrapped_texture(texture, look.color, around, along, image));
+            }
+            node
+        } else if let Some(plane) = shape.as_plane() {
+            // The group is placed by the collider, the square in it turned to face along the normal
+            let mut g = parent.add_group();
+            let mut p = match &look.texture {
+                Some(texture) => {
+                    add_textured_plane(&mut g, self.plane_size, texture, look.color, image)
+                }
+                None => g.add_quad(self.plane_size, self.plane_size, 1, 1),
+            };
+            p.set_local_rotation(plane_rotation(plane.normal()));
+            g
+        } else if let Some(capsule) = shape.as_capsule() {
+            let mut node = parent.add_capsule(capsule.radius, 2. * capsule.half_height);
+            if let Some(texture) = &look.texture {
+                let around = TAU * capsule.radius;
+                let along = 2. * capsule.half_height + PI * capsule.radius;
+                node.set_texture(wrapped_texture(texture, look.color, around, along, image));
+            }
+            node
+        } else {
+            panic!("ERROR:Trying to create GameObject from a shape not yet implemented in the renderer")
+        }
+    }
+
+    /// Replace the SceneNode of the object at `index` with a new one for the colliders of `object` and its look
+    fn replace_node(&mut self, index: usize, object: &GameObject) {
+        let look = self.looks[index].clone();
+        let mut new_node = self.node_from_object(object, &look);
+        let old_node = &mut self.renderables[index];
+        new_node.set_local_transformation(old_node.data().local_transformation());
+        new_node.set_visible(old_node.is_visible());
+        old_node.unlink();
+        self.renderables[index] = new_node;
+        self.looks[index].outdated = false;
+    }
+}
+
+impl Renderer for Kiss3dRenderer {
+    /// Draw function to call at each frame update.
+    /// The frame is saved if `save_frames_to` or `record_gif` was used.
+    /// Fails if the objects aren't as many as the added ones.
+    fn draw(&mut self, gameobjects: &[GameObject]) -> Result<(), String> {
+        if gameobjects.len() != self.renderables.len() {
+            return Err(format!(
+                "Error: Drawing {} objects but {} were added to the renderer",
+                gameobjects.len(),
+                self.renderables.len()
+            ));
+        }
+        for (index, go) in gameobjects.iter().enumerate() {
+            if self.looks.get(index).is_some_and(|look| look.outdated) {
+                self.replace_node(index, go);
+            }
+        }
+        if self.window.render_with_camera(&mut self.camera) {
+            //Sync position of objects by setting the position of rendered object
+            //to the position of GameObject
+            for (node, go) in self.renderables.iter_mut().zip(gameobjects) {
+                node.set_local_transformation(go.position);
+            }
+            if self.capture.is_capturing() {
+                let frame = self.window.snap_image();
+                self.capture.add_frame(&frame)?;
+            } else {
+                self.capture.skip_frame();
+            }
+            return Ok(());
+        }
+        Err(String::from("Error: Window closed for rendering"))
+    }
+
+    /// Add a rendered SceneNode to the renderer
+    fn add_obj(&mut self, object: &GameObject) -> Result<(), String> {
+        let look = Look::new(object.color());
+        let new_node = self.node_from_object(object, &look);
+        self.renderables.push(new_node);
+        self.looks.push(look);
+        Ok(())
+    }
+
+    /// Remove a rendered SceneNode by index from the renderer
+    fn remove_obj(&mut self, index: usize) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        self.renderables.remove(index).unlink();
+        self.looks.remove(index);
+        Ok(())
+    }
+
+    /// Textured objects are made again with the colour in their texture at the next `draw`
+    fn set_color(&mut self, index: usize, color: [u8; 3]) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        let look = &mut self.looks[index];
+        look.color = color;
+        if look.texture.is_some() {
+            look.outdated = true;
+        } else {
+            let [r, g, b] = to_float(color);
+            self.renderables[index].set_color(r, g, b);
+        }
+        Ok(())
+    }
+
+    fn set_visible(&mut self, index: usize, visible: bool) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        self.renderables[index].set_visible(visible);
+        Ok(())
+    }
+
+    /// Textures are made and images loaded now, but the object is drawn with it from the next `draw`.
+    /// Fails if the image can't be loaded.
+    fn set_texture(&mut self, index: usize, texture: Option<Texture>) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        if let Some(texture) = &texture {
+            check_texture(texture)?;
+        }
+        if let Some(Texture::Image { path, .. }) = &texture {
+            if !self.images.contains_key(path) {
+                let image = image::open(path).map_err(|error| {
+                    format!("Error: Couldn't load {}: {}", path.display(), error)
+                })?;
+                self.images.insert(path.clone(), image.to_rgb8());
+            }
+        }
+        let look = &mut self.looks[index];
+        look.texture = texture;
+        look.outdated = true;
+        Ok(())
+    }
+
+    /// The object is drawn with it from the next `draw`
+    fn set_material(&mut self, index: usize, material: RenderMaterial) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        let look = &mut self.looks[index];
+        look.material = material;
+        look.outdated = true;
+        Ok(())
+    }
+
+    /// Replace the SceneNode of the object with a new one for its current colliders
+    fn update_shape(&mut self, index: usize, object: &GameObject) -> Result<(), String> {
+        check_index(index, self.renderables.len())?;
+        self.replace_node(index, object);
+        Ok(())
+    }
+
+    fn draw_line(&mut self, a: &Point3<f32>, b: &Point3<f32>, color: [u8; 3]) {
+        self.window.draw_line(a, b, &Point3::from(to_float(color)));
+    }
+
+    fn draw_point(&mut self, point: &Point3<f32>, color: [u8; 3]) {
+        self.window
+            .draw_point(point, &Point3::from(to_float(color)));
+    }
+}
+
+/// How an object is drawn
+#[derive(Clone)]
+struct Look {
+    color: [u8; 3],
+    texture: Option<Texture>,
+    material: RenderMaterial,
+    // The node must be made again at the next draw, to change its texture or material
+    outdated: bool,
+}
+
+impl Look {
+    fn new(color: [u8; 3]) -> Look {
+        Look {
+            color,
+            texture: None,
+            material: RenderMaterial::default(),
+            outdated: false,
+        }
+    }
+}
+
+/// An RGB colour as Kiss3D wants it, in [0, 1]
+fn to_float(color: [u8; 3]) -> [f32; 3] {
+    color.map(|c| c as f32 / 255.)
+}
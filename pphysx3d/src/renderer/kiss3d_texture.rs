@@ -0,0 +1,120 @@
+use std::{cell::RefCell, rc::Rc};
+
+use image::{DynamicImage, Rgb, RgbImage};
+use kiss3d::{
+    nalgebra::{Point2, Point3, Vector3},
+    resource::{self, Mesh, TextureManager},
+    scene::SceneNode,
+};
+
+use super::appearance::Texture;
+
+/// Pixels across each square of a texture image
+const PIXELS_PER_SQUARE: u32 = 16;
+/// The most squares across a texture image, keeping it at most 2048 pixels wide
+const MAX_SQUARES: u32 = 128;
+/// The most cells across a textured plane, keeping its mesh below the 65536 vertices Kiss3D allows
+const MAX_CELLS: u32 = 127;
+
+/// A plane about `size` metres wide in the xy plane with `texture` over `color`, added to `parent`.
+/// Kiss3D's textures can't repeat, so the plane is made of cells that each get a whole image of many squares.
+/// The squares line up with the origin, like in the other renderers.
+pub(crate) fn add_textured_plane(
+    parent: &mut SceneNode,
+    size: f32,
+    texture: &Texture,
+    color: [u8; 3],
+    image: Option<&RgbImage>,
+) -> SceneNode {
+    let squares = (size / texture.size()).ceil().max(1.) as u32;
+    let squares_per_cell = squares.min(MAX_SQUARES);
+    let cells = squares.div_ceil(squares_per_cell).min(MAX_CELLS);
+    let cell_size = squares_per_cell as f32 * texture.size();
+    let start = -((cells / 2) as f32) * cell_size;
+
+    let mut coords = Vec::new();
+    let mut uvs = Vec::new();
+    let mut faces = Vec::new();
+    for i in 0..cells {
+        for j in 0..cells {
+            let (x, y) = (start + i as f32 * cell_size, start + j as f32 * cell_size);
+            let first = coords.len() as u16;
+            coords.extend([
+                Point3::new(x, y, 0.),
+                Point3::new(x + cell_size, y, 0.),
+                Point3::new(x + cell_size, y + cell_size, 0.),
+                Point3::new(x, y + cell_size, 0.),
+            ]);
+            uvs.extend([
+                Point2::new(0., 0.),
+                Point2::new(1., 0.),
+                Point2::new(1., 1.),
+                Point2::new(0., 1.),
+            ]);
+            faces.push(Point3::new(first, first + 1, first + 2));
+            faces.push(Point3::new(first, first + 2, first + 3));
+        }
+    }
+    let normals = vec![Vector3::z(); coords.len()];
+    let mesh = Mesh::new(coords, faces, Some(normals), Some(uvs), false);
+
+    let mut node = parent.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::from_element(1.));
+    node.enable_backface_culling(false);
+    node.set_texture(texture_of(
+        texture,
+        color,
+        (squares_per_cell, squares_per_cell),
+        image,
+    ));
+    node
+}
+
+/// `texture` over `color` wrapped once around a sphere or capsule `around` metres around and `along` metres from end to end
+pub(crate) fn wrapped_texture(
+    texture: &Texture,
+    color: [u8; 3],
+    around: f32,
+    along: f32,
+    image: Option<&RgbImage>,
+) -> Rc<resource::Texture> {
+    let squares = |length: f32| ((length / texture.size()).round() as u32).clamp(1, MAX_SQUARES);
+    texture_of(texture, color, (squares(around), squares(along)), image)
+}
+
+/// The texture of `squares` squares of `texture` over `color`, made once and then shared.
+/// `image` is the loaded image of an image texture.
+fn texture_of(
+    texture: &Texture,
+    color: [u8; 3],
+    squares: (u32, u32),
+    image: Option<&RgbImage>,
+) -> Rc<resource::Texture> {
+    let name = format!(
+        "pphysx3d {:?} over {:?}, {}x{}",
+        texture, color, squares.0, squares.1
+    );
+    TextureManager::get_global_manager(|manager| match manager.get(&name) {
+        Some(made) => made,
+        None => {
+            let pixels = RgbImage::from_fn(
+                squares.0 * PIXELS_PER_SQUARE,
+                squares.1 * PIXELS_PER_SQUARE,
+                |x, y| {
+                    let u = (x as f32 + 0.5) / PIXELS_PER_SQUARE as f32;
+                    let v = (y as f32 + 0.5) / PIXELS_PER_SQUARE as f32;
+                    match image {
+                        // The same pixel of the image in every square
+                        Some(image) => *image.get_pixel(
+                            (((u - u.floor()) * image.width() as f32) as u32)
+                                .min(image.width() - 1),
+                            (((v - v.floor()) * image.height() as f32) as u32)
+                                .min(image.height() - 1),
+                        ),
+                        None => Rgb(texture.color_in_square(color, u, v)),
+                    }
+                },
+            );
+            manager.add_image(DynamicImage::ImageRgb8(pixels), &name)
+        }
+    })
+}
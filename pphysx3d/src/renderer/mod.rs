@@ -1,157 +1,131 @@
-use kiss3d::{
-    camera::{Camera, FirstPerson},
-    event::Key,
-    nalgebra::{Point3, UnitQuaternion, Vector3},
-    scene::SceneNode,
-    window::Window,
-};
-
-use crate::{scene::game_object::GameObject, shapes::shape::Shape};
-
+#[cfg(feature = "kiss3d")]
+pub use kiss3d_renderer::Kiss3dRenderer;
+#[cfg(feature = "kiss3d")]
+pub use viewer::Viewer;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{scene::game_object::GameObject, shapes::bounding_volume::AABB};
+use appearance::{RenderMaterial, Texture};
+
+pub mod appearance;
+pub mod camera;
+#[cfg(feature = "kiss3d")]
+pub mod capture;
+pub mod controls;
+pub mod debug;
+#[cfg(feature = "kiss3d")]
+mod kiss3d_camera;
+#[cfg(feature = "kiss3d")]
+mod kiss3d_renderer;
+#[cfg(feature = "kiss3d")]
+mod kiss3d_texture;
+pub mod recording;
+pub mod software;
 mod tests;
+#[cfg(feature = "kiss3d")]
+mod viewer;
 
 ///The actual size of the planes used in Kiss3dRenderer as infinite planes are not possible without further setup
 pub const PLANE_SIZE: f32 = 10000.;
 
-///Trait representing the bare minimum capabilities required of a renderer for the PhysicsScene
+///Trait representing the capabilities required of a renderer for the PhysicsScene.
+///Objects are referred to by their index, the same as in the PhysicsScene.
+///Debug primitives (lines, points, boxes and arrows) are only drawn by the next `draw`,
+///so they must be given again for every frame they should be seen in.
+///Only `draw`, `add_obj` and `remove_obj` must be implemented: by default the other changes of objects
+///fail as not supported and debug primitives aren't drawn.
 pub trait Renderer {
     fn draw(&mut self, gameobjects: &[GameObject]) -> Result<(), String>;
 
     fn add_obj(&mut self, object: &GameObject) -> Result<(), String>;
 
     fn remove_obj(&mut self, index: usize) -> Result<(), String>;
-}
-
-/// Renderer struct for using Kiss3D.
-/// Adding and removing objects must be done in sync with Scene.
-pub struct Kiss3dRenderer {
-    window: Window,
-    camera: FirstPerson,
-    // Objects must be in same order as in Scene
-    renderables: Vec<SceneNode>,
-}
-
-impl Kiss3dRenderer {
-    /// Add basic rendering window with size and title and free moving
-    /// Camera using move_step 0.1 & wasd-keys as well as
-    /// rotation and movement holding left/right mouse button
-    pub fn new(title: &str, width: u32, height: u32) -> Kiss3dRenderer {
-        let mut camera = kiss3d::camera::FirstPerson::new(
-            Point3::new(0.0, 0.0, 0.0),
-            Point3::new(0.3, 0.2, 1.0),
-        );
-
-        camera.set_move_step(0.1);
-        camera.rebind_left_key(Some(Key::A));
-        camera.rebind_right_key(Some(Key::D));
-        camera.rebind_up_key(Some(Key::W));
-        camera.rebind_down_key(Some(Key::S));
-
-        let mut window = kiss3d::window::Window::new_with_size(title, width, height);
-        window.set_light(kiss3d::light::Light::StickToCamera);
-        Kiss3dRenderer {
-            window,
-            camera,
-            renderables: Vec::new(),
-        }
-    }
 
-    ///Set the position of the camera
-    pub fn camera_position(&mut self, position: [f32; 3]) {
-        self.camera
-            .look_at(Point3::from(position), self.camera.at());
+    /// Change the colour the object at `index` is drawn with
+    fn set_color(&mut self, _index: usize, _color: [u8; 3]) -> Result<(), String> {
+        Err(unsupported("set_color"))
     }
 
-    ///Set the view direction of the camera
-    pub fn camera_direction(&mut self, direction: [f32; 3]) {
-        self.camera.look_at(
-            self.camera.eye(),
-            self.camera.eye() + Vector3::from(direction),
-        )
+    /// Hide or show the object at `index`, hidden objects are still simulated
+    fn set_visible(&mut self, _index: usize, _visible: bool) -> Result<(), String> {
+        Err(unsupported("set_visible"))
     }
 
-    ///Change keybindings and speed of camera
-    pub fn set_camera_movement(
-        &mut self,
-        move_step: f32,
-        up: Key,
-        down: Key,
-        left: Key,
-        right: Key,
-    ) {
-        self.camera.unbind_movement_keys();
-        self.camera.set_move_step(move_step);
-        self.camera.rebind_left_key(Some(left));
-        self.camera.rebind_right_key(Some(right));
-        self.camera.rebind_up_key(Some(up));
-        self.camera.rebind_down_key(Some(down));
-    }
-    ///Change the distance the camera moves with a single key press
-    pub fn change_camera_speed(&mut self, move_step: f32) {
-        self.camera.set_move_step(move_step);
+    /// Draw `texture` over the object at `index` instead of its plain colour, `None` goes back to the colour
+    fn set_texture(&mut self, _index: usize, _texture: Option<Texture>) -> Result<(), String> {
+        Err(unsupported("set_texture"))
     }
 
-    ///Change the background colour
-    pub fn set_background(&mut self, r: f32, g: f32, b: f32) {
-        self.window.set_background_color(r, g, b);
+    /// Change how the object at `index` is shaded
+    fn set_material(&mut self, _index: usize, _material: RenderMaterial) -> Result<(), String> {
+        Err(unsupported("set_material"))
     }
 
-    ///Set the one global light source allowed by kiss3d to a point
-    pub fn set_point_light_source(&mut self, point: [f32; 3]) {
-        self.window
-            .set_light(kiss3d::light::Light::Absolute(Point3::from(point)));
-    }
-    ///Set the one global light source allowed by kiss3d to follow the camera.
-    pub fn set_light_to_camera(&mut self) {
-        self.window.set_light(kiss3d::light::Light::StickToCamera);
+    /// Draw the object at `index` with the colliders of `object`, after they were changed
+    fn update_shape(&mut self, _index: usize, _object: &GameObject) -> Result<(), String> {
+        Err(unsupported("update_shape"))
     }
 
-    ///Match a Shape with a Scene Node to render
-    fn node_from_shape(&mut self, shape: &dyn Shape) -> SceneNode {
-        if let Ok(sphere) = shape.as_sphere() {
-            self.window.add_sphere(sphere.radius)
-        } else if let Ok(plane) = shape.as_plane() {
-            let mut g = self.window.add_group();
-            let mut p = g.add_quad(PLANE_SIZE, PLANE_SIZE, 1, 1);
-            if let Some(rotation) = UnitQuaternion::rotation_between(&Vector3::z(), plane.normal())
-            {
-                p.append_rotation(&rotation);
+    /// Draw a line from `a` to `b` in the next frame
+    fn draw_line(&mut self, _a: &Point3<f32>, _b: &Point3<f32>, _color: [u8; 3]) {}
+
+    /// Draw a point in the next frame
+    fn draw_point(&mut self, _point: &Point3<f32>, _color: [u8; 3]) {}
+
+    /// Draw the edges of `aabb` in the next frame
+    fn draw_aabb(&mut self, aabb: &AABB, color: [u8; 3]) {
+        let (mins, maxs) = (aabb.mins(), aabb.maxs());
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { mins.x } else { maxs.x },
+                if i & 2 == 0 { mins.y } else { maxs.y },
+                if i & 4 == 0 { mins.z } else { maxs.z },
+            )
+        };
+        // Corners differing in exactly one coordinate share an edge
+        for i in 0..8 {
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    self.draw_line(&corner(i), &corner(i | axis), color);
+                }
             }
-            g
-        } else {
-            panic!("ERROR:Trying to create GameObject from a shape not yet implemented in the renderer")
         }
     }
-}
 
-impl Renderer for Kiss3dRenderer {
-    /// Draw function to call at each frame update.
-    fn draw(&mut self, gameobjects: &[GameObject]) -> Result<(), String> {
-        let mut i = 0;
-        if self.window.render_with_camera(&mut self.camera) {
-            //Sync position of objects by setting the position of rendered object
-            //to the position of GameObject
-            for go in gameobjects {
-                self.renderables[i].set_local_transformation(go.position);
-                i += 1;
-            }
-            return Ok(());
+    /// Draw an arrow from `origin` to `origin + vector` in the next frame, for example to show a velocity or force
+    fn draw_arrow(&mut self, origin: &Point3<f32>, vector: &Vector3<f32>, color: [u8; 3]) {
+        if vector.norm() <= f32::EPSILON {
+            return self.draw_point(origin, color);
+        }
+        let tip = origin + vector;
+        self.draw_line(origin, &tip, color);
+        // The head is four lines back from the tip, a fifth of the arrow long
+        let side = vector
+            .cross(&Vector3::y())
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::x)
+            * vector.norm()
+            * 0.1;
+        let other_side = vector.normalize().cross(&side);
+        for offset in [side, -side, other_side, -other_side] {
+            self.draw_line(&tip, &(tip - vector * 0.2 + offset), color);
         }
-        Err(String::from("Error: Window closed for rendering"))
     }
+}
 
-    /// Add a rendered SceneNode to the renderer
-    fn add_obj(&mut self, object: &GameObject) -> Result<(), String> {
-        let mut new_node = self.node_from_shape(object.shape());
-        let [r, g, b] = object.color();
-        new_node.set_color(r as f32 / 255., g as f32 / 255., b as f32 / 255.);
-        self.renderables.push(new_node);
-        Ok(())
-    }
+/// The error for a `Renderer` method the renderer doesn't implement
+fn unsupported(method: &str) -> String {
+    format!("Error: {} isn't supported by this renderer", method)
+}
 
-    /// Remove a rendered SceneNode by index from the renderer
-    fn remove_obj(&mut self, index: usize) -> Result<(), String> {
-        self.renderables.remove(index);
-        Ok(())
+/// The error for an object `index` that isn't in a renderer holding `count` objects
+pub(crate) fn check_index(index: usize, count: usize) -> Result<(), String> {
+    if index >= count {
+        return Err(format!(
+            "Error: No object {} in the renderer, it has {}",
+            index, count
+        ));
     }
+    Ok(())
 }
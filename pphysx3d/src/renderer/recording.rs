@@ -0,0 +1,144 @@
+use nalgebra::{Isometry3, Point3};
+
+use super::{
+    appearance::{check_texture, RenderMaterial, Texture},
+    check_index, Renderer,
+};
+use crate::scene::game_object::GameObject;
+
+/// What a RecordingRenderer knows about an added object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedObject {
+    pub color: [u8; 3],
+    pub visible: bool,
+    pub texture: Option<Texture>,
+    pub material: RenderMaterial,
+    /// The number of colliders it's drawn with
+    pub colliders: usize,
+}
+
+/// Everything given to a RecordingRenderer for one `draw`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordedFrame {
+    /// The pose of every object, in the same order as in Scene
+    pub poses: Vec<Isometry3<f32>>,
+    pub lines: Vec<(Point3<f32>, Point3<f32>, [u8; 3])>,
+    pub points: Vec<(Point3<f32>, [u8; 3])>,
+}
+
+/// Renderer that draws nothing but remembers the pose of every object at every `draw`,
+/// to check in tests what a renderer would have been given.
+/// Like the other renderers, adding and removing objects must be done in sync with Scene.
+#[derive(Debug, Default)]
+pub struct RecordingRenderer {
+    // In the same order as in Scene
+    objects: Vec<RecordedObject>,
+    frames: Vec<RecordedFrame>,
+    // Debug primitives for the next frame
+    next_frame: RecordedFrame,
+}
+
+impl RecordingRenderer {
+    pub fn new() -> RecordingRenderer {
+        RecordingRenderer::default()
+    }
+
+    /// Every `draw` so far, oldest first
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// The latest `draw`
+    pub fn last_frame(&self) -> Option<&RecordedFrame> {
+        self.frames.last()
+    }
+
+    /// The objects currently added, in the order they were added
+    pub fn objects(&self) -> &[RecordedObject] {
+        &self.objects
+    }
+
+    /// Forgets all recorded frames, keeps the added objects
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    fn record(object: &GameObject) -> RecordedObject {
+        RecordedObject {
+            color: object.color(),
+            visible: true,
+            texture: None,
+            material: RenderMaterial::default(),
+            colliders: object.colliders().len(),
+        }
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    /// Records the pose of each object, fails if they're not as many as the added objects
+    fn draw(&mut self, gameobjects: &[GameObject]) -> Result<(), String> {
+        if gameobjects.len() != self.objects.len() {
+            return Err(format!(
+                "Error: Drawing {} objects but {} were added to the renderer",
+                gameobjects.len(),
+                self.objects.len()
+            ));
+        }
+        let mut frame = std::mem::take(&mut self.next_frame);
+        frame.poses = gameobjects.iter().map(|go| go.position).collect();
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    fn add_obj(&mut self, object: &GameObject) -> Result<(), String> {
+        self.objects.push(RecordingRenderer::record(object));
+        Ok(())
+    }
+
+    fn remove_obj(&mut self, index: usize) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        self.objects.remove(index);
+        Ok(())
+    }
+
+    fn set_color(&mut self, index: usize, color: [u8; 3]) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        self.objects[index].color = color;
+        Ok(())
+    }
+
+    fn set_visible(&mut self, index: usize, visible: bool) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        self.objects[index].visible = visible;
+        Ok(())
+    }
+
+    fn set_texture(&mut self, index: usize, texture: Option<Texture>) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        if let Some(texture) = &texture {
+            check_texture(texture)?;
+        }
+        self.objects[index].texture = texture;
+        Ok(())
+    }
+
+    fn set_material(&mut self, index: usize, material: RenderMaterial) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        self.objects[index].material = material;
+        Ok(())
+    }
+
+    fn update_shape(&mut self, index: usize, object: &GameObject) -> Result<(), String> {
+        check_index(index, self.objects.len())?;
+        self.objects[index].colliders = object.colliders().len();
+        Ok(())
+    }
+
+    fn draw_line(&mut self, a: &Point3<f32>, b: &Point3<f32>, color: [u8; 3]) {
+        self.next_frame.lines.push((*a, *b, color));
+    }
+
+    fn draw_point(&mut self, point: &Point3<f32>, color: [u8; 3]) {
+        self.next_frame.points.push((*point, color));
+    }
+}
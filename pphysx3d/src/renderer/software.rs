@@ -0,0 +1,444 @@
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+
+#[cfg(feature = "png")]
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    appearance::{check_texture, surface_coordinates, RenderMaterial, Texture},
+    check_index, Renderer,
+};
+use crate::{
+    scene::game_object::GameObject,
+    shapes::{ray::Ray, shape::Shape},
+};
+
+/// Vertical field of view of the camera [rad], the same as Kiss3D's
+const FIELD_OF_VIEW: f32 = std::f32::consts::FRAC_PI_4;
+/// Share of an object's colour it has even when facing away from the light
+const AMBIENT: f32 = 0.25;
+/// Debug lines closer to the camera than this [m] are cut off
+const NEAR: f32 = 0.01;
+
+/// Renderer drawing the objects on the CPU into an RGB image, without a window or a GPU.
+/// Every pixel casts a ray into the scene and is shaded by the closest hit, so spheres,
+/// capsules and planes look the same as their colliders and planes really are infinite.
+/// Grid and checker textures are drawn, image textures and the wireframe material aren't.
+/// With the `png` feature each frame can be written to a PNG file.
+/// Adding and removing objects must be done in sync with Scene.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    eye: Point3<f32>,
+    at: Point3<f32>,
+    background: [u8; 3],
+    // The light follows the camera when None
+    light: Option<Point3<f32>>,
+    // Colours, visibility, textures and materials of the added objects, in the same order as in Scene
+    colors: Vec<[u8; 3]>,
+    visible: Vec<bool>,
+    textures: Vec<Option<Texture>>,
+    materials: Vec<RenderMaterial>,
+    // Debug primitives for the next frame
+    lines: Vec<(Point3<f32>, Point3<f32>, [u8; 3])>,
+    points: Vec<(Point3<f32>, [u8; 3])>,
+    // RGB, row by row from the top left
+    pixels: Vec<u8>,
+    frame_count: usize,
+    #[cfg(feature = "png")]
+    output_directory: Option<PathBuf>,
+}
+
+impl SoftwareRenderer {
+    /// Renderer making images of `width` x `height` pixels, with the camera placed
+    /// like Kiss3dRenderer's and the light following the camera
+    pub fn new(width: u32, height: u32) -> SoftwareRenderer {
+        SoftwareRenderer {
+            width,
+            height,
+            eye: Point3::new(0.0, 0.0, 0.0),
+            at: Point3::new(0.3, 0.2, 1.0),
+            background: [0, 0, 0],
+            light: None,
+            colors: Vec::new(),
+            visible: Vec::new(),
+            textures: Vec::new(),
+            materials: Vec::new(),
+            lines: Vec::new(),
+            points: Vec::new(),
+            pixels: vec![0; 3 * width as usize * height as usize],
+            frame_count: 0,
+            #[cfg(feature = "png")]
+            output_directory: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The image of the latest `draw` as RGB values, row by row from the top left
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The colour of pixel (`x`, `y`) counted from the top left, panics outside the image
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        assert!(x < self.width && y < self.height, "pixel outside the image");
+        let i = 3 * (y as usize * self.width as usize + x as usize);
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// The number of frames drawn so far
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Place the camera at `eye` looking at `at`
+    pub fn look_at(&mut self, eye: [f32; 3], at: [f32; 3]) {
+        self.eye = Point3::from(eye);
+        self.at = Point3::from(at);
+    }
+
+    ///Set the position of the camera, keeping the point it looks at
+    pub fn camera_position(&mut self, position: [f32; 3]) {
+        self.eye = Point3::from(position);
+    }
+
+    ///Set the view direction of the camera
+    pub fn camera_direction(&mut self, direction: [f32; 3]) {
+        self.at = self.eye + Vector3::from(direction);
+    }
+
+    ///Change the background colour
+    pub fn set_background(&mut self, r: f32, g: f32, b: f32) {
+        self.background = [to_byte(r), to_byte(g), to_byte(b)];
+    }
+
+    ///Set the light source to a point
+    pub fn set_point_light_source(&mut self, point: [f32; 3]) {
+        self.light = Some(Point3::from(point));
+    }
+
+    ///Set the light source to follow the camera
+    pub fn set_light_to_camera(&mut self) {
+        self.light = None;
+    }
+
+    /// Write every following frame to `directory` as `frame_00000.png`, `frame_00001.png`...
+    /// numbered by `frame_count`. The directory must exist.
+    #[cfg(feature = "png")]
+    pub fn save_frames_to(&mut self, directory: impl Into<PathBuf>) {
+        self.output_directory = Some(directory.into());
+    }
+
+    /// Write the image of the latest `draw` to `path` as a PNG
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::RGB);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    /// Cast a ray through every pixel and shade it by the closest collider it hits
+    fn render(&mut self, gameobjects: &[GameObject]) {
+        // Everything the rays are tested against: the shape, its pose and the index of its object
+        let colliders: Vec<(&dyn Shape, Isometry3<f32>, usize)> = gameobjects
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.visible[*index])
+            .flat_map(|(index, go)| {
+                go.colliders()
+                    .iter()
+                    .map(move |collider| (collider.shape(), go.position * collider.position, index))
+            })
+            .collect();
+        let bounds: Vec<_> = colliders
+            .iter()
+            .map(|(shape, position, _)| shape.compute_bounding_sphere(position))
+            .collect();
+
+        let view = self.view();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let u = (2. * (x as f32 + 0.5) / self.width as f32 - 1.) * view.half_width;
+                let v = (1. - 2. * (y as f32 + 0.5) / self.height as f32) * view.half_height;
+                let ray = Ray::new(
+                    self.eye,
+                    UnitVector3::new_normalize(view.forward + view.right * u + view.up * v),
+                );
+
+                let mut closest = None;
+                for (collider, bound) in colliders.iter().zip(&bounds) {
+                    let (shape, position, _) = collider;
+                    // Skip the exact test for colliders whose bounding sphere is missed
+                    let to_center = bound.center() - self.eye;
+                    let along = to_center.dot(ray.direction());
+                    let radius = bound.radius();
+                    if to_center.norm_squared() - along * along > radius * radius
+                        || along + radius < 0.
+                    {
+                        continue;
+                    }
+                    let hit = shape.ray_cast(position, &ray);
+                    if hit.hit
+                        && closest
+                            .as_ref()
+                            .is_none_or(|(distance, _, _)| hit.distance < *distance)
+                    {
+                        closest = Some((hit.distance, hit, collider));
+                    }
+                }
+
+                let shade = match closest {
+                    Some((_, hit, (shape, position, index))) => {
+                        if self.materials[*index] == RenderMaterial::Normals {
+                            hit.normal.map(|c| to_byte((c + 1.) / 2.)).into()
+                        } else {
+                            let color = match &self.textures[*index] {
+                                Some(texture) => texture.color_at(
+                                    self.colors[*index],
+                                    &surface_coordinates(*shape, position, &hit.contact_point),
+                                ),
+                                None => self.colors[*index],
+                            };
+                            let light = self.light.unwrap_or(self.eye);
+                            let brightness = (light - hit.contact_point)
+                                .try_normalize(f32::EPSILON)
+                                .map_or(1., |to_light| hit.normal.dot(&to_light).max(0.));
+                            let intensity = AMBIENT + (1. - AMBIENT) * brightness;
+                            color.map(|c| (c as f32 * intensity).round() as u8)
+                        }
+                    }
+                    None => self.background,
+                };
+                self.set_pixel(x as f32, y as f32, shade);
+            }
+        }
+    }
+
+    /// Draw the debug lines and points on top of the image, then forget them
+    fn render_debug(&mut self) {
+        let view = self.view();
+        for (a, b, color) in std::mem::take(&mut self.lines) {
+            // Cut off the part behind the camera
+            let (mut a, mut b) = (a - self.eye, b - self.eye);
+            let (depth_a, depth_b) = (a.dot(&view.forward), b.dot(&view.forward));
+            if depth_a < NEAR && depth_b < NEAR {
+                continue;
+            } else if depth_a < NEAR {
+                a += (b - a) * (NEAR - depth_a) / (depth_b - depth_a);
+            } else if depth_b < NEAR {
+                b += (a - b) * (NEAR - depth_b) / (depth_a - depth_b);
+            }
+            let (start, end) = (self.project(&view, &a), self.project(&view, &b));
+            let Some((start, end)) = self.clip(start, end) else {
+                continue;
+            };
+            let steps = (end.0 - start.0).abs().max((end.1 - start.1).abs()).ceil() as usize;
+            for step in 0..=steps {
+                let t = if steps == 0 {
+                    0.
+                } else {
+                    step as f32 / steps as f32
+                };
+                self.set_pixel(
+                    start.0 + t * (end.0 - start.0),
+                    start.1 + t * (end.1 - start.1),
+                    color,
+                );
+            }
+        }
+        for (point, color) in std::mem::take(&mut self.points) {
+            let point = point - self.eye;
+            if point.dot(&view.forward) < NEAR {
+                continue;
+            }
+            // A square of 3 x 3 pixels
+            let (x, y) = self.project(&view, &point);
+            for dx in [-1., 0., 1.] {
+                for dy in [-1., 0., 1.] {
+                    self.set_pixel(x + dx, y + dy, color);
+                }
+            }
+        }
+    }
+
+    /// The directions of the camera and the size of the image one metre in front of it
+    fn view(&self) -> View {
+        let forward = (self.at - self.eye)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        let right = forward
+            .cross(&Vector3::y())
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::x);
+        let up = right.cross(&forward);
+        let half_height = (FIELD_OF_VIEW / 2.).tan();
+        let half_width = half_height * self.width as f32 / self.height as f32;
+        View {
+            forward,
+            right,
+            up,
+            half_width,
+            half_height,
+        }
+    }
+
+    /// Where `offset` from the camera ends up in the image, in pixels from the top left.
+    /// The point must be in front of the camera.
+    fn project(&self, view: &View, offset: &Vector3<f32>) -> (f32, f32) {
+        let depth = offset.dot(&view.forward);
+        let u = offset.dot(&view.right) / depth / view.half_width;
+        let v = offset.dot(&view.up) / depth / view.half_height;
+        (
+            (u + 1.) / 2. * self.width as f32,
+            (1. - v) / 2. * self.height as f32,
+        )
+    }
+
+    /// The part of the line from `start` to `end` inside the image, None if it's all outside
+    fn clip(&self, start: (f32, f32), end: (f32, f32)) -> Option<((f32, f32), (f32, f32))> {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let (mut t_min, mut t_max) = (0f32, 1f32);
+        // Each side of the image as how far the line goes outwards and how far inside `start` is
+        for (outwards, inside) in [
+            (-dx, start.0),
+            (dx, self.width as f32 - start.0),
+            (-dy, start.1),
+            (dy, self.height as f32 - start.1),
+        ] {
+            if outwards == 0. {
+                if inside < 0. {
+                    return None;
+                }
+            } else if outwards < 0. {
+                t_min = t_min.max(inside / outwards);
+            } else {
+                t_max = t_max.min(inside / outwards);
+            }
+        }
+        if t_min > t_max {
+            return None;
+        }
+        Some((
+            (start.0 + t_min * dx, start.1 + t_min * dy),
+            (start.0 + t_max * dx, start.1 + t_max * dy),
+        ))
+    }
+
+    /// Colour the pixel containing (`x`, `y`), if it's in the image
+    fn set_pixel(&mut self, x: f32, y: f32, color: [u8; 3]) {
+        if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+            return;
+        }
+        let i = 3 * (y as usize * self.width as usize + x as usize);
+        self.pixels[i..i + 3].copy_from_slice(&color);
+    }
+}
+
+/// The camera's directions and half the width and height of the image one metre in front of it
+struct View {
+    forward: Vector3<f32>,
+    right: Vector3<f32>,
+    up: Vector3<f32>,
+    half_width: f32,
+    half_height: f32,
+}
+
+impl Renderer for SoftwareRenderer {
+    /// Render the objects into the image, and write it to a file if `save_frames_to` was used.
+    /// Fails if the objects aren't as many as the added ones.
+    fn draw(&mut self, gameobjects: &[GameObject]) -> Result<(), String> {
+        if gameobjects.len() != self.colors.len() {
+            return Err(format!(
+                "Error: Drawing {} objects but {} were added to the renderer",
+                gameobjects.len(),
+                self.colors.len()
+            ));
+        }
+        self.render(gameobjects);
+        self.render_debug();
+        #[cfg(feature = "png")]
+        if let Some(directory) = &self.output_directory {
+            let path = directory.join(format!("frame_{:05}.png", self.frame_count));
+            self.save_png(&path)
+                .map_err(|error| format!("Error: Couldn't write {}: {}", path.display(), error))?;
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    fn add_obj(&mut self, object: &GameObject) -> Result<(), String> {
+        self.colors.push(object.color());
+        self.visible.push(true);
+        self.textures.push(None);
+        self.materials.push(RenderMaterial::default());
+        Ok(())
+    }
+
+    fn remove_obj(&mut self, index: usize) -> Result<(), String> {
+        check_index(index, self.colors.len())?;
+        self.colors.remove(index);
+        self.visible.remove(index);
+        self.textures.remove(index);
+        self.materials.remove(index);
+        Ok(())
+    }
+
+    fn set_color(&mut self, index: usize, color: [u8; 3]) -> Result<(), String> {
+        check_index(index, self.colors.len())?;
+        self.colors[index] = color;
+        Ok(())
+    }
+
+    fn set_visible(&mut self, index: usize, visible: bool) -> Result<(), String> {
+        check_index(index, self.visible.len())?;
+        self.visible[index] = visible;
+        Ok(())
+    }
+
+    fn set_texture(&mut self, index: usize, texture: Option<Texture>) -> Result<(), String> {
+        check_index(index, self.textures.len())?;
+        if let Some(texture) = &texture {
+            check_texture(texture)?;
+        }
+        self.textures[index] = texture;
+        Ok(())
+    }
+
+    fn set_material(&mut self, index: usize, material: RenderMaterial) -> Result<(), String> {
+        check_index(index, self.materials.len())?;
+        self.materials[index] = material;
+        Ok(())
+    }
+
+    /// The colliders are read at every `draw`, so there's nothing to update
+    fn update_shape(&mut self, index: usize, _object: &GameObject) -> Result<(), String> {
+        check_index(index, self.colors.len())
+    }
+
+    fn draw_line(&mut self, a: &Point3<f32>, b: &Point3<f32>, color: [u8; 3]) {
+        self.lines.push((*a, *b, color));
+    }
+
+    fn draw_point(&mut self, point: &Point3<f32>, color: [u8; 3]) {
+        self.points.push((*point, color));
+    }
+}
+
+/// A colour channel in [0, 1] as a byte
+fn to_byte(channel: f32) -> u8 {
+    (channel.clamp(0., 1.) * 255.).round() as u8
+}
@@ -1 +1,638 @@
+use nalgebra::{Isometry3, Point2, Point3, Unit, UnitQuaternion, Vector3};
 
+use crate::{
+    renderer::{
+        appearance::{plane_rotation, surface_coordinates, RenderMaterial, Texture},
+        camera::{CameraMode, FollowCamera},
+        controls::SimulationControls,
+        debug::{object_properties, DebugOverlay},
+        recording::{RecordedObject, RecordingRenderer},
+        software::SoftwareRenderer,
+        Renderer,
+    },
+    scene::{
+        builder::GameObjectBuilder, collider::Collider, mouse_joint::MouseJoint, BodyHandle,
+        PhysicsScene,
+    },
+    shapes::{bounding_volume::AABB, plane::Plane, sphere::Sphere},
+};
+
+/// A red sphere in front of the camera at the origin, above a white floor
+#[cfg(test)]
+fn sphere_over_floor() -> PhysicsScene {
+    let mut scene = PhysicsScene::new();
+    scene.add(
+        GameObjectBuilder::sphere(1.)
+            .translation([0., 0., 5.])
+            .color([255, 0, 0])
+            .build()
+            .unwrap(),
+    );
+    scene.add(
+        GameObjectBuilder::plane([0., 1., 0.])
+            .translation([0., -2., 0.])
+            .color([255, 255, 255])
+            .build()
+            .unwrap(),
+    );
+    scene
+}
+
+#[test]
+fn recording_renderer() {
+    let mut scene = sphere_over_floor();
+    let mut renderer = RecordingRenderer::new();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let colors: Vec<_> = renderer.objects().iter().map(|o| o.color).collect();
+    assert_eq!(colors, [[255, 0, 0], [255, 255, 255]]);
+    assert!(renderer.last_frame().is_none());
+
+    for _ in 0..3 {
+        scene.update(0.01);
+        renderer.draw(scene.objects()).unwrap();
+    }
+    assert_eq!(renderer.frames().len(), 3);
+    assert!(
+        renderer.frames()[0].poses[0].translation.y > renderer.frames()[2].poses[0].translation.y
+    );
+    let positions: Vec<_> = scene.objects().iter().map(|go| go.position).collect();
+    assert_eq!(renderer.last_frame().unwrap().poses, positions);
+
+    // Out of sync with the scene
+    assert!(renderer.draw(&scene.objects()[..1]).is_err());
+    assert!(renderer.remove_obj(2).is_err());
+    renderer.remove_obj(0).unwrap();
+    assert_eq!(renderer.objects().len(), 1);
+    assert_eq!(renderer.objects()[0].color, [255, 255, 255]);
+    assert_eq!(renderer.frames().len(), 3);
+    renderer.clear();
+    assert!(renderer.frames().is_empty());
+}
+
+#[test]
+fn software_renderer() {
+    let scene = sphere_over_floor();
+    let mut renderer = SoftwareRenderer::new(40, 30);
+    renderer.look_at([0., 0., 0.], [0., 0., 1.]);
+    renderer.set_background(0., 0., 1.);
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.draw(scene.objects()).unwrap();
+    assert_eq!(renderer.frame_count(), 1);
+    assert_eq!(renderer.pixels().len(), 40 * 30 * 3);
+
+    // The sphere faces the light at the camera in the middle of the image
+    let [r, g, b] = renderer.pixel(20, 15);
+    assert!(r > 240 && g == 0 && b == 0);
+    // The floor is grey at the bottom, the sky is the background at the top
+    let [r, g, b] = renderer.pixel(20, 29);
+    assert!(r > 0 && r == g && g == b);
+    assert_eq!(renderer.pixel(0, 0), [0, 0, 255]);
+
+    // Looking away from the sphere only leaves the floor and the sky
+    renderer.camera_direction([0., 0., -1.]);
+    renderer.draw(scene.objects()).unwrap();
+    assert!(renderer
+        .pixels()
+        .chunks(3)
+        .all(|pixel| pixel == [0, 0, 255] || pixel[0] == pixel[1]));
+    assert!(renderer.draw(&scene.objects()[..1]).is_err());
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn software_renderer_png() {
+    let scene = sphere_over_floor();
+    let mut renderer = SoftwareRenderer::new(16, 12);
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let directory = std::env::temp_dir().join(format!("pphysx3d_frames_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    renderer.save_frames_to(&directory);
+    renderer.draw(scene.objects()).unwrap();
+    renderer.draw(scene.objects()).unwrap();
+
+    for frame in ["frame_00000.png", "frame_00001.png"] {
+        let bytes = std::fs::read(directory.join(frame)).unwrap();
+        assert_eq!(bytes[..8], [137, 80, 78, 71, 13, 10, 26, 10]);
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn renderer_object_updates() {
+    let mut scene = sphere_over_floor();
+    let mut renderer = RecordingRenderer::new();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.set_color(0, [0, 255, 0]).unwrap();
+    renderer.set_visible(1, false).unwrap();
+    scene
+        .get_mut(BodyHandle(0))
+        .unwrap()
+        .add_collider(Collider::new(Box::new(Sphere::new(0.5)), 1.));
+    renderer.update_shape(0, &scene.objects()[0]).unwrap();
+    assert_eq!(
+        renderer.objects(),
+        [
+            RecordedObject {
+                color: [0, 255, 0],
+                visible: true,
+                texture: None,
+                material: RenderMaterial::Shaded,
+                colliders: 2
+            },
+            RecordedObject {
+                color: [255, 255, 255],
+                visible: false,
+                texture: None,
+                material: RenderMaterial::Shaded,
+                colliders: 1
+            },
+        ]
+    );
+    assert!(renderer.set_color(2, [0, 0, 0]).is_err());
+    assert!(renderer.set_visible(2, true).is_err());
+    assert!(renderer.update_shape(2, &scene.objects()[0]).is_err());
+}
+
+#[test]
+fn debug_primitives() {
+    let scene = sphere_over_floor();
+    let mut renderer = RecordingRenderer::new();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let origin = Point3::new(1., 2., 3.);
+    renderer.draw_point(&origin, [1, 1, 1]);
+    renderer.draw_aabb(
+        &AABB::new(Point3::new(0., 0., 0.), Point3::new(1., 2., 3.)),
+        [2, 2, 2],
+    );
+    renderer.draw_arrow(&origin, &Vector3::new(0., 0., 10.), [3, 3, 3]);
+    renderer.draw_arrow(&origin, &Vector3::zeros(), [4, 4, 4]);
+    renderer.draw(scene.objects()).unwrap();
+
+    let frame = renderer.last_frame().unwrap();
+    assert_eq!(frame.points, [(origin, [1, 1, 1]), (origin, [4, 4, 4])]);
+    // 12 edges of the box, each one along a single axis
+    let edges: Vec<_> = frame.lines.iter().filter(|l| l.2 == [2, 2, 2]).collect();
+    assert_eq!(edges.len(), 12);
+    for (a, b, _) in edges {
+        assert_eq!((a - b).iter().filter(|d| **d != 0.).count(), 1);
+    }
+    // The shaft and four lines of the head, all ending at the tip
+    let arrow: Vec<_> = frame.lines.iter().filter(|l| l.2 == [3, 3, 3]).collect();
+    assert_eq!(arrow.len(), 5);
+    assert_eq!(arrow[0].0, origin);
+    let tip = Point3::new(1., 2., 13.);
+    assert_eq!(arrow[0].1, tip);
+    for (a, b, _) in &arrow[1..] {
+        assert_eq!(*a, tip);
+        assert!((b.z - 11.).abs() < 1e-5 && (b.xy() - origin.xy()).norm() > 0.5);
+    }
+
+    // Primitives are only drawn in the next frame
+    renderer.draw(scene.objects()).unwrap();
+    assert_eq!(renderer.last_frame().unwrap().lines.len(), 0);
+}
+
+#[test]
+fn software_renderer_updates() {
+    let scene = sphere_over_floor();
+    let mut renderer = SoftwareRenderer::new(40, 30);
+    renderer.look_at([0., 0., 0.], [0., 0., 1.]);
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.set_color(0, [0, 255, 0]).unwrap();
+    renderer.draw(scene.objects()).unwrap();
+    let [r, g, b] = renderer.pixel(20, 15);
+    assert!(r == 0 && g > 240 && b == 0);
+
+    // Hidden, the sky behind the sphere shows
+    renderer.set_visible(0, false).unwrap();
+    renderer.draw(scene.objects()).unwrap();
+    assert_eq!(renderer.pixel(20, 12), [0, 0, 0]);
+    assert!(renderer.set_visible(2, false).is_err());
+
+    // A line across the middle of the image, one partly behind the camera and a point
+    renderer.draw_line(
+        &Point3::new(-10., 0., 5.),
+        &Point3::new(10., 0., 5.),
+        [255, 0, 255],
+    );
+    renderer.draw_line(
+        &Point3::new(0., 1., -5.),
+        &Point3::new(0., 1., 5.),
+        [0, 255, 255],
+    );
+    renderer.draw_line(
+        &Point3::new(0., 1., -5.),
+        &Point3::new(0., 2., -5.),
+        [255, 255, 0],
+    );
+    renderer.draw_point(&Point3::new(-1., -1., 5.), [255, 255, 255]);
+    renderer.draw(scene.objects()).unwrap();
+    let count = |renderer: &SoftwareRenderer, color: [u8; 3]| {
+        renderer.pixels().chunks(3).filter(|p| *p == color).count()
+    };
+    assert!((0..40)
+        .all(|x| renderer.pixel(x, 14) == [255, 0, 255] || renderer.pixel(x, 15) == [255, 0, 255]));
+    assert!(count(&renderer, [0, 255, 255]) > 5);
+    assert_eq!(count(&renderer, [255, 255, 0]), 0);
+    assert_eq!(count(&renderer, [255, 255, 255]), 9);
+
+    renderer.draw(scene.objects()).unwrap();
+    assert_eq!(count(&renderer, [255, 0, 255]), 0);
+}
+
+/// A renderer implementing only what the Renderer trait requires
+#[cfg(test)]
+struct CountingRenderer {
+    objects: usize,
+}
+
+#[cfg(test)]
+impl Renderer for CountingRenderer {
+    fn draw(
+        &mut self,
+        _gameobjects: &[crate::scene::game_object::GameObject],
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn add_obj(&mut self, _object: &crate::scene::game_object::GameObject) -> Result<(), String> {
+        self.objects += 1;
+        Ok(())
+    }
+
+    fn remove_obj(&mut self, _index: usize) -> Result<(), String> {
+        self.objects -= 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn renderer_defaults() {
+    let scene = sphere_over_floor();
+    let mut renderer = CountingRenderer { objects: 0 };
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let error = renderer.set_color(0, [0, 0, 0]).unwrap_err();
+    assert!(error.contains("set_color"), "{}", error);
+    assert!(renderer.set_visible(0, false).is_err());
+    assert!(renderer.set_texture(0, None).is_err());
+    assert!(renderer.set_material(0, RenderMaterial::Normals).is_err());
+    assert!(renderer.update_shape(0, &scene.objects()[0]).is_err());
+
+    // Debug primitives are left out
+    renderer.draw_aabb(
+        &AABB::new(Point3::origin(), Point3::new(1., 1., 1.)),
+        [255, 255, 255],
+    );
+    renderer.draw_arrow(&Point3::origin(), &Vector3::x(), [255, 255, 255]);
+    renderer.draw(scene.objects()).unwrap();
+    assert_eq!(renderer.objects, 2);
+}
+
+#[test]
+fn debug_overlay() {
+    let mut scene = sphere_over_floor();
+    scene.get_mut(BodyHandle(0)).unwrap().position.translation.y = -1.1;
+    scene.update(0.01);
+    assert!(!scene.contacts().is_empty());
+
+    let mut renderer = RecordingRenderer::new();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let lines_of = |overlay: DebugOverlay, renderer: &mut RecordingRenderer| {
+        overlay.draw(renderer, &scene);
+        renderer.draw(scene.objects()).unwrap();
+        let frame = renderer.last_frame().unwrap();
+        (frame.lines.len(), frame.points.len())
+    };
+    let none = DebugOverlay::default();
+    assert!(!none.is_enabled());
+    assert_eq!(lines_of(none, &mut renderer), (0, 0));
+
+    // The floor's infinite volumes are left out
+    let aabbs = DebugOverlay {
+        aabbs: true,
+        ..none
+    };
+    assert_eq!(lines_of(aabbs, &mut renderer), (12, 0));
+    let spheres = DebugOverlay {
+        bounding_spheres: true,
+        ..none
+    };
+    assert_eq!(lines_of(spheres, &mut renderer), (3 * 24, 0));
+    // An arrow and a point for each contact point
+    let contacts = DebugOverlay {
+        contacts: true,
+        ..none
+    };
+    let count = scene.contacts()[0].2.contacts.len();
+    assert_eq!(lines_of(contacts, &mut renderer), (5 * count, count));
+    // Linear and angular velocity of the falling sphere only, a point if one is zero
+    let velocities = DebugOverlay {
+        velocities: true,
+        ..none
+    };
+    let (lines, points) = lines_of(velocities, &mut renderer);
+    assert_eq!(lines + 5 * points, 10);
+    // Every node of the tree with a finite box
+    let bvh = DebugOverlay { bvh: true, ..none };
+    assert_eq!(lines_of(bvh, &mut renderer), (12, 0));
+    // The anchor and the line to the target of each joint passed in
+    let joints = DebugOverlay {
+        joints: true,
+        ..none
+    };
+    assert_eq!(lines_of(joints, &mut renderer), (0, 0));
+    let joint = MouseJoint::new(&scene, BodyHandle(0), Point3::new(0., -1., 0.)).unwrap();
+    joints.draw_with_joints(&mut renderer, &scene, &[joint]);
+    renderer.draw(scene.objects()).unwrap();
+    let frame = renderer.last_frame().unwrap();
+    assert_eq!((frame.lines.len(), frame.points.len()), (1, 1));
+
+    assert!(DebugOverlay::all().is_enabled());
+}
+
+#[test]
+fn selected_object_properties() {
+    let scene = sphere_over_floor();
+    let text = object_properties(BodyHandle(0), scene.get(BodyHandle(0)).unwrap());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "Object 0 (Dynamic, 1 colliders)");
+    assert_eq!(lines[2], "position: (0.00, 0.00, 5.00)");
+    assert_eq!(lines[3], "rotation: 0.0° around (0.00, 1.00, 0.00)");
+    assert_eq!(lines[4], "velocity: (0.00, 0.00, 0.00) m/s");
+
+    let floor = object_properties(BodyHandle(1), scene.get(BodyHandle(1)).unwrap());
+    assert!(floor.starts_with("Object 1 (Static"));
+}
+
+#[test]
+fn simulation_controls() {
+    let mut controls = SimulationControls::new(0.01);
+    assert!(!controls.is_paused());
+    assert_eq!(controls.next_steps(), Some((0.01, 1)));
+    assert_eq!(controls.next_steps(), Some((0.01, 1)));
+
+    // Slow motion steps with a shorter time step
+    controls.slower();
+    assert_eq!(controls.time_scale(), 0.5);
+    assert_eq!(controls.next_steps(), Some((0.005, 1)));
+    // Running faster takes more steps of the same length
+    controls.faster();
+    controls.faster();
+    assert_eq!(controls.next_steps(), Some((0.01, 2)));
+    controls.set_time_scale(100.);
+    assert_eq!(controls.time_scale(), 4.);
+    assert_eq!(controls.next_steps(), Some((0.01, 4)));
+    controls.set_time_scale(0.);
+    assert_eq!(controls.time_scale(), 1. / 32.);
+    controls.set_time_scale(1.);
+
+    controls.toggle_pause();
+    assert_eq!(controls.next_steps(), None);
+    // A single step, then paused again
+    controls.step_once();
+    assert_eq!(controls.next_steps(), Some((0.01, 1)));
+    assert_eq!(controls.next_steps(), None);
+    assert!(controls.is_paused());
+    controls.toggle_pause();
+    assert_eq!(controls.next_steps(), Some((0.01, 1)));
+
+    // Stepping while running pauses, and takes a single step also when running faster
+    controls.faster();
+    controls.step_once();
+    assert_eq!(controls.next_steps(), Some((0.01, 1)));
+    assert_eq!(controls.next_steps(), None);
+}
+
+#[test]
+fn simulation_status() {
+    let mut scene = sphere_over_floor();
+    scene.get_mut(BodyHandle(0)).unwrap().position.translation.y = -1.1;
+    scene.update(0.01);
+    let mut controls = SimulationControls::new(0.01);
+    controls.slower();
+    let status = controls.status(&scene, std::time::Duration::from_micros(1500));
+    assert_eq!(
+        status,
+        "running at 0.5x\nstep: 1.50 ms\nbodies: 2\ncontacts: 1"
+    );
+    controls.toggle_pause();
+    assert!(controls
+        .status(&scene, std::time::Duration::ZERO)
+        .starts_with("paused"));
+}
+
+#[test]
+fn camera_modes() {
+    let selected = Some(BodyHandle(3));
+    let mut mode = CameraMode::FirstPerson;
+    let mut modes = vec![];
+    for _ in 0..4 {
+        mode = mode.next(selected);
+        modes.push(mode);
+    }
+    assert_eq!(
+        modes,
+        vec![
+            CameraMode::Orbit,
+            CameraMode::Follow(FollowCamera::new(BodyHandle(3))),
+            CameraMode::Fixed,
+            CameraMode::FirstPerson,
+        ]
+    );
+    // Nothing to follow
+    assert_eq!(CameraMode::Orbit.next(None), CameraMode::Fixed);
+}
+
+#[test]
+fn follow_camera() {
+    let scene = sphere_over_floor();
+    let center = Point3::new(0., 0., 5.);
+    let mut follow = FollowCamera::new(BodyHandle(0));
+    follow.offset = Vector3::new(0., 0., -5.);
+    let (eye, at) = (Point3::new(0., 3., 0.), Point3::new(0., 3., 1.));
+
+    // It moves part of the way at first, and gets there in the end
+    let (near_eye, near_at) = follow.follow(&scene, &eye, &at, 0.1).unwrap();
+    assert!(near_eye.y > 0.1 && near_eye.y < 2.9);
+    assert!((near_at - center).norm() < (at - center).norm());
+    let (far_eye, far_at) = follow.follow(&scene, &eye, &at, 10.).unwrap();
+    assert!((far_eye - Point3::origin()).norm() < 1e-3);
+    assert!((far_at - center).norm() < 1e-3);
+
+    // Without smoothing it's there at once
+    follow.smoothing = 0.;
+    assert_eq!(
+        follow.follow(&scene, &eye, &at, 0.),
+        Some((Point3::origin(), center))
+    );
+
+    follow.body = BodyHandle(7);
+    assert_eq!(follow.follow(&scene, &eye, &at, 0.1), None);
+}
+
+#[test]
+fn texture_colors() {
+    let grid = Texture::Grid {
+        size: 2.,
+        color: [0, 0, 0],
+    };
+    assert_eq!(grid.color_at([255; 3], &Point2::new(0.05, 1.)), [0, 0, 0]);
+    assert_eq!(grid.color_at([255; 3], &Point2::new(1., -3.95)), [0, 0, 0]);
+    assert_eq!(grid.color_at([255; 3], &Point2::new(1., 1.)), [255; 3]);
+
+    let checker = Texture::Checker {
+        size: 1.,
+        color: [0, 0, 255],
+    };
+    assert_eq!(
+        checker.color_at([255, 0, 0], &Point2::new(0.5, 0.5)),
+        [255, 0, 0]
+    );
+    assert_eq!(
+        checker.color_at([255, 0, 0], &Point2::new(1.5, 0.5)),
+        [0, 0, 255]
+    );
+    assert_eq!(
+        checker.color_at([255, 0, 0], &Point2::new(-0.5, 0.5)),
+        [0, 0, 255]
+    );
+    assert_eq!(
+        checker.color_at([255, 0, 0], &Point2::new(-0.5, -0.5)),
+        [255, 0, 0]
+    );
+}
+
+#[test]
+fn plane_rotations() {
+    for normal in [Vector3::z(), -Vector3::z(), Vector3::x(), Vector3::y()] {
+        let rotation = plane_rotation(&Unit::new_normalize(normal));
+        assert!((rotation * Vector3::z() - normal).norm() < 1e-6);
+    }
+
+    // Points on a plane are measured along it, from the position of the plane
+    let plane = Plane::new(Vector3::y_axis());
+    let position = Isometry3::from_parts(
+        Vector3::new(0., -2., 0.).into(),
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.3),
+    );
+    let point = position * Point3::new(1., 0., -3.);
+    let coordinates = surface_coordinates(&plane, &position, &point);
+    assert!((coordinates.coords.norm() - 10f32.sqrt()).abs() < 1e-5);
+    let other = surface_coordinates(&plane, &position, &(position * Point3::new(1., 0., -2.)));
+    assert!(((coordinates - other).norm() - 1.).abs() < 1e-5);
+}
+
+#[test]
+fn renderer_textures() {
+    let scene = sphere_over_floor();
+    let mut renderer = RecordingRenderer::new();
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    let grid = Texture::Grid {
+        size: 1.,
+        color: [0, 0, 0],
+    };
+    renderer.set_texture(1, Some(grid.clone())).unwrap();
+    renderer.set_material(0, RenderMaterial::Normals).unwrap();
+    assert_eq!(renderer.objects()[1].texture, Some(grid));
+    assert_eq!(renderer.objects()[0].material, RenderMaterial::Normals);
+
+    let empty = Texture::Checker {
+        size: 0.,
+        color: [0, 0, 0],
+    };
+    assert!(renderer.set_texture(0, Some(empty)).is_err());
+    assert!(renderer.set_texture(2, None).is_err());
+    assert!(renderer.set_material(2, RenderMaterial::Wireframe).is_err());
+}
+
+#[test]
+fn software_renderer_textures() {
+    let mut scene = PhysicsScene::new();
+    scene.add(
+        GameObjectBuilder::plane([0., 0., -1.])
+            .translation([0., 0., 5.])
+            .color([255, 0, 0])
+            .build()
+            .unwrap(),
+    );
+    let mut renderer = SoftwareRenderer::new(40, 30);
+    renderer.look_at([0., 0., 0.], [0., 0., 1.]);
+    renderer.add_obj(&scene.objects()[0]).unwrap();
+    let checker = Texture::Checker {
+        size: 1.,
+        color: [0, 0, 255],
+    };
+    renderer.set_texture(0, Some(checker)).unwrap();
+    renderer.draw(scene.objects()).unwrap();
+
+    // The squares above and below the middle of the wall in front of the camera have different colours
+    let [r, g, b] = renderer.pixel(24, 11);
+    assert!(r > 200 && g == 0 && b == 0);
+    let [r, g, b] = renderer.pixel(24, 19);
+    assert!(r == 0 && g == 0 && b > 200);
+
+    // Coloured by its normal, facing the camera along -z
+    renderer.set_material(0, RenderMaterial::Normals).unwrap();
+    renderer.draw(scene.objects()).unwrap();
+    let [r, g, b] = renderer.pixel(24, 11);
+    assert!((120..136).contains(&r) && (120..136).contains(&g) && b == 0);
+}
+
+#[cfg(feature = "kiss3d")]
+#[test]
+fn frame_capture() {
+    use crate::renderer::capture::FrameCapture;
+    use std::time::Duration;
+
+    let scene = sphere_over_floor();
+    let mut renderer = SoftwareRenderer::new(16, 12);
+    for object in scene.objects() {
+        renderer.add_obj(object).unwrap();
+    }
+    renderer.draw(scene.objects()).unwrap();
+    let frame = image::RgbImage::from_raw(16, 12, renderer.pixels().to_vec()).unwrap();
+
+    let directory = std::env::temp_dir().join(format!("pphysx3d_capture_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut capture = FrameCapture::new();
+    capture.skip_frame();
+    assert!(!capture.is_capturing());
+    capture.save_frames_to(&directory);
+    capture
+        .record_gif(directory.join("frames.gif"), Duration::from_millis(40))
+        .unwrap();
+    capture.add_frame(&frame).unwrap();
+    capture.add_frame(&frame).unwrap();
+    capture.finish_gif();
+    assert_eq!(capture.frame_count(), 3);
+
+    // The PNG images are the frames, without the skipped one
+    assert!(!directory.join("frame_00000.png").exists());
+    for name in ["frame_00001.png", "frame_00002.png"] {
+        let saved = image::open(directory.join(name)).unwrap().to_rgb8();
+        assert_eq!(saved, frame);
+    }
+    let bytes = std::fs::read(directory.join("frames.gif")).unwrap();
+    assert_eq!(bytes[..6], *b"GIF89a");
+    assert_eq!(bytes.last(), Some(&0x3b));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+use kiss3d::event::Key;
+
+use super::{
+    controls::SimulationControls,
+    kiss3d_renderer::{Kiss3dRenderer, TEXT_SIZE},
+    Renderer,
+};
+use crate::scene::{
+    builder::GameObjectBuilder, game_object::GameObject, snapshot::SceneSnapshot, BodyHandle,
+    PhysicsScene,
+};
+
+/// The time step at normal speed [s]
+const TIME_STEP: f32 = 0.007;
+const SPAWN_RADIUS: f32 = 0.25;
+const SPAWN_MASS: f32 = 1.;
+/// The speed spawned spheres are thrown with [m/s]
+const SPAWN_SPEED: f32 = 10.;
+/// Spawned spheres take these colours in turn
+const SPAWN_COLORS: [[u8; 3]; 4] = [[0, 160, 255], [255, 160, 0], [160, 255, 0], [255, 0, 160]];
+const HUD_COLOR: [u8; 3] = [255, 255, 255];
+
+/// A window that owns a PhysicsScene and steps it every frame, with keyboard controls for the simulation:
+///
+///     space: pause / resume
+///     period: take a single step, pausing first
+///     minus / equals: run half / twice as fast
+///     r: reset to the state at the first frame
+///     f: throw a sphere from the camera
+///
+/// The HUD in the bottom left corner shows the speed, the time the last step took and the number of bodies and contacts.
+/// Everything `Kiss3dRenderer::draw_scene` does works as well, including dragging objects.
+pub struct Viewer {
+    scene: PhysicsScene,
+    renderer: Kiss3dRenderer,
+    controls: SimulationControls,
+    // The state `reset` goes back to, saved at the first frame
+    initial_state: Option<SceneSnapshot>,
+    // The time the last update took
+    step_time: Duration,
+    spawned: usize,
+}
+
+impl Viewer {
+    /// Open a window with size and title showing `scene`
+    pub fn new(title: &str, width: u32, height: u32, scene: PhysicsScene) -> Viewer {
+        let mut renderer = Kiss3dRenderer::new(title, width, height);
+        for object in scene.objects() {
+            // Adding to a Kiss3dRenderer can't fail
+            renderer.add_obj(object).unwrap();
+        }
+        Viewer {
+            scene,
+            renderer,
+            controls: SimulationControls::new(TIME_STEP),
+            initial_state: None,
+            step_time: Duration::ZERO,
+            spawned: 0,
+        }
+    }
+
+    pub fn scene(&self) -> &PhysicsScene {
+        &self.scene
+    }
+
+    /// The scene, to change its objects. Objects must be added with `add` to be drawn, and can't be removed.
+    pub fn scene_mut(&mut self) -> &mut PhysicsScene {
+        &mut self.scene
+    }
+
+    /// The renderer, to set up the camera and the lights
+    pub fn renderer_mut(&mut self) -> &mut Kiss3dRenderer {
+        &mut self.renderer
+    }
+
+    pub fn controls(&self) -> &SimulationControls {
+        &self.controls
+    }
+
+    /// The controls, to change them from code instead of with the keys
+    pub fn controls_mut(&mut self) -> &mut SimulationControls {
+        &mut self.controls
+    }
+
+    /// Add an object to the scene and the window.
+    /// Objects added after the first frame are removed again by `reset`.
+    pub fn add(&mut self, object: GameObject) -> BodyHandle {
+        self.renderer.add_obj(&object).unwrap();
+        self.scene.add(object)
+    }
+
+    /// Throw a sphere from the camera in the direction it looks in
+    pub fn spawn_sphere(&mut self) -> BodyHandle {
+        let ray = self.renderer.view_ray();
+        let color = SPAWN_COLORS[self.spawned % SPAWN_COLORS.len()];
+        self.spawned += 1;
+        let sphere = GameObjectBuilder::sphere(SPAWN_RADIUS)
+            .translation(ray.point_at(2. * SPAWN_RADIUS).coords.into())
+            .velocity((ray.direction().into_inner() * SPAWN_SPEED).into())
+            .mass(SPAWN_MASS)
+            .color(color)
+            .build()
+            .expect("the spawned sphere is valid");
+        self.add(sphere)
+    }
+
+    /// Put the scene back into its state at the first frame, removing the objects added since
+    pub fn reset(&mut self) -> Result<(), String> {
+        let initial_state = match &self.initial_state {
+            Some(initial_state) => initial_state,
+            None => return Ok(()),
+        };
+        while self.scene.objects().len() > initial_state.len() {
+            let index = self.scene.objects().len() - 1;
+            self.renderer.remove_obj(index)?;
+            self.scene.remove(self.scene.handles()[index]);
+        }
+        self.scene
+            .restore(initial_state)
+            .map_err(|error| format!("Error: Can't reset the scene, {}", error))
+    }
+
+    /// Handle the keys, step the scene and draw it.
+    /// Fails when the window is closed.
+    pub fn frame(&mut self) -> Result<(), String> {
+        if self.initial_state.is_none() {
+            self.initial_state = Some(self.scene.snapshot());
+        }
+        // The keys pressed since the last frame
+        let keys = self.renderer.pressed_keys().to_vec();
+        for key in keys {
+            match key {
+                Key::Space => self.controls.toggle_pause(),
+                Key::Period => self.controls.step_once(),
+                Key::Minus => self.controls.slower(),
+                Key::Equals => self.controls.faster(),
+                Key::R => self.reset()?,
+                Key::F => {
+                    self.spawn_sphere();
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((time_step, steps)) = self.controls.next_steps() {
+            let start = Instant::now();
+            for _ in 0..steps {
+                if let Some(joint) = self.renderer.mouse_joint() {
+                    joint.apply(&mut self.scene, time_step);
+                }
+                self.scene.update(time_step);
+            }
+            self.step_time = start.elapsed() / steps as u32;
+        }
+
+        let status = self.controls.status(&self.scene, self.step_time);
+        let [_, height] = self.renderer.window_size();
+        let lines = status.lines().count() as f32;
+        self.renderer.draw_text(
+            &status,
+            [10., height as f32 - (lines + 0.5) * TEXT_SIZE],
+            HUD_COLOR,
+        );
+        self.renderer.draw_scene(&self.scene)
+    }
+
+    /// Show the scene until the window is closed
+    pub fn run(mut self) {
+        while self.frame().is_ok() {}
+    }
+}
@@ -0,0 +1,438 @@
+use std::{fmt, sync::Arc};
+
+use nalgebra::{Isometry3, UnitVector3, Vector3};
+
+use super::{
+    collider::{Collider, CollisionGroups},
+    game_object::{BodyType, GameObject, DAMPING, INFINITY},
+    material::PhysicsMaterial,
+    rigid_body::{GyroscopicMode, LockedAxes, RigidBody},
+};
+use crate::shapes::{capsule::Capsule, plane::Plane, shape::Shape, sphere::Sphere};
+
+/// Reasons a `GameObjectBuilder` refuses to build a GameObject
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameObjectError {
+    /// A dimension of the shape (radius, half height) is zero, negative or not a number
+    InvalidDimension { name: &'static str, value: f32 },
+    /// A property of the object is NaN or infinite
+    NotFinite { name: &'static str },
+    /// The mass of a dynamic object isn't a positive finite number
+    InvalidMass(f32),
+    /// The density isn't a positive finite number
+    InvalidDensity(f32),
+    /// A coefficient (bounciness, frictions, dampings, maximum velocities) is negative or not a number
+    InvalidCoefficient { name: &'static str, value: f32 },
+}
+
+impl fmt::Display for GameObjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameObjectError::InvalidDimension { name, value } => {
+                write!(f, "the shape's {} must be positive, got {}", name, value)
+            }
+            GameObjectError::NotFinite { name } => write!(f, "the {} must be finite", name),
+            GameObjectError::InvalidMass(mass) => {
+                write!(
+                    f,
+                    "a dynamic object's mass must be positive and finite, got {}",
+                    mass
+                )
+            }
+            GameObjectError::InvalidDensity(density) => {
+                write!(
+                    f,
+                    "the density must be positive and finite, got {}",
+                    density
+                )
+            }
+            GameObjectError::InvalidCoefficient { name, value } => {
+                write!(
+                    f,
+                    "the {} must be a non-negative number, got {}",
+                    name, value
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameObjectError {}
+
+/// Builds a GameObject from named properties instead of the long argument list of `GameObject::new`.
+/// Unless set, objects are dynamic, white, have a density of 1, a bounciness and friction of 0.5,
+/// the default damping and no velocity limits.
+/// The mass is computed from the density and the shape's volume unless set explicitly.
+/// More colliders can be attached with `collider`, adding their masses to the object's.
+///
+/// ```no_run
+/// # use pphysx3d::scene::builder::GameObjectBuilder;
+/// let ball = GameObjectBuilder::sphere(0.5)
+///     .translation([0., 2., 0.])
+///     .velocity([1., 0., 0.])
+///     .mass(2.)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct GameObjectBuilder {
+    shape: Box<dyn Shape>,
+    body_type: BodyType,
+    color: [u8; 3],
+    position: Isometry3<f32>,
+    shape_position: Isometry3<f32>,
+    velocity: [f32; 3],
+    angular_velocity: [f32; 3],
+    mass: Option<f32>,
+    density: f32,
+    bounciness: f32,
+    friction: f32,
+    material: Option<Arc<PhysicsMaterial>>,
+    sensor: bool,
+    groups: CollisionGroups,
+    colliders: Vec<Collider>,
+    linear_damping: f32,
+    angular_damping: f32,
+    max_linear_velocity: f32,
+    max_angular_velocity: f32,
+    locked_axes: LockedAxes,
+    gyroscopic: GyroscopicMode,
+}
+
+impl GameObjectBuilder {
+    /// Starts building a dynamic object with `shape`
+    pub fn new(shape: Box<dyn Shape>) -> GameObjectBuilder {
+        GameObjectBuilder {
+            shape,
+            body_type: BodyType::Dynamic,
+            color: [255, 255, 255],
+            position: Isometry3::identity(),
+            shape_position: Isometry3::identity(),
+            velocity: [0.; 3],
+            angular_velocity: [0.; 3],
+            mass: None,
+            density: 1.,
+            bounciness: 0.5,
+            friction: 0.5,
+            material: None,
+            sensor: false,
+            groups: CollisionGroups::ALL,
+            colliders: Vec::new(),
+            linear_damping: DAMPING,
+            angular_damping: DAMPING,
+            max_linear_velocity: INFINITY,
+            max_angular_velocity: INFINITY,
+            locked_axes: LockedAxes::NONE,
+            gyroscopic: GyroscopicMode::default(),
+        }
+    }
+
+    /// Starts building a dynamic sphere
+    pub fn sphere(radius: f32) -> GameObjectBuilder {
+        GameObjectBuilder::new(Box::new(Sphere::new(radius)))
+    }
+
+    /// Starts building a static plane. A dynamic plane needs its `mass` set since planes have infinite volume.
+    pub fn plane(normal: [f32; 3]) -> GameObjectBuilder {
+        let normal = UnitVector3::new_normalize(Vector3::from(normal));
+        GameObjectBuilder::new(Box::new(Plane::new(normal))).body_type(BodyType::Static)
+    }
+
+    /// Starts building a dynamic capsule standing along the y axis
+    pub fn capsule(half_height: f32, radius: f32) -> GameObjectBuilder {
+        GameObjectBuilder::new(Box::new(Capsule::new(half_height, radius)))
+    }
+
+    /// How the object is moved by the PhysicsScene
+    pub fn body_type(mut self, body_type: BodyType) -> Self {
+        self.body_type = body_type;
+        self
+    }
+
+    /// RGB colour of the object
+    pub fn color(mut self, color: [u8; 3]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Position and rotation of the object
+    pub fn position(mut self, position: Isometry3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Position of the object's center
+    pub fn translation(mut self, translation: [f32; 3]) -> Self {
+        self.position.translation.vector = Vector3::from(translation);
+        self
+    }
+
+    /// Rotation of the object as a scaled axis [rad]
+    pub fn rotation(mut self, rotation: [f32; 3]) -> Self {
+        self.position.rotation = Isometry3::new(Vector3::zeros(), Vector3::from(rotation)).rotation;
+        self
+    }
+
+    /// Position and rotation of the shape relative to the object, the object's origin by default
+    pub fn shape_position(mut self, position: Isometry3<f32>) -> Self {
+        self.shape_position = position;
+        self
+    }
+
+    /// Initial linear velocity [m/s]
+    pub fn velocity(mut self, velocity: [f32; 3]) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Initial angular velocity [rad/s]
+    pub fn angular_velocity(mut self, angular_velocity: [f32; 3]) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    /// Mass of the shape [kg], overrides the mass computed from the density
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = Some(mass);
+        self
+    }
+
+    /// Density [kg/m^3] used to compute the mass from the shape's volume
+    pub fn density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Coefficient of restitution
+    pub fn bounciness(mut self, bounciness: f32) -> Self {
+        self.bounciness = bounciness;
+        self
+    }
+
+    /// Coefficient of friction
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Shared surface material, overrides `bounciness` and `friction`
+    pub fn material(mut self, material: Arc<PhysicsMaterial>) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// Makes the shape a sensor, which reports overlaps without being pushed and adds no mass
+    pub fn sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    /// Collision groups of the shape
+    pub fn collision_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Attaches another collider to the object
+    pub fn collider(mut self, collider: Collider) -> Self {
+        self.colliders.push(collider);
+        self
+    }
+
+    /// How fast the velocity decays, as e^(-damping * t) [1/s]
+    pub fn linear_damping(mut self, damping: f32) -> Self {
+        self.linear_damping = damping;
+        self
+    }
+
+    /// How fast the angular velocity decays, as e^(-damping * t) [1/s]
+    pub fn angular_damping(mut self, damping: f32) -> Self {
+        self.angular_damping = damping;
+        self
+    }
+
+    /// Highest speed the object can reach [m/s]
+    pub fn max_linear_velocity(mut self, max: f32) -> Self {
+        self.max_linear_velocity = max;
+        self
+    }
+
+    /// Highest angular speed the object can reach [rad/s]
+    pub fn max_angular_velocity(mut self, max: f32) -> Self {
+        self.max_angular_velocity = max;
+        self
+    }
+
+    /// Axes the object can't move along or turn around
+    pub fn locked_axes(mut self, locked_axes: LockedAxes) -> Self {
+        self.locked_axes = locked_axes;
+        self
+    }
+
+    /// How the gyroscopic torque of the spinning object is integrated
+    pub fn gyroscopic(mut self, gyroscopic: GyroscopicMode) -> Self {
+        self.gyroscopic = gyroscopic;
+        self
+    }
+
+    /// The mass the shape adds to the object, infinite for static objects
+    pub fn compute_mass(&self) -> Result<f32, GameObjectError> {
+        if self.body_type == BodyType::Static {
+            return Ok(INFINITY);
+        }
+        let mass = match self.mass {
+            Some(mass) => mass,
+            None => {
+                if !(self.density > 0. && self.density.is_finite()) {
+                    return Err(GameObjectError::InvalidDensity(self.density));
+                }
+                self.density * self.shape.volume()
+            }
+        };
+        if mass > 0. && mass.is_finite() {
+            Ok(mass)
+        } else if self.body_type == BodyType::Dynamic {
+            Err(GameObjectError::InvalidMass(mass))
+        } else {
+            // Kinematic objects aren't moved by forces, so their mass only matters to what they hit
+            Ok(INFINITY)
+        }
+    }
+
+    /// Validates the properties and builds the GameObject
+    pub fn build(self) -> Result<GameObject, GameObjectError> {
+        self.validate()?;
+        let mass = if self.sensor {
+            0.
+        } else {
+            self.compute_mass()?
+        };
+        let material = self
+            .material
+            .unwrap_or_else(|| Arc::new(PhysicsMaterial::new(self.friction, self.bounciness)));
+        let collider = Collider::new(self.shape, mass)
+            .with_position(self.shape_position)
+            .with_material(material)
+            .with_sensor(self.sensor)
+            .with_groups(self.groups);
+        let mut body = RigidBody::new(self.position, self.velocity, self.angular_velocity, mass);
+        body.linear_damping = self.linear_damping;
+        body.angular_damping = self.angular_damping;
+        body.max_linear_velocity = self.max_linear_velocity;
+        body.max_angular_velocity = self.max_angular_velocity;
+        body.locked_axes = self.locked_axes;
+        body.gyroscopic = self.gyroscopic;
+
+        let mut object = GameObject::with_collider(body, collider, self.color);
+        for collider in self.colliders {
+            object.add_collider(collider);
+        }
+        // All colliders together must give a dynamic object some mass
+        object.set_body_type(self.body_type)?;
+        Ok(object)
+    }
+
+    /// Checks everything except the mass
+    fn validate(&self) -> Result<(), GameObjectError> {
+        validate_shape(self.shape.as_ref())?;
+        finite(
+            "collider position",
+            self.shape_position.translation.vector.iter(),
+        )?;
+        finite(
+            "collider rotation",
+            self.shape_position.rotation.coords.iter(),
+        )?;
+        for collider in &self.colliders {
+            validate_shape(collider.shape())?;
+            finite(
+                "collider position",
+                collider.position.translation.vector.iter(),
+            )?;
+            finite(
+                "collider rotation",
+                collider.position.rotation.coords.iter(),
+            )?;
+            // Infinite masses are allowed here, `build` refuses them for dynamic objects
+            if collider.mass().is_nan() || collider.mass() < 0. {
+                return Err(GameObjectError::InvalidMass(collider.mass()));
+            }
+            coefficients(material_coefficients(collider.material()))?;
+        }
+
+        finite("position", self.position.translation.vector.iter())?;
+        finite("rotation", self.position.rotation.coords.iter())?;
+        finite("velocity", self.velocity.iter())?;
+        finite("angular velocity", self.angular_velocity.iter())?;
+
+        match &self.material {
+            Some(material) => coefficients(material_coefficients(material))?,
+            None => coefficients([("bounciness", self.bounciness), ("friction", self.friction)])?,
+        }
+        coefficients([
+            ("linear damping", self.linear_damping),
+            ("angular damping", self.angular_damping),
+        ])?;
+        // Infinite maximum velocities mean no limit
+        for (name, value) in [
+            ("maximum linear velocity", self.max_linear_velocity),
+            ("maximum angular velocity", self.max_angular_velocity),
+        ] {
+            if value.is_nan() || value < 0. {
+                return Err(GameObjectError::InvalidCoefficient { name, value });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error if the shape has a dimension that isn't positive and finite
+pub(crate) fn validate_shape(shape: &dyn Shape) -> Result<(), GameObjectError> {
+    let positive = |name: &'static str, value: f32| {
+        if value > 0. && value.is_finite() {
+            Ok(())
+        } else {
+            Err(GameObjectError::InvalidDimension { name, value })
+        }
+    };
+    if let Some(sphere) = shape.as_sphere() {
+        positive("radius", sphere.radius)?;
+    } else if let Some(capsule) = shape.as_capsule() {
+        positive("radius", capsule.radius)?;
+        positive("half height", capsule.half_height)?;
+    } else if let Some(plane) = shape.as_plane() {
+        finite("normal", plane.normal().iter())?;
+    }
+    Ok(())
+}
+
+/// The coefficients of `material` with their names
+fn material_coefficients(material: &PhysicsMaterial) -> [(&'static str, f32); 4] {
+    [
+        ("bounciness", material.bounciness),
+        ("static friction", material.static_friction),
+        ("dynamic friction", material.dynamic_friction),
+        ("rolling friction", material.rolling_friction),
+    ]
+}
+
+/// Error if any of the named `values` is negative or not finite
+fn coefficients<const N: usize>(values: [(&'static str, f32); N]) -> Result<(), GameObjectError> {
+    for (name, value) in values {
+        if !(value >= 0. && value.is_finite()) {
+            return Err(GameObjectError::InvalidCoefficient { name, value });
+        }
+    }
+    Ok(())
+}
+
+/// Error if any of `values` is NaN or infinite
+pub(crate) fn finite<'a>(
+    name: &'static str,
+    mut values: impl Iterator<Item = &'a f32>,
+) -> Result<(), GameObjectError> {
+    if values.all(|value| value.is_finite()) {
+        Ok(())
+    } else {
+        Err(GameObjectError::NotFinite { name })
+    }
+}
@@ -0,0 +1,233 @@
+use nalgebra::{Isometry3, UnitVector3, Vector3};
+
+use super::{game_object::BodyType, BodyHandle, PhysicsScene, QueryFilter};
+use crate::shapes::shape::Shape;
+
+/// Movements shorter than this are considered done
+const MIN_MOVEMENT: f32 = 1.0e-5;
+
+/// Moves a character's shape through a PhysicsScene by shape casting instead of letting the solver push it around.
+/// The character slides along walls, climbs steps, refuses slopes that are too steep and sticks to the ground when walking down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharacterController {
+    /// The direction the character considers up
+    pub up: UnitVector3<f32>,
+    /// Gap kept between the character and everything it touches
+    pub offset: f32,
+    /// The steepest slope [rad] the character can walk on, steeper surfaces are treated as walls
+    pub max_slope_angle: f32,
+    /// The highest step the character climbs on its own, 0 disables climbing
+    pub max_step_height: f32,
+    /// How far below the character it looks for ground to stick to when not moving up, 0 disables snapping
+    pub snap_to_ground: f32,
+    /// How many times the movement may be redirected along the surfaces it hits
+    pub max_iterations: usize,
+}
+
+/// The result of moving a character
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharacterMovement {
+    /// How far the character actually moved
+    pub translation: Vector3<f32>,
+    /// True if the character ends up standing on walkable ground
+    pub grounded: bool,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        CharacterController::new()
+    }
+}
+
+impl CharacterController {
+    /// Creates a controller with y as up, able to walk on 45° slopes and climb 0.3 high steps
+    pub fn new() -> CharacterController {
+        CharacterController {
+            up: Vector3::y_axis(),
+            offset: 0.01,
+            max_slope_angle: std::f32::consts::FRAC_PI_4,
+            max_step_height: 0.3,
+            snap_to_ground: 0.2,
+            max_iterations: 8,
+        }
+    }
+
+    /// Moves the object with `handle` by up to `desired_translation`, see `compute_movement`.
+    /// The character's shape is the one of its first collider.
+    /// Position based kinematic objects get the new pose as their next kinematic position,
+    /// velocity based ones are teleported there.
+    /// Returns `None` without moving anything if there is no kinematic object with `handle`,
+    /// dynamic and static objects are left to the solver.
+    pub fn move_character(
+        &self,
+        scene: &mut PhysicsScene,
+        handle: BodyHandle,
+        desired_translation: &Vector3<f32>,
+    ) -> Option<CharacterMovement> {
+        let character = scene.get(handle).filter(|object| object.is_kinematic())?;
+        let collider = &character.colliders()[0];
+        let not_self = |other: BodyHandle, _: &_| other != handle;
+        let movement = self.compute_movement(
+            scene,
+            collider.shape(),
+            &collider.world_position(&character.position),
+            desired_translation,
+            Some(&not_self),
+        );
+
+        let character = scene.get_mut(handle)?;
+        let mut position = character.position;
+        position.translation.vector += movement.translation;
+        if character.body_type() == BodyType::KinematicPositionBased {
+            character.set_next_kinematic_position(position);
+        } else {
+            character.position = position;
+        }
+        Some(movement)
+    }
+
+    /// Computes how far a character with `shape` at `position` can move towards `desired_translation` without going through anything in `scene`.
+    /// Objects for which `filter` returns false are ignored, which should at least exclude the character itself.
+    pub fn compute_movement(
+        &self,
+        scene: &PhysicsScene,
+        shape: &dyn Shape,
+        position: &Isometry3<f32>,
+        desired_translation: &Vector3<f32>,
+        filter: QueryFilter,
+    ) -> CharacterMovement {
+        let mut translation: Vector3<f32> = Vector3::zeros();
+        let mut remaining = *desired_translation;
+        let mut grounded = false;
+
+        for _ in 0..self.max_iterations {
+            let length = remaining.norm();
+            if length <= MIN_MOVEMENT {
+                break;
+            }
+            let current = translated(position, &translation);
+            let hit = match scene.cast_shape(shape, &current, &remaining, 1.0, filter) {
+                Some((_, hit)) => hit,
+                None => {
+                    translation += remaining;
+                    break;
+                }
+            };
+
+            // Move up to the obstacle, backing off far enough to keep the offset along the hit normal
+            let direction = remaining / length;
+            let approach = (-direction.dot(&hit.normal)).max(0.1);
+            let allowed = (hit.toi * length - self.offset / approach).max(0.0);
+            translation += direction * allowed;
+            remaining = direction * (length - allowed);
+
+            if self.is_walkable(&hit.normal) {
+                grounded = true;
+            } else if let Some(step) =
+                self.try_step(scene, shape, &translated(position, &translation), &remaining, filter)
+            {
+                translation += step;
+                grounded = true;
+                // The step used up the horizontal part of the movement
+                remaining = self.vertical(&remaining);
+                continue;
+            }
+            remaining = self.slide(&remaining, &hit.normal);
+        }
+
+        // Look for ground below unless moving upwards, sticking to it if it's close enough
+        if desired_translation.dot(&self.up) <= MIN_MOVEMENT {
+            let current = translated(position, &translation);
+            let probe_distance = self.snap_to_ground.max(0.0) + 2.0 * self.offset;
+            let probe = -self.up.into_inner() * probe_distance;
+            if let Some((_, hit)) = scene.cast_shape(shape, &current, &probe, 1.0, filter) {
+                if self.is_walkable(&hit.normal) {
+                    grounded = true;
+                    if self.snap_to_ground > 0.0 {
+                        let gap = (hit.toi * probe_distance - self.offset).max(0.0);
+                        translation -= self.up.into_inner() * gap;
+                    }
+                }
+            }
+        }
+
+        CharacterMovement {
+            translation,
+            grounded,
+        }
+    }
+
+    /// True if the character can stand on a surface with `normal`
+    pub fn is_walkable(&self, normal: &UnitVector3<f32>) -> bool {
+        normal.dot(&self.up) >= self.max_slope_angle.cos() - f32::EPSILON
+    }
+
+    /// The part of `vector` along the up direction
+    fn vertical(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        self.up.into_inner() * vector.dot(&self.up)
+    }
+
+    /// Removes the part of `remaining` going into a surface with `normal`.
+    /// Surfaces too steep to walk on can't be used to gain height, so they act as vertical walls unless falling.
+    fn slide(&self, remaining: &Vector3<f32>, normal: &UnitVector3<f32>) -> Vector3<f32> {
+        let project = |normal: &Vector3<f32>| remaining - normal * remaining.dot(normal).min(0.0);
+        if self.is_walkable(normal) {
+            return project(normal);
+        }
+        if remaining.dot(&self.up) >= 0.0 {
+            let horizontal = normal.into_inner() - self.vertical(normal);
+            let wall = horizontal.try_normalize(f32::EPSILON).unwrap_or(**normal);
+            return project(&wall);
+        }
+        // Sliding down a steep surface is fine, but it mustn't push the character up
+        let slid = project(normal);
+        let rise = slid.dot(&self.up).max(0.0);
+        slid - self.up.into_inner() * rise
+    }
+
+    /// Tries to climb a step blocking the horizontal part of `remaining` by lifting the character, moving it forward and putting it down on top.
+    /// Returns the translation over the step, or `None` if there is no room or no walkable ground within `max_step_height`.
+    fn try_step(
+        &self,
+        scene: &PhysicsScene,
+        shape: &dyn Shape,
+        position: &Isometry3<f32>,
+        remaining: &Vector3<f32>,
+        filter: QueryFilter,
+    ) -> Option<Vector3<f32>> {
+        let forward = remaining - self.vertical(remaining);
+        if self.max_step_height <= 0.0 || forward.norm() <= MIN_MOVEMENT {
+            return None;
+        }
+
+        // Lift, there has to be room above
+        let lift = self.up.into_inner() * self.max_step_height;
+        if scene.cast_shape(shape, position, &lift, 1.0, filter).is_some() {
+            return None;
+        }
+        let lifted = translated(position, &lift);
+
+        // Move forward, the step must not block the lifted character
+        if scene.cast_shape(shape, &lifted, &forward, 1.0, filter).is_some() {
+            return None;
+        }
+        let moved = translated(&lifted, &forward);
+
+        // Put it back down, there has to be ground to stand on within the lifted height and the offset kept from the ground
+        let drop_distance = self.max_step_height + 2.0 * self.offset;
+        let drop = -self.up.into_inner() * drop_distance;
+        let (_, hit) = scene.cast_shape(shape, &moved, &drop, 1.0, filter)?;
+        if !self.is_walkable(&hit.normal) {
+            return None;
+        }
+        let dropped = (hit.toi * drop_distance - self.offset).max(0.0);
+        Some(lift + forward - self.up.into_inner() * dropped)
+    }
+}
+
+/// `position` moved by `translation`
+fn translated(position: &Isometry3<f32>, translation: &Vector3<f32>) -> Isometry3<f32> {
+    let mut moved = *position;
+    moved.translation.vector += translation;
+    moved
+}
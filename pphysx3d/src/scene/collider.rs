@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use nalgebra::Isometry3;
+
+use super::material::PhysicsMaterial;
+use crate::shapes::{mass_properties::MassProperties, shape::Shape};
+
+/// Decides which colliders can touch each other.
+/// Two colliders interact if each one is a member of a group the other one's filter accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollisionGroups {
+    /// Bit mask of the groups the collider belongs to
+    pub memberships: u32,
+    /// Bit mask of the groups the collider can touch
+    pub filter: u32,
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        CollisionGroups::ALL
+    }
+}
+
+impl CollisionGroups {
+    /// Member of every group and touches every group
+    pub const ALL: CollisionGroups = CollisionGroups {
+        memberships: u32::MAX,
+        filter: u32::MAX,
+    };
+
+    pub fn new(memberships: u32, filter: u32) -> CollisionGroups {
+        CollisionGroups {
+            memberships,
+            filter,
+        }
+    }
+
+    /// True if colliders with these groups and `other` can touch
+    pub fn interacts_with(&self, other: &CollisionGroups) -> bool {
+        self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+    }
+}
+
+/// A shape attached to a rigid body, giving it its extent, mass and surface
+pub struct Collider {
+    shape: Box<dyn Shape>,
+    /// Pose relative to the body it's attached to
+    pub position: Isometry3<f32>,
+    mass: f32, // [kg], the collider's share of the body's mass
+    material: Arc<PhysicsMaterial>,
+    /// Sensors detect overlaps but are never pushed apart
+    pub sensor: bool,
+    pub groups: CollisionGroups,
+}
+
+impl Collider {
+    /// Creates a collider at the body's origin with the default material
+    pub fn new(shape: Box<dyn Shape>, mass: f32) -> Collider {
+        Collider {
+            shape,
+            position: Isometry3::identity(),
+            mass,
+            material: Arc::new(PhysicsMaterial::default()),
+            sensor: false,
+            groups: CollisionGroups::ALL,
+        }
+    }
+
+    /// Creates a collider whose mass is computed from `density` and the shape's volume
+    pub fn with_density(shape: Box<dyn Shape>, density: f32) -> Collider {
+        let mass = density * shape.volume();
+        Collider::new(shape, mass)
+    }
+
+    /// Places the collider at `position` relative to its body
+    pub fn with_position(mut self, position: Isometry3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Gives the collider a (possibly shared) material
+    pub fn with_material(mut self, material: Arc<PhysicsMaterial>) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Makes the collider a sensor or a solid collider
+    pub fn with_sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    /// Sets the collision groups of the collider
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// The collider's shape
+    pub fn shape(&self) -> &dyn Shape {
+        self.shape.as_ref()
+    }
+
+    /// The collider's mass [kg], sensors don't add to the mass of their body
+    pub fn mass(&self) -> f32 {
+        if self.sensor {
+            return 0.;
+        }
+        self.mass
+    }
+
+    /// The collider's surface material
+    pub fn material(&self) -> &Arc<PhysicsMaterial> {
+        &self.material
+    }
+
+    /// Change the collider's surface material
+    pub fn set_material(&mut self, material: Arc<PhysicsMaterial>) {
+        self.material = material;
+    }
+
+    /// The collider's pose in world space when its body is at `body_position`
+    pub fn world_position(&self, body_position: &Isometry3<f32>) -> Isometry3<f32> {
+        body_position * self.position
+    }
+
+    /// True if this collider and `other` can touch
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.groups.interacts_with(&other.groups)
+    }
+
+    /// The collider's mass properties in the space of its body
+    pub fn mass_properties(&self) -> MassProperties {
+        self.shape
+            .mass_properties(self.mass())
+            .transformed(&self.position)
+    }
+}
@@ -1,33 +1,67 @@
-use kiss3d::nalgebra::{
-    Isometry3, Matrix3, Point3, Translation3, UnitQuaternion, UnitVector3, Vector3,
+use nalgebra::{Isometry3, UnitVector3, Vector3};
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
-use crate::shapes::{plane::Plane, shape::Shape, sphere::Sphere};
+use super::{
+    builder::{GameObjectBuilder, GameObjectError},
+    collider::Collider,
+    material::PhysicsMaterial,
+    rigid_body::RigidBody,
+};
+use crate::shapes::{mass_properties::MassProperties, plane::Plane, shape::Shape, sphere::Sphere};
 
 pub const INFINITY: f32 = f32::INFINITY;
-pub const DAMPING: f32 = 0.001;
-/// Physics enabled gameobjects to be put and handled by PhysicsScene
+/// Default linear and angular damping of bodies [1/s]
+pub const DAMPING: f32 = 0.1;
+
+/// How a GameObject is moved by the PhysicsScene
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BodyType {
+    /// Moved by forces, gravity and collisions
+    Dynamic,
+    /// Never moves, acts as if it had infinite mass
+    Static,
+    /// Moved only by the velocity set by the user, ignores forces, gravity and collisions
+    KinematicVelocityBased,
+    /// Moved to the pose set with `set_next_kinematic_position`, its velocity is inferred from the movement each step
+    KinematicPositionBased,
+}
+
+/// Physics enabled gameobjects to be put and handled by PhysicsScene.
+/// A rigid body with at least one collider attached to it and the colour it's drawn with.
+/// The body's fields and methods can be used directly on the GameObject.
 pub struct GameObject {
-    shape: Box<dyn Shape>, // The collider
+    body: RigidBody,
+    colliders: Vec<Collider>, // Never empty, the first one is the collider given on creation
     // Texture:
-    color: [u8; 3], //RGB values for the object's default colour (overwritten if texture exists)
-    inverse_mass: f32, // [1/kg]
-    bounciness: f32, // elasticity aka coefficient of restitution
-    friction: f32,  // coefficient of friction
-    // Regular momentum stuff:
-    pub position: Isometry3<f32>, // includes a translation vector and a rotation part as an unit quaternion
-    pub velocity: Vector3<f32>,   // [m/s]
-    pub acceleration: Vector3<f32>, // [m/s^2]
-    force_accum: Vector3<f32>,    // Forces summed a la d'Alembert's principle [N]
-    // Angular momentum stuff:
-    // Orientation is stored in the unit quaternion of position
-    pub angular_velocity: Vector3<f32>, // Angular velocity [rad/s]
-    pub angular_acceleration: Vector3<f32>, // Angular acceleration [rad/s^2]
-    pub torque_accum: Vector3<f32>,     // Torque summed, same principle as force_accum [Nm]
+    color: [u8; 3], //RGB values for the object's default colour (textures are drawn over it, see Renderer::set_texture)
+}
+
+impl Deref for GameObject {
+    type Target = RigidBody;
+
+    fn deref(&self) -> &RigidBody {
+        &self.body
+    }
+}
+
+impl DerefMut for GameObject {
+    fn deref_mut(&mut self) -> &mut RigidBody {
+        &mut self.body
+    }
 }
 
 impl GameObject {
-    /// Create a new GameObject using initialized Shape & nalgebra Isometry to represent rotation and position
+    /// Create a new GameObject using initialized Shape & nalgebra Isometry to represent rotation and position.
+    /// Objects with infinite mass become `BodyType::Static`, all others `BodyType::Dynamic`.
+    /// `bounciness` and `friction` make up a new `PhysicsMaterial`, use `set_material` to share one between objects.
+    /// Nothing is validated, so a zero radius or a NaN mass make an object the scene can't simulate.
+    #[deprecated(note = "use GameObjectBuilder")]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         shape: Box<dyn Shape>,
         color: [u8; 3],
@@ -38,117 +72,64 @@ impl GameObject {
         bounciness: f32,
         friction: f32,
     ) -> GameObject {
-        let inv_mass = if mass >= INFINITY - f32::EPSILON {
-            0.
-        } else if mass <= f32::EPSILON {
-            // How should mass = 0 be handled?
-            // - It shouldn't be a thing
-            INFINITY
-        } else {
-            1. / mass
-        };
-
-        GameObject {
-            shape,
-            color,
-            inverse_mass: inv_mass,
-            bounciness,
-            friction,
-            position,
-            velocity: Vector3::from(velocity),
-            acceleration: Vector3::new(0., 0., 0.),
-            force_accum: Vector3::new(0., 0., 0.),
-            angular_velocity: Vector3::from(angular_velocity),
-            angular_acceleration: Vector3::new(0., 0., 0.),
-            torque_accum: Vector3::new(0., 0., 0.),
-        }
-    }
-
-    pub fn add_linear_impulse(&mut self, impulse: Vector3<f32>) {
-        self.velocity += impulse;
-    }
-
-    pub fn add_rotational_impulse(&mut self, contact_point: &Point3<f32>, impulse: &Vector3<f32>) {
-        let center_of_mass = &self.position.translation;
-        let torque: Vector3<f32> = (contact_point.coords - center_of_mass.vector).cross(&impulse);
-        let angular_acceleration = &self.inv_tensor() * torque;
-        self.angular_velocity += angular_acceleration; // NOT SURE IF THIS IS NECESSARY
+        let body = RigidBody::new(position, velocity, angular_velocity, mass);
+        let collider = Collider::new(shape, mass)
+            .with_material(Arc::new(PhysicsMaterial::new(friction, bounciness)));
+        GameObject::with_collider(body, collider, color)
     }
 
-    // Only works for simple shapes atm. Will need to incorporate Steiner and stuff if we want inertia tensors for composite bodies
-    // May need to be slightly different from real world inerta tensors to feel realistic in a physics engine
-    pub fn inv_tensor(&self) -> Matrix3<f32> {
-        // An object's inertia tensor is defined by its geometric properties
-        let mut inv_tensor: Matrix3<f32> = Matrix3::<f32>::zeros();
-        let inv_mass = &self.inverse_mass;
-
-        if let Ok(_sphere) = &self.shape.as_sphere() {
-            let radius = &self.shape.as_sphere().unwrap().radius;
-            // RADIUS CAN'T BE 0
-            let diagonal: Vector3<f32> = Vector3::new(
-                2.5 * inv_mass / (radius * radius),
-                2.5 * inv_mass / (radius * radius),
-                2.5 * inv_mass / (radius * radius),
-            );
-            inv_tensor.set_diagonal(&diagonal);
-        }
-
-        return inv_tensor;
+    /// Create a GameObject from a body and its first collider.
+    /// The body's mass properties are computed from the collider.
+    pub fn with_collider(body: RigidBody, collider: Collider, color: [u8; 3]) -> GameObject {
+        let mut object = GameObject {
+            body,
+            colliders: vec![collider],
+            color,
+        };
+        object.update_mass_properties();
+        object
     }
 
-    /// The Object's shape
-    pub fn shape(&self) -> &dyn Shape {
-        self.shape.as_ref()
+    /// Attach another collider to the object and recompute the body's mass properties from all colliders
+    pub fn add_collider(&mut self, collider: Collider) {
+        self.colliders.push(collider);
+        self.update_mass_properties();
     }
 
-    /// All accumulated forces acting on the Object
-    pub fn force_accum(&self) -> &Vector3<f32> {
-        &self.force_accum
+    /// Sets the body's mass properties to the combination of its colliders'
+    pub fn update_mass_properties(&mut self) {
+        let mass_properties = self
+            .colliders
+            .iter()
+            .map(Collider::mass_properties)
+            .fold(MassProperties::zero(), |total, part| total + part);
+        self.body.set_mass_properties(mass_properties);
     }
 
-    /// Add a force acting on the object to it's force accumulator
-    pub fn add_force(&mut self, force: Vector3<f32>) {
-        self.force_accum = self.force_accum + force;
+    /// The object's rigid body
+    pub fn body(&self) -> &RigidBody {
+        &self.body
     }
 
-    /// All accumulated angular forces acting on the Object
-    pub fn torque_accum(&self) -> &Vector3<f32> {
-        &self.torque_accum
+    /// The object's rigid body for modification
+    pub fn body_mut(&mut self) -> &mut RigidBody {
+        &mut self.body
     }
 
-    /// Add an angular force acting on the object to it's torque accumulator
-    pub fn add_angular_force(&mut self, force: Vector3<f32>) {
-        self.torque_accum = self.torque_accum + force;
+    /// The colliders attached to the object, there is always at least one
+    pub fn colliders(&self) -> &[Collider] {
+        &self.colliders
     }
 
-    /// Remove all accumulated forces acting on the Object
-    fn clear_accum(&mut self) {
-        // maybe don't have to create a new Vector3 idk yet
-        self.force_accum = Vector3::new(0., 0., 0.);
-        self.torque_accum = Vector3::new(0., 0., 0.);
+    /// The colliders attached to the object for modification.
+    /// Call `update_mass_properties` after changing their masses or positions.
+    pub fn colliders_mut(&mut self) -> &mut [Collider] {
+        &mut self.colliders
     }
 
-    // Pretty much just Explicit Euler, might want to change to something like Verlet
-    pub fn integrate(&mut self, dt: f32) {
-        // Update linear position
-        //self.position.translation = self.position.translation.one() * Translation::from(DURATION * self.velocity);
-        self.position.translation.vector = self.position.translation.vector + dt * self.velocity;
-
-        // I'm so confused over how these work, maybe this is completely wrong:
-        self.position.rotation =
-            self.position.rotation * UnitQuaternion::new(0.5 * dt * self.angular_velocity);
-
-        // Calculate acceleration from force
-        self.acceleration += self.inverse_mass * self.force_accum;
-        self.angular_acceleration += self.inv_tensor() * self.torque_accum;
-
-        // Calculate new velocity
-        self.velocity = (1. - DAMPING) * (self.velocity + dt * self.acceleration);
-        self.angular_velocity =
-            (1. - DAMPING) * (self.angular_velocity + dt * self.angular_acceleration);
-
-        // (NOT SURE IF HAVE TO MAKE NEW ZERO VECTOR)
-        self.clear_accum();
+    /// The shape of the object's first collider
+    pub fn shape(&self) -> &dyn Shape {
+        self.colliders[0].shape()
     }
 
     /// The Objects colour
@@ -156,64 +137,128 @@ impl GameObject {
         self.color
     }
 
-    /// The mass of the Object (1/inverse_mass)
-    pub fn mass(&self) -> f32 {
-        if self.inverse_mass != 0. {
-            return 1. / self.inverse_mass;
-        }
-        return INFINITY;
+    /// The surface material of the object's first collider
+    pub fn material(&self) -> &Arc<PhysicsMaterial> {
+        self.colliders[0].material()
     }
 
-    /// The inverse mass of the Object
-    pub fn inv_mass(&self) -> f32 {
-        if self.inverse_mass != 0. {
-            return 1. / self.inverse_mass;
+    /// Change the surface material of all the object's colliders, the same material can be used by many objects
+    pub fn set_material(&mut self, material: Arc<PhysicsMaterial>) {
+        for collider in &mut self.colliders {
+            collider.set_material(material.clone());
         }
-        return 0.;
     }
 
     /// The Object's coefficient of bounciness
     pub fn bounciness(&self) -> f32 {
-        self.bounciness
+        self.material().bounciness
     }
 
-    /// The Object's coefficient of friction
+    /// The Object's coefficient of dynamic friction
     pub fn friction(&self) -> f32 {
-        self.friction
-    }
-
-    /// Convenience function to add to the object's velocity
-    pub fn add_velocity(&mut self, velocity: [f32; 3]) {
-        self.velocity += Vector3::from(velocity);
-    }
-
-    /// Convenience function to add to the object's acceleration
-    pub fn add_acceleration(&mut self, acceleration: [f32; 3]) {
-        self.acceleration += Vector3::from(acceleration);
+        self.material().dynamic_friction
     }
 
-    /// Convenience function to add to the object's angular velocity
-    pub fn add_angularvelocity(&mut self, velocity: [f32; 3]) {
-        self.angular_velocity += Vector3::from(velocity);
+    /*Constructor Helper functions!*/
+    /// Creates a sphere with given radius, rotations and velocities.
+    /// Static if `mass` is infinite, fails where `GameObjectBuilder` would.
+    #[deprecated(note = "use GameObjectBuilder")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sphere(
+        radius: f32,
+        color: [u8; 3],
+        position: [f32; 3],
+        rotation: [f32; 3],
+        velocity: [f32; 3],
+        angular_velocity: [f32; 3],
+        mass: f32,
+        bounciness: f32,
+        friction: f32,
+    ) -> Result<Self, GameObjectError> {
+        helper_build(
+            GameObjectBuilder::sphere(radius)
+                .rotation(rotation)
+                .velocity(velocity)
+                .angular_velocity(angular_velocity),
+            color,
+            position,
+            mass,
+            bounciness,
+            friction,
+        )
     }
 
-    /// Convenience function to add to the object's angular acceleration
-    pub fn add_angularacceleration(&mut self, acceleration: [f32; 3]) {
-        self.angular_acceleration += Vector3::from(acceleration);
+    /// Creates a sphere with given radius, zero rotations and velocities.
+    /// Static if `mass` is infinite, fails where `GameObjectBuilder` would.
+    pub fn sphere_default(
+        radius: f32,
+        color: [u8; 3],
+        position: [f32; 3],
+        mass: f32,
+        bounciness: f32,
+        friction: f32,
+    ) -> Result<Self, GameObjectError> {
+        helper_build(
+            GameObjectBuilder::sphere(radius),
+            color,
+            position,
+            mass,
+            bounciness,
+            friction,
+        )
     }
 
-    /// Convenience function to set the object's translational position
-    pub fn set_translation(&mut self, position: [f32; 3]) {
-        self.position.translation = Translation3::new(position[0], position[1], position[2]);
+    /// Creates a Plane with given normal, rotations and velocities.
+    /// Static if `mass` is infinite, fails where `GameObjectBuilder` would.
+    #[deprecated(note = "use GameObjectBuilder")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn plane(
+        normal: [f32; 3],
+        color: [u8; 3],
+        position: [f32; 3],
+        rotation: [f32; 3],
+        velocity: [f32; 3],
+        angular_velocity: [f32; 3],
+        mass: f32,
+        bounciness: f32,
+        friction: f32,
+    ) -> Result<Self, GameObjectError> {
+        helper_build(
+            GameObjectBuilder::plane(normal)
+                .rotation(rotation)
+                .velocity(velocity)
+                .angular_velocity(angular_velocity),
+            color,
+            position,
+            mass,
+            bounciness,
+            friction,
+        )
     }
 
-    /// Convenience function to set the object's rotation
-    pub fn set_rotation(&mut self, position: [f32; 3]) {
-        self.position.rotation = UnitQuaternion::new(Vector3::from(position));
+    /// Creates a plane with given normal, zero rotations and velocities.
+    /// Static if `mass` is infinite, fails where `GameObjectBuilder` would.
+    pub fn plane_default(
+        normal: [f32; 3],
+        color: [u8; 3],
+        position: [f32; 3],
+        mass: f32,
+        bounciness: f32,
+        friction: f32,
+    ) -> Result<Self, GameObjectError> {
+        helper_build(
+            GameObjectBuilder::plane(normal),
+            color,
+            position,
+            mass,
+            bounciness,
+            friction,
+        )
     }
 
-    /*Constructor Helper functions!*/
-    /// Creates a sphere with given radius, rotations and velocities
+    /// Creates a sphere with given radius, rotations and velocities, without checking the values
+    #[deprecated(note = "use GameObjectBuilder")]
+    #[allow(non_snake_case, clippy::too_many_arguments)]
     pub fn Sphere(
         radius: f32,
         color: [u8; 3],
@@ -225,13 +270,11 @@ impl GameObject {
         bounciness: f32,
         friction: f32,
     ) -> Self {
-        let shape = Box::new(Sphere::new(radius));
-
-        let iso = Isometry3::new(Vector3::from(position), Vector3::from(rotation));
+        #[allow(deprecated)]
         GameObject::new(
-            shape,
+            Box::new(Sphere::new(radius)),
             color,
-            iso,
+            Isometry3::new(Vector3::from(position), Vector3::from(rotation)),
             velocity,
             angular_velocity,
             mass,
@@ -240,7 +283,9 @@ impl GameObject {
         )
     }
 
-    /// Creates a sphere with given radius, zero rotations and velocities
+    /// Creates a sphere with given radius, zero rotations and velocities, without checking the values
+    #[deprecated(note = "use GameObject::sphere_default")]
+    #[allow(non_snake_case)]
     pub fn Sphere_default(
         radius: f32,
         color: [u8; 3],
@@ -249,22 +294,15 @@ impl GameObject {
         bounciness: f32,
         friction: f32,
     ) -> Self {
-        let shape = Box::new(Sphere::new(radius));
-
-        let iso = Isometry3::translation(position[0], position[1], position[2]);
-        GameObject::new(
-            shape,
-            color,
-            iso,
-            [0., 0., 0.],
-            [0., 0., 0.],
-            mass,
-            bounciness,
-            friction,
+        #[allow(deprecated)]
+        GameObject::Sphere(
+            radius, color, position, [0.; 3], [0.; 3], [0.; 3], mass, bounciness, friction,
         )
     }
 
-    /// Creates a Plane with given normal, rotations and velocities
+    /// Creates a Plane with given normal, rotations and velocities, without checking the values
+    #[deprecated(note = "use GameObjectBuilder")]
+    #[allow(non_snake_case, clippy::too_many_arguments)]
     pub fn Plane(
         normal: [f32; 3],
         color: [u8; 3],
@@ -276,15 +314,13 @@ impl GameObject {
         bounciness: f32,
         friction: f32,
     ) -> Self {
-        let shape = Box::new(Plane::new(UnitVector3::new_normalize(Vector3::from(
-            normal,
-        ))));
-
-        let iso = Isometry3::new(Vector3::from(position), Vector3::from(rotation));
+        #[allow(deprecated)]
         GameObject::new(
-            shape,
+            Box::new(Plane::new(UnitVector3::new_normalize(Vector3::from(
+                normal,
+            )))),
             color,
-            iso,
+            Isometry3::new(Vector3::from(position), Vector3::from(rotation)),
             velocity,
             angular_velocity,
             mass,
@@ -293,7 +329,9 @@ impl GameObject {
         )
     }
 
-    /// Creates a plane with given normal, zero rotations and velocities
+    /// Creates a plane with given normal, zero rotations and velocities, without checking the values
+    #[deprecated(note = "use GameObject::plane_default")]
+    #[allow(non_snake_case)]
     pub fn Plane_default(
         normal: [f32; 3],
         color: [u8; 3],
@@ -302,20 +340,31 @@ impl GameObject {
         bounciness: f32,
         friction: f32,
     ) -> Self {
-        let shape = Box::new(Plane::new(UnitVector3::new_normalize(Vector3::from(
-            normal,
-        ))));
-
-        let iso = Isometry3::translation(position[0], position[1], position[2]);
-        GameObject::new(
-            shape,
-            color,
-            iso,
-            [0., 0., 0.],
-            [0., 0., 0.],
-            mass,
-            bounciness,
-            friction,
+        #[allow(deprecated)]
+        GameObject::Plane(
+            normal, color, position, [0.; 3], [0.; 3], [0.; 3], mass, bounciness, friction,
         )
     }
 }
+
+/// Builds the object of a constructor helper, static if `mass` is infinite and dynamic otherwise
+fn helper_build(
+    builder: GameObjectBuilder,
+    color: [u8; 3],
+    position: [f32; 3],
+    mass: f32,
+    bounciness: f32,
+    friction: f32,
+) -> Result<GameObject, GameObjectError> {
+    let builder = if mass == INFINITY {
+        builder.body_type(BodyType::Static)
+    } else {
+        builder.body_type(BodyType::Dynamic).mass(mass)
+    };
+    builder
+        .color(color)
+        .translation(position)
+        .bounciness(bounciness)
+        .friction(friction)
+        .build()
+}
@@ -0,0 +1,125 @@
+/// How the coefficients of two materials in contact are combined into one.
+/// When the materials use different rules, the one declared last wins
+/// (`Max` > `Multiply` > `Min` > `Average` > `GeometricMean`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CombineRule {
+    /// sqrt(a * b)
+    GeometricMean,
+    /// (a + b) / 2
+    Average,
+    /// The smallest of a and b
+    Min,
+    /// a * b
+    Multiply,
+    /// The largest of a and b
+    Max,
+}
+
+impl CombineRule {
+    /// Combines the coefficients `a` and `b` with this rule
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            CombineRule::GeometricMean => (a * b).sqrt(),
+            CombineRule::Average => 0.5 * (a + b),
+            CombineRule::Min => a.min(b),
+            CombineRule::Multiply => a * b,
+            CombineRule::Max => a.max(b),
+        }
+    }
+
+    /// The rule used when a material with rule `self` touches one with rule `other`
+    pub fn resolve(self, other: CombineRule) -> CombineRule {
+        self.max(other)
+    }
+}
+
+/// Surface properties of an object, usually shared between many objects through an `Arc`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysicsMaterial {
+    /// Name of the material, like "ice" or "rubber", empty for materials made for a single object
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: String,
+    /// Friction coefficient holding objects at rest relative to each other
+    pub static_friction: f32,
+    /// Friction coefficient slowing down objects sliding against each other
+    pub dynamic_friction: f32,
+    /// How much the objects resist rolling and spinning against each other [m], the maximum torque is this times the normal impulse
+    pub rolling_friction: f32,
+    /// Coefficient of restitution, 0 for no bounce and 1 for a perfectly elastic bounce
+    pub bounciness: f32,
+    /// How the frictions of two materials are combined
+    pub friction_combine: CombineRule,
+    /// How the bouncinesses of two materials are combined
+    pub bounciness_combine: CombineRule,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        PhysicsMaterial::new(0.5, 0.5)
+    }
+}
+
+impl PhysicsMaterial {
+    /// Creates an unnamed material with the same static and dynamic `friction`, no rolling friction and `bounciness`.
+    /// Frictions are combined by their geometric mean and the smallest bounciness is used.
+    pub fn new(friction: f32, bounciness: f32) -> PhysicsMaterial {
+        PhysicsMaterial {
+            name: String::new(),
+            static_friction: friction,
+            dynamic_friction: friction,
+            rolling_friction: 0.,
+            bounciness,
+            friction_combine: CombineRule::GeometricMean,
+            bounciness_combine: CombineRule::Min,
+        }
+    }
+
+    /// Sets the name of the material
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets separate static and dynamic friction coefficients
+    pub fn with_frictions(mut self, static_friction: f32, dynamic_friction: f32) -> Self {
+        self.static_friction = static_friction;
+        self.dynamic_friction = dynamic_friction;
+        self
+    }
+
+    /// Sets the rolling friction
+    pub fn with_rolling_friction(mut self, rolling_friction: f32) -> Self {
+        self.rolling_friction = rolling_friction;
+        self
+    }
+
+    /// Sets the combine rules for friction and bounciness
+    pub fn with_combine_rules(mut self, friction: CombineRule, bounciness: CombineRule) -> Self {
+        self.friction_combine = friction;
+        self.bounciness_combine = bounciness;
+        self
+    }
+
+    /// The coefficients used for a contact between materials `self` and `other`
+    pub fn combine(&self, other: &PhysicsMaterial) -> CombinedMaterial {
+        let friction = self.friction_combine.resolve(other.friction_combine);
+        let bounciness = self.bounciness_combine.resolve(other.bounciness_combine);
+        CombinedMaterial {
+            static_friction: friction.combine(self.static_friction, other.static_friction),
+            dynamic_friction: friction.combine(self.dynamic_friction, other.dynamic_friction),
+            rolling_friction: friction.combine(self.rolling_friction, other.rolling_friction),
+            bounciness: bounciness.combine(self.bounciness, other.bounciness),
+        }
+    }
+}
+
+/// The coefficients of a contact between two materials
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombinedMaterial {
+    pub static_friction: f32,
+    pub dynamic_friction: f32,
+    pub rolling_friction: f32,
+    pub bounciness: f32,
+}
@@ -1,61 +1,438 @@
-use crate::collision::*;
-use crate::shapes::bounding_volume::BoundingVolume;
+use crate::collision::{
+    bvh::Bvh,
+    distance::{distance, sphere_plane_distance, DistanceResult},
+    time_of_impact::{time_of_impact, ShapeCastHit},
+    *,
+};
+use crate::shapes::{
+    bounding_volume::{BoundingVolume, AABB},
+    ray::Ray,
+    raycast::RayCastResult,
+    shape::Shape,
+    sphere::Sphere,
+};
+use collider::Collider;
 use game_object::GameObject;
-use kiss3d::nalgebra::{Translation, Unit, UnitVector3, Vector3};
+use material::CombinedMaterial;
+use nalgebra::{Isometry3, Point3, Translation, Unit, UnitVector3, Vector3};
+use std::cell::OnceCell;
 use std::cmp::min;
 
+pub mod builder;
+pub mod character_controller;
+pub mod collider;
 pub mod game_object;
+pub mod material;
+pub mod mouse_joint;
+pub mod rigid_body;
+pub mod scenarios;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod snapshot;
+pub mod trajectory;
 mod tests;
 
 // For gravity!!!
 const g: f32 = 9.82;
+
+/// Identifies a GameObject in a PhysicsScene. Every added object gets a new handle, counting up from 0,
+/// which stays the same until the object is removed and is never given to another object.
+/// Handles are ordered by when their objects were added, like `PhysicsScene::objects`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyHandle(pub(crate) usize);
+
+/// Predicate deciding if an object takes part in a scene query, `None` lets every object take part
+pub type QueryFilter<'a> = Option<&'a dyn Fn(BodyHandle, &GameObject) -> bool>;
+
+/// Identifies a collider as (object index, collider index within the object)
+type ColliderRef = (usize, usize);
+
 ///The main physics scene containing physics enabled GameObjects
+///
+/// The simulation is deterministic: the same scene, built by adding the same objects in the same order,
+/// gives bit-identical results every time it's stepped with the same time steps, also after `restore`.
+/// Collision pairs are resolved in ascending order of their colliders and no hash maps are iterated.
+/// Results do depend on the insertion order, and may differ between platforms whose `sin`, `cos` and `exp` differ.
+/// Use `state_hash` to compare runs.
 pub struct PhysicsScene {
     objects: Vec<GameObject>,
+    // The handle of each object, ascending since handles are never reused
+    handles: Vec<BodyHandle>,
+    // The handle given to the next added object
+    next_handle: usize,
+    // Tree over the colliders' AABBs at their current positions, used by the queries.
+    // It's emptied when the objects change and built again when it's next needed.
+    tree: OnceCell<QueryTree>,
+    // Pairs of objects with overlapping sensors found in the last update
+    sensor_intersections: Vec<(BodyHandle, BodyHandle)>,
+    // Touching pairs of objects found in the last update
+    contacts: Vec<(BodyHandle, BodyHandle, CollisionManifold)>,
 }
 
 impl PhysicsScene {
     ///Create a new empty PhysicsScene
     pub fn new() -> PhysicsScene {
-        PhysicsScene { objects: vec![] }
+        PhysicsScene {
+            objects: vec![],
+            handles: vec![],
+            next_handle: 0,
+            tree: OnceCell::new(),
+            sensor_intersections: vec![],
+            contacts: vec![],
+        }
     }
 
-    /// Add a GameObject to the scene
-    pub fn add(&mut self, object: GameObject) {
+    /// Add a GameObject to the scene and returns its handle
+    pub fn add(&mut self, object: GameObject) -> BodyHandle {
+        let handle = BodyHandle(self.next_handle);
+        self.next_handle += 1;
         self.objects.push(object);
+        self.handles.push(handle);
+        self.outdate_bvh();
+        handle
     }
 
-    /// Remove a GameObject by index from the scene
-    pub fn remove(&mut self, index: usize) {
-        self.objects.remove(index);
+    /// Remove the GameObject with `handle` from the scene and return it, `None` if there is none.
+    /// The objects after it move down in `objects`, the handles of all other objects stay valid.
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<GameObject> {
+        let index = self.index_of(handle)?;
+        self.handles.remove(index);
+        let object = self.objects.remove(index);
+        self.outdate_bvh();
+        Some(object)
     }
 
     pub fn objects(&self) -> &[GameObject] {
         &self.objects
     }
 
-    /// Updates the physics in the scene, such as collisions
+    /// The handles of the objects, in the same order as `objects`
+    pub fn handles(&self) -> &[BodyHandle] {
+        &self.handles
+    }
+
+    /// The GameObject with the given handle, if it exists
+    pub fn get(&self, handle: BodyHandle) -> Option<&GameObject> {
+        Some(&self.objects[self.index_of(handle)?])
+    }
+
+    /// The GameObject with the given handle for modification, if it exists.
+    /// Changes to its position and colliders are seen by the following queries.
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut GameObject> {
+        let index = self.index_of(handle)?;
+        self.outdate_bvh();
+        Some(&mut self.objects[index])
+    }
+
+    /// The index in `objects` of the object with `handle`, if it exists
+    fn index_of(&self, handle: BodyHandle) -> Option<usize> {
+        self.handles.binary_search(&handle).ok()
+    }
+
+    /// The tree over the colliders' AABBs used by the queries, built first if the objects changed.
+    /// Its leaves number the colliders of all objects in order.
+    pub fn bvh(&self) -> &Bvh {
+        &self.tree().bvh
+    }
+
+    /// Pairs of objects, as (lower handle, higher handle), whose sensor colliders overlapped something in the last update
+    pub fn sensor_intersections(&self) -> &[(BodyHandle, BodyHandle)] {
+        &self.sensor_intersections
+    }
+
+    /// The contacts between colliders that aren't sensors found in the last update, in the order they were resolved.
+    /// Each is (lower handle, higher handle, manifold) with the manifold as found by the narrow phase.
+    pub fn contacts(&self) -> &[(BodyHandle, BodyHandle, CollisionManifold)] {
+        &self.contacts
+    }
+
+    /// Distance and closest points between the closest colliders of two objects.
+    /// Returns `None` if a handle is invalid or no pair of their shapes is supported.
+    pub fn distance(&self, handle_1: BodyHandle, handle_2: BodyHandle) -> Option<DistanceResult> {
+        let (object_1, object_2) = (self.get(handle_1)?, self.get(handle_2)?);
+        let mut closest: Option<DistanceResult> = None;
+        for collider_1 in object_1.colliders() {
+            for collider_2 in object_2.colliders() {
+                let result = distance(
+                    collider_1.shape(),
+                    &collider_1.world_position(&object_1.position),
+                    collider_2.shape(),
+                    &collider_2.world_position(&object_2.position),
+                );
+                closest = match (closest, result) {
+                    (Some(current), Some(result)) if result.distance < current.distance => {
+                        Some(result)
+                    }
+                    (None, result) => result,
+                    (current, _) => current,
+                };
+            }
+        }
+        closest
+    }
+
+    /// The query tree over the objects' current positions, built if they changed since it was last used
+    fn tree(&self) -> &QueryTree {
+        self.tree.get_or_init(|| QueryTree::new(&self.objects))
+    }
+
+    /// Throws away the query tree after the objects changed, so the next query builds it again.
+    /// Building it only when it's needed keeps adding many objects in a row fast.
+    fn outdate_bvh(&mut self) {
+        self.tree.take();
+    }
+
+    /// Returns the first object hit by `ray` within distance `max_toi`.
+    /// Objects for which `filter` returns false are ignored.
+    pub fn cast_ray(
+        &self,
+        ray: &Ray,
+        max_toi: f32,
+        filter: QueryFilter,
+    ) -> Option<(BodyHandle, RayCastResult)> {
+        self.cast_ray_all(ray, max_toi, filter).into_iter().next()
+    }
+
+    /// Returns all objects hit by `ray` within distance `max_toi`, sorted by distance.
+    /// Objects for which `filter` returns false and sensors are ignored.
+    pub fn cast_ray_all(
+        &self,
+        ray: &Ray,
+        max_toi: f32,
+        filter: QueryFilter,
+    ) -> Vec<(BodyHandle, RayCastResult)> {
+        let mut hits: Vec<(BodyHandle, RayCastResult)> = self
+            .filtered(self.bvh().intersect_ray(ray, max_toi), filter)
+            .filter(|(_, _, collider, _)| !collider.sensor)
+            .filter_map(|(handle, _, collider, position)| {
+                let result = collider.shape().ray_cast(&position, ray);
+                if result.hit && result.distance <= max_toi {
+                    Some((handle, result))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        hits.sort_by(|(handle_a, a), (handle_b, b)| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(handle_a.cmp(handle_b))
+        });
+        // Only the closest hit of objects with several colliders
+        let mut seen = Vec::new();
+        hits.retain(|(handle, _)| {
+            let first = !seen.contains(handle);
+            seen.push(*handle);
+            first
+        });
+        hits
+    }
+
+    /// Returns the objects whose shape contains `point`
+    pub fn intersections_with_point(
+        &self,
+        point: &Point3<f32>,
+        filter: QueryFilter,
+    ) -> Vec<BodyHandle> {
+        let mut handles: Vec<BodyHandle> = self
+            .filtered(self.bvh().intersect_point(point), filter)
+            .filter(|(_, _, collider, position)| collider.shape().contains_point(position, point))
+            .map(|(handle, ..)| handle)
+            .collect();
+        handles.dedup();
+        handles
+    }
+
+    /// Returns the objects whose shape overlaps the axis-aligned box `aabb`
+    pub fn intersections_with_aabb(
+        &self,
+        aabb: &AABB,
+        filter: QueryFilter,
+    ) -> Vec<BodyHandle> {
+        let mut handles: Vec<BodyHandle> = self
+            .filtered(self.bvh().intersect_aabb(aabb), filter)
+            .filter(|(_, _, collider, position)| {
+                let shape = collider.shape();
+                if let Some(sphere) = shape.as_sphere() {
+                    aabb_sphere(aabb, sphere, position)
+                } else if let Some(plane) = shape.as_plane() {
+                    aabb_plane(aabb, plane, position)
+                } else {
+                    // Fall back to the AABB of the shape for shapes without an exact test
+                    shape.compute_aabb(position).interects(aabb)
+                }
+            })
+            .map(|(handle, ..)| handle)
+            .collect();
+        handles.dedup();
+        handles
+    }
+
+    /// Returns the objects whose shape overlaps a sphere with `radius` at `center`
+    pub fn intersections_with_sphere(
+        &self,
+        center: &Point3<f32>,
+        radius: f32,
+        filter: QueryFilter,
+    ) -> Vec<BodyHandle> {
+        let query_sphere = Sphere::new(radius);
+        let query_iso = Isometry3::translation(center.x, center.y, center.z);
+        let query_aabb = query_sphere.aabb(&query_iso);
+        let mut handles: Vec<BodyHandle> = self
+            .filtered(self.bvh().intersect_aabb(&query_aabb), filter)
+            .filter(|(_, _, collider, position)| {
+                let shape = collider.shape();
+                if let Some(sphere) = shape.as_sphere() {
+                    sphere_sphere(&query_sphere, sphere, &query_iso, position)
+                } else if let Some(plane) = shape.as_plane() {
+                    // A solid half-space, like for `distance` and `contains_point`
+                    sphere_plane_distance(&query_sphere, &query_iso, plane, position)
+                        .is_overlapping()
+                } else if let Some(result) = distance(&query_sphere, &query_iso, shape, position) {
+                    result.is_overlapping()
+                } else {
+                    shape.compute_aabb(position).interects(&query_aabb)
+                }
+            })
+            .map(|(handle, ..)| handle)
+            .collect();
+        handles.dedup();
+        handles
+    }
+
+    /// Sweeps `shape` from `position` along `velocity` and returns the first object it hits within time `max_toi`.
+    /// The objects in the scene are treated as standing still during the sweep, sensors are ignored.
+    pub fn cast_shape(
+        &self,
+        shape: &dyn Shape,
+        position: &Isometry3<f32>,
+        velocity: &Vector3<f32>,
+        max_toi: f32,
+        filter: QueryFilter,
+    ) -> Option<(BodyHandle, ShapeCastHit)> {
+        // Everything the shape can touch is inside the AABB swept from start to end
+        let mut swept_aabb = shape.compute_aabb(position);
+        let mut end_aabb = swept_aabb;
+        end_aabb.translate(&(velocity * max_toi));
+        swept_aabb = swept_aabb.merged(&end_aabb);
+
+        let still = Vector3::zeros();
+        self.filtered(self.bvh().intersect_aabb(&swept_aabb), filter)
+            .filter(|(_, _, collider, _)| !collider.sensor)
+            .filter_map(|(handle, _, collider, collider_position)| {
+                time_of_impact(
+                    shape,
+                    position,
+                    velocity,
+                    collider.shape(),
+                    &collider_position,
+                    &still,
+                    max_toi,
+                )
+                .map(|hit| (handle, hit))
+            })
+            .fold(None, |closest: Option<(BodyHandle, ShapeCastHit)>, hit| {
+                match closest {
+                    Some(closest) if closest.1.toi <= hit.1.toi => Some(closest),
+                    _ => Some(hit),
+                }
+            })
+    }
+
+    /// Turns candidate leaf indices from the tree into handles, objects, colliders and their world poses,
+    /// for the objects accepted by `filter` in ascending order
+    fn filtered<'a>(
+        &'a self,
+        mut candidates: Vec<usize>,
+        filter: QueryFilter<'a>,
+    ) -> impl Iterator<Item = (BodyHandle, &'a GameObject, &'a Collider, Isometry3<f32>)> + 'a {
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .map(move |index| self.tree().colliders[index])
+            .filter(move |(object, _)| {
+                filter.is_none_or(|filter| filter(self.handles[*object], &self.objects[*object]))
+            })
+            .map(move |(index, collider)| {
+                let object = &self.objects[index];
+                let collider = &object.colliders()[collider];
+                let position = collider.world_position(&object.position);
+                (self.handles[index], object, collider, position)
+            })
+    }
+
+    /// Updates the physics in the scene, such as collisions.
+    /// Collisions are resolved one pair at a time in the order given by `broad_phase`, then the objects are integrated in insertion order.
     pub fn update(&mut self, time_step: f32) {
         // Physics loop
 
-        // Detect collisions
-        let collision_pairs = broad_phase(&self.objects);
-        let manifolds = narrow_phase(&self.objects, &collision_pairs);
+        // Kinematic objects moving to a target pose need their velocity before collisions are resolved
+        for object in &mut self.objects {
+            object.infer_kinematic_velocity(time_step);
+            // The objects may have been turned since the last update
+            object.update_world_inertia();
+        }
+
+        // Detect collisions, with the tree the queries since the last update used if there is one.
+        // The objects move at the end of the update, so it's thrown away then.
+        let tree = self
+            .tree
+            .take()
+            .unwrap_or_else(|| QueryTree::new(&self.objects));
+        let collision_pairs = broad_phase(&self.objects, &tree);
+        let manifolds = manifolds(&self.objects, &tree.colliders, &collision_pairs);
+        let colliders = tree.colliders;
+        self.sensor_intersections.clear();
+        self.contacts.clear();
 
         // Resolve collisions & apply impulse + friction
         for (i, manifold) in manifolds.iter().enumerate() {
+            let (collider_1, collider_2) = collision_pairs[i];
+            let (index, (collider_1, collider_2)) = (
+                (colliders[collider_1].0, colliders[collider_2].0),
+                (colliders[collider_1].1, colliders[collider_2].1),
+            );
+            let collider_1 = &self.objects[index.0].colliders()[collider_1];
+            let collider_2 = &self.objects[index.1].colliders()[collider_2];
+
+            // Sensors only report that they overlap
+            if collider_1.sensor || collider_2.sensor {
+                let pair = (self.handles[index.0], self.handles[index.1]);
+                if manifold.colliding && !self.sensor_intersections.contains(&pair) {
+                    self.sensor_intersections.push(pair);
+                }
+                continue;
+            }
+
             if manifold.colliding {
-                let index = &collision_pairs[i];
+                self.contacts.push((
+                    self.handles[index.0],
+                    self.handles[index.1],
+                    manifold.clone(),
+                ));
+            }
+
+            // Objects that can't be moved by collisions have nothing to resolve between them
+            if manifold.colliding
+                && (self.objects[index.0].is_dynamic() || self.objects[index.1].is_dynamic())
+            {
                 let contacts = manifold.contacts.len() as f32;
-                let inv_tensor_1 = self.objects[index.0].inv_tensor();
-                let inv_tensor_2 = self.objects[index.1].inv_tensor();
+                // Friction and bounciness for this pair, according to the materials' combine rules
+                let material = collider_1.material().combine(collider_2.material());
 
                 // Calculate impulse for every contact point in collision
                 for (_, contact) in manifold.contacts.iter().enumerate() {
                     let manifold_normal = &manifold.normal;
 
-                    let [(impulse1, friction1), (impulse2, friction2)] =
-                        self.calculate_impulse(index.0, index.1, manifold_normal, &contact.coords);
+                    let [(impulse1, friction1, rolling1), (impulse2, friction2, rolling2)] = self
+                        .calculate_impulse(
+                            index.0,
+                            index.1,
+                            &material,
+                            manifold_normal,
+                            &contact.coords,
+                        );
 
                     // Change velocity of object_1:
                     self.objects[index.0].add_linear_impulse(
@@ -63,6 +440,7 @@ impl PhysicsScene {
                     );
                     self.objects[index.0]
                         .add_rotational_impulse(contact, &manifold.normal.scale(-impulse1));
+                    self.objects[index.0].add_angular_impulse(&(rolling1 / contacts));
 
                     // Change velocity of object_2:
                     self.objects[index.1].add_linear_impulse(
@@ -70,6 +448,7 @@ impl PhysicsScene {
                     );
                     self.objects[index.1]
                         .add_rotational_impulse(contact, &manifold.normal.scale(impulse2));
+                    self.objects[index.1].add_angular_impulse(&(rolling2 / contacts));
                 }
             }
         }
@@ -83,27 +462,30 @@ impl PhysicsScene {
         &self,
         index_1: usize,
         index_2: usize,
+        material: &CombinedMaterial,
         manifold_normal: &Unit<Vector3<f32>>,
         contact_point: &Vector3<f32>,
-    ) -> [(f32 /* impulse */, Vector3<f32> /*friction */); 2] {
+    ) -> [(
+        f32,          /* impulse */
+        Vector3<f32>, /*friction */
+        Vector3<f32>, /* rolling friction */
+    ); 2] {
         let object_1 = &self.objects[index_1];
         let object_2 = &self.objects[index_2];
         // Relative position from center of mass to contact point for respective object
-        let r_1: &Vector3<f32> = &(contact_point - object_1.position.translation.vector);
-        let r_2: &Vector3<f32> = &(contact_point - object_2.position.translation.vector);
-        // Mass for respective object
-        let invmass_1 = object_1.inv_mass();
-        let invmass_2 = object_2.inv_mass();
+        let r_1: &Vector3<f32> = &(contact_point - object_1.center_of_mass().coords);
+        let r_2: &Vector3<f32> = &(contact_point - object_2.center_of_mass().coords);
+        // Inverse mass for respective object along the normal, locked axes act as infinite mass
+        let invmass_1 = object_1.inv_mass_along(manifold_normal);
+        let invmass_2 = object_2.inv_mass_along(manifold_normal);
         // Inverse inertia tensor for respective object
         let inv_tensor_1 = object_1.inv_tensor();
         let inv_tensor_2 = object_2.inv_tensor();
-        // Relative velocity
-        let v_r = (object_2.velocity + object_2.angular_velocity.cross(r_2)) // Linear + rotational velocity for object 1
-            - (object_1.velocity + object_1.angular_velocity.cross(r_1)); // Linear + rotational velocity for object 2
-
+        // Relative velocity, kinematic objects contribute the velocity they are moved with
+        let v_r = object_2.velocity_at_point(r_2) - object_1.velocity_at_point(r_1);
         // COLLISION:
-        // Coefficient of resitution (e), use smallest BOUNCINESS for the objects
-        let e = object_1.bounciness().min(object_2.bounciness());
+        // Coefficient of resitution (e)
+        let e = material.bounciness;
         // Magnitude of impulse used to calculate new velocities
         // Some of these multiplications may look like possible division by zero if inverse_mass = 0 for both objects. However, that'd mean they're both immovable which means they can't collide. Could also be added as an extra check in broad_phase just to be sure.
         let impulse_magnitude = -(1. + e) * (v_r.dot(manifold_normal))
@@ -113,27 +495,52 @@ impl PhysicsScene {
                 + manifold_normal.dot(&(inv_tensor_2 * (r_2.cross(manifold_normal))).cross(r_2)));
 
         // FRICTION:
-        // Tangent vector for the collision
-        let tangent_vector = (v_r - manifold_normal.scale(v_r.dot(manifold_normal))).normalize();
-        // Magnitude of friction
-        let mut friction_magnitude = -(v_r.dot(&tangent_vector))
-            / (invmass_1
-                + invmass_2
-                + manifold_normal.dot(&(inv_tensor_1 * (r_1.cross(&tangent_vector))).cross(r_1))
-                + manifold_normal.dot(&(inv_tensor_2 * (r_2.cross(&tangent_vector))).cross(r_2)));
-        let friction = (object_1.friction() * object_2.friction()).sqrt();
-        friction_magnitude = friction_magnitude
-            .max(-impulse_magnitude * friction)
-            .min(impulse_magnitude * friction);
+        // Tangent vector for the collision, there is none for a head-on collision
+        let tangent_vector = (v_r - manifold_normal.scale(v_r.dot(manifold_normal)))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::zeros);
+        // Magnitude of friction needed to stop the sliding
+        let inv_mass_tangent = object_1.inv_mass_along(&tangent_vector)
+            + object_2.inv_mass_along(&tangent_vector)
+            + manifold_normal.dot(&(inv_tensor_1 * (r_1.cross(&tangent_vector))).cross(r_1))
+            + manifold_normal.dot(&(inv_tensor_2 * (r_2.cross(&tangent_vector))).cross(r_2));
+        let mut friction_magnitude = if inv_mass_tangent.abs() > f32::EPSILON {
+            -(v_r.dot(&tangent_vector)) / inv_mass_tangent
+        } else {
+            0.
+        };
+        // Static friction can stop the sliding completely, otherwise it's slowed down by dynamic friction (Coulomb's law)
+        if friction_magnitude.abs() > impulse_magnitude.abs() * material.static_friction {
+            let limit = impulse_magnitude.abs() * material.dynamic_friction;
+            friction_magnitude = friction_magnitude.max(-limit).min(limit);
+        }
+
+        // ROLLING FRICTION:
+        // Angular impulse opposing the objects' relative rotation, limited by the normal impulse
+        let w_r = object_2.angular_velocity - object_1.angular_velocity;
+        let rolling = match w_r.try_normalize(f32::EPSILON) {
+            Some(axis) if material.rolling_friction > 0. => {
+                let inv_inertia = axis.dot(&((inv_tensor_1 + inv_tensor_2) * axis));
+                let needed = if inv_inertia > f32::EPSILON {
+                    w_r.norm() / inv_inertia
+                } else {
+                    0.
+                };
+                axis * needed.min(impulse_magnitude.abs() * material.rolling_friction)
+            }
+            _ => Vector3::zeros(),
+        };
 
         [
             (
-                impulse_magnitude * invmass_1,
-                tangent_vector * friction_magnitude * invmass_1,
+                impulse_magnitude * object_1.inv_mass(),
+                tangent_vector * friction_magnitude * object_1.inv_mass(),
+                rolling,
             ),
             (
-                impulse_magnitude * invmass_2,
-                tangent_vector * friction_magnitude * invmass_2,
+                impulse_magnitude * object_2.inv_mass(),
+                tangent_vector * friction_magnitude * object_2.inv_mass(),
+                -rolling,
             ),
         ]
     }
@@ -142,9 +549,10 @@ impl PhysicsScene {
     fn update_positions(&mut self, time_step: f32) {
         let gravity: Vector3<f32> = Vector3::new(0., -g, 0.); // would declare as constant Vector3 but our nalgebra is too outdated for that atm
         for object in &mut self.objects {
-            // If object is immovable, aka object has infinite mass, then don't apply gravity (as it would be an infinite force)
-            if object.inv_mass() > f32::EPSILON {
-                object.add_force(time_step * gravity * object.mass());
+            // Only dynamic objects are affected by gravity, the rest have infinite mass or are moved by the user
+            if object.is_dynamic() {
+                let weight = time_step * gravity * object.mass();
+                object.add_force(weight);
             }
             // Integrate one time step
             object.integrate(time_step);
@@ -152,39 +560,89 @@ impl PhysicsScene {
     }
 }
 
-/// The broad phase, where we check for possible collisions using AABB.
-/// Returns indices for collision pairs
-fn broad_phase(objects: &Vec<GameObject>) -> Vec<(usize, usize)> {
-    // This is a naive solution at O(n^2), the plan is to do a Bounding Volume Tree at some point
-    let mut collisions: Vec<(usize, usize)> = Vec::with_capacity(objects.len() * objects.len());
-    for current_i in 0..objects.len() {
-        let current = &objects[current_i];
-        for test_i in current_i..objects.len() {
-            let test = &objects[test_i];
-            if current_i != test_i
-                && current
-                    .shape()
-                    .compute_aabb(&current.position)
-                    .interects(&test.shape().compute_aabb(&test.position))
-            {
-                collisions.push((current_i, test_i));
-            }
-        }
+/// All colliders of the objects in order, their index in this list is used by the tree and the collision pairs
+fn collider_refs(objects: &[GameObject]) -> Vec<ColliderRef> {
+    objects
+        .iter()
+        .enumerate()
+        .flat_map(|(i, object)| (0..object.colliders().len()).map(move |j| (i, j)))
+        .collect()
+}
+
+/// The tree used by the queries of a PhysicsScene
+struct QueryTree {
+    bvh: Bvh,
+    // The collider of each leaf index in the tree
+    colliders: Vec<ColliderRef>,
+}
+
+impl QueryTree {
+    /// Builds a Bounding Volume Tree over the colliders' AABBs at their current positions
+    fn new(objects: &[GameObject]) -> QueryTree {
+        let colliders = collider_refs(objects);
+        let bvh = Bvh::build(
+            colliders
+                .iter()
+                .enumerate()
+                .map(|(i, &(object, collider))| {
+                    let object = &objects[object];
+                    let collider = &object.colliders()[collider];
+                    let aabb = collider
+                        .shape()
+                        .compute_aabb(&collider.world_position(&object.position));
+                    (i, aabb)
+                })
+                .collect(),
+        );
+        QueryTree { bvh, colliders }
     }
-    return collisions;
 }
 
-/// Calculates collision manifolds for the given collision pairs.
+/// The broad phase, where we check for possible collisions using AABB.
+/// Returns indices for collision pairs of colliders, numbered in order over all objects' colliders
+/// (the same as the object indices if every object has a single collider), as `(lower, higher)` sorted in ascending order
+/// so that the order doesn't depend on the shape of the tree.
+/// Colliders of the same object and colliders whose groups don't interact are never paired.
+/// `tree` must be built over the current positions of `objects`.
+fn broad_phase(objects: &[GameObject], tree: &QueryTree) -> Vec<(usize, usize)> {
+    let colliders = &tree.colliders;
+    let collider = |i: usize| &objects[colliders[i].0].colliders()[colliders[i].1];
+    tree.bvh
+        .collision_pairs()
+        .into_iter()
+        .filter(|(a, b)| {
+            colliders[*a].0 != colliders[*b].0 && collider(*a).interacts_with(collider(*b))
+        })
+        .collect()
+}
+
+/// Calculates collision manifolds for the given collision pairs of colliders, numbered as in `broad_phase`.
 /// Returns a list of manifolds in the same order as `pairs`
-pub fn narrow_phase(
-    objects: &Vec<GameObject>,
-    pairs: &Vec<(usize, usize)>,
+pub fn narrow_phase(objects: &[GameObject], pairs: &[(usize, usize)]) -> Vec<CollisionManifold> {
+    manifolds(objects, &collider_refs(objects), pairs)
+}
+
+/// The narrow phase with the colliders `colliders` of `objects` already listed
+fn manifolds(
+    objects: &[GameObject],
+    colliders: &[ColliderRef],
+    pairs: &[(usize, usize)],
 ) -> Vec<CollisionManifold> {
     let mut manifolds: Vec<CollisionManifold> = Vec::with_capacity(pairs.len());
 
-    for (obj_1, obj_2) in pairs {
-        let obj_1 = &objects[*obj_1];
-        let obj_2 = &objects[*obj_2];
+    for (collider_1, collider_2) in pairs {
+        let (obj_1, collider_1) = colliders[*collider_1];
+        let (obj_2, collider_2) = colliders[*collider_2];
+        let obj_1 = &objects[obj_1];
+        let obj_2 = &objects[obj_2];
+        let (shape_1, shape_2) = (
+            obj_1.colliders()[collider_1].shape(),
+            obj_2.colliders()[collider_2].shape(),
+        );
+        let (position_1, position_2) = (
+            obj_1.colliders()[collider_1].world_position(&obj_1.position),
+            obj_2.colliders()[collider_2].world_position(&obj_2.position),
+        );
 
         if obj_1.velocity.x * obj_2.velocity.x > 0.0
             && obj_1.velocity.y * obj_2.velocity.y > 0.0
@@ -195,23 +653,33 @@ pub fn narrow_phase(
         }
 
         // pattern-match the specific collision
-        if let (Ok(sph_1), Ok(sph_2)) = (obj_1.shape().as_sphere(), obj_2.shape().as_sphere()) {
+        if let (Some(sph_1), Some(sph_2)) = (shape_1.as_sphere(), shape_2.as_sphere()) {
             let manifold =
-                CollisionManifold::sphere_sphere(&sph_1, &sph_2, &obj_1.position, &obj_2.position);
+                CollisionManifold::sphere_sphere(&sph_1, &sph_2, &position_1, &position_2);
             manifolds.push(manifold);
-        } else if let (Ok(plane), Ok(sphere)) =
-            (obj_1.shape().as_plane(), obj_2.shape().as_sphere())
+        } else if let (Some(plane), Some(sphere)) =
+            (shape_1.as_plane(), shape_2.as_sphere())
         {
             let mut manifold =
-                CollisionManifold::sphere_plane(&sphere, &plane, &obj_1.position, &obj_2.position);
+                CollisionManifold::sphere_plane(&sphere, &plane, &position_2, &position_1);
             manifold.normal = UnitVector3::new_normalize(manifold.normal.scale(-1.0));
             manifolds.push(manifold);
-        } else if let (Ok(sphere), Ok(plane)) =
-            (obj_1.shape().as_sphere(), obj_2.shape().as_plane())
+        } else if let (Some(sphere), Some(plane)) =
+            (shape_1.as_sphere(), shape_2.as_plane())
         {
             let mut manifold =
-                CollisionManifold::sphere_plane(&sphere, &plane, &obj_1.position, &obj_2.position);
+                CollisionManifold::sphere_plane(&sphere, &plane, &position_1, &position_2);
             manifolds.push(manifold);
+        } else if let Some(result) = distance(
+            shape_1,
+            &position_1,
+            shape_2,
+            &position_2,
+        ) {
+            manifolds.push(CollisionManifold::from_distance(&result));
+        } else {
+            // Keep the manifolds in the same order as the pairs
+            manifolds.push(CollisionManifold::new());
         }
     }
     return manifolds;
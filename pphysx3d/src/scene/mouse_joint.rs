@@ -0,0 +1,62 @@
+use nalgebra::{Point3, Vector3};
+
+use super::{BodyHandle, PhysicsScene};
+
+/// Pulls a point of an object towards a target with a damped spring, like dragging it with the mouse.
+/// The pull is scaled by the object's mass, so every object follows the same way.
+/// Only dynamic objects are pulled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseJoint {
+    /// The object being pulled
+    pub body: BodyHandle,
+    /// The point being pulled, in the object's space
+    pub local_anchor: Point3<f32>,
+    /// Where the point is pulled to, in world space
+    pub target: Point3<f32>,
+    /// Acceleration per metre between the point and the target [1/s^2]
+    pub stiffness: f32,
+    /// Acceleration against the point's velocity per m/s [1/s]
+    pub damping: f32,
+    /// The largest acceleration the spring gives [m/s^2], so objects far from the target don't shoot off
+    pub max_acceleration: f32,
+}
+
+impl MouseJoint {
+    /// Grabs the object with `body` at `point` in world space, with the target at the point.
+    /// Returns `None` if there is no object with `body`.
+    pub fn new(scene: &PhysicsScene, body: BodyHandle, point: Point3<f32>) -> Option<MouseJoint> {
+        let object = scene.get(body)?;
+        Some(MouseJoint {
+            body,
+            local_anchor: object.position.inverse_transform_point(&point),
+            target: point,
+            stiffness: 100.,
+            damping: 20.,
+            max_acceleration: 200.,
+        })
+    }
+
+    /// The pulled point in world space, `None` if the object is gone
+    pub fn anchor(&self, scene: &PhysicsScene) -> Option<Point3<f32>> {
+        scene
+            .get(self.body)
+            .map(|object| object.position * self.local_anchor)
+    }
+
+    /// Gives the object the spring's impulse for a step of `time_step`, call it before every `update`
+    pub fn apply(&self, scene: &mut PhysicsScene, time_step: f32) {
+        let object = match scene.get_mut(self.body) {
+            Some(object) if object.is_dynamic() => object,
+            _ => return,
+        };
+        let anchor = object.position * self.local_anchor;
+        let velocity = object.velocity_at_point(&(anchor - object.center_of_mass()));
+        let acceleration: Vector3<f32> =
+            (self.target - anchor) * self.stiffness - velocity * self.damping;
+        let velocity_change = acceleration.cap_magnitude(self.max_acceleration) * time_step;
+        // The linear impulse is a change of velocity, the rotational one takes the momentum
+        let momentum = velocity_change * object.mass();
+        object.add_linear_impulse(velocity_change);
+        object.add_rotational_impulse(&anchor, &momentum);
+    }
+}
@@ -0,0 +1,504 @@
+use std::hash::Hasher;
+
+use nalgebra::{Isometry3, Matrix3, Point3, Translation3, UnitQuaternion, Vector3};
+
+use super::{
+    builder::GameObjectError,
+    game_object::{BodyType, DAMPING, INFINITY},
+};
+use crate::shapes::mass_properties::MassProperties;
+
+/// How the gyroscopic torque of a spinning body, which makes tops precess, is integrated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GyroscopicMode {
+    /// Ignored, the angular velocity only changes through torques and impulses
+    Disabled,
+    /// Explicit Euler, cheap but unstable when spinning fast around an axis of intermediate inertia
+    Explicit,
+    /// Implicit Euler with one Newton step, stable for fast spins
+    #[default]
+    Implicit,
+}
+
+/// World axes along which a dynamic body can't move or around which it can't turn, as if its mass or inertia were infinite there.
+/// Kinematic bodies ignore their locks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockedAxes {
+    /// Locked x, y and z translation
+    pub translation: [bool; 3],
+    /// Locked rotation around x, y and z
+    pub rotation: [bool; 3],
+}
+
+impl LockedAxes {
+    /// Free to move and turn
+    pub const NONE: LockedAxes = LockedAxes::new([false; 3], [false; 3]);
+    /// Moves but never turns
+    pub const ROTATION: LockedAxes = LockedAxes::new([false; 3], [true; 3]);
+    /// Turns but never moves
+    pub const TRANSLATION: LockedAxes = LockedAxes::new([true; 3], [false; 3]);
+    /// Moves in the xy plane and only turns around z, for 2.5D games
+    pub const PLANE_XY: LockedAxes = LockedAxes::new([false, false, true], [true, true, false]);
+
+    pub const fn new(translation: [bool; 3], rotation: [bool; 3]) -> LockedAxes {
+        LockedAxes {
+            translation,
+            rotation,
+        }
+    }
+
+    /// 0 for the locked translation axes, 1 for the free ones
+    pub fn translation_mask(&self) -> Vector3<f32> {
+        mask(&self.translation)
+    }
+
+    /// 0 for the locked rotation axes, 1 for the free ones
+    pub fn rotation_mask(&self) -> Vector3<f32> {
+        mask(&self.rotation)
+    }
+}
+
+/// The dynamics state of a GameObject: mass, pose, velocities and accumulated forces.
+/// Its extent comes from the colliders attached to it.
+#[derive(Clone)]
+pub struct RigidBody {
+    body_type: BodyType,
+    inverse_mass: f32,               // [1/kg]
+    mass_properties: MassProperties, // In body space, relative to `position`
+    world_inv_inertia: Matrix3<f32>, // Inverse inertia tensor in world space at the current orientation
+    // Regular momentum stuff:
+    pub position: Isometry3<f32>, // includes a translation vector and a rotation part as an unit quaternion
+    pub velocity: Vector3<f32>,   // [m/s]
+    pub acceleration: Vector3<f32>, // [m/s^2]
+    force_accum: Vector3<f32>,    // Forces summed a la d'Alembert's principle [N]
+    // Angular momentum stuff:
+    // Orientation is stored in the unit quaternion of position
+    pub angular_velocity: Vector3<f32>, // Angular velocity [rad/s]
+    pub angular_acceleration: Vector3<f32>, // Angular acceleration [rad/s^2]
+    pub torque_accum: Vector3<f32>,     // Torque summed, same principle as force_accum [Nm]
+    next_position: Option<Isometry3<f32>>, // Target pose of a position based kinematic body
+    pub gyroscopic: GyroscopicMode,
+    // Damping, the velocity decays as e^(-damping * t) [1/s]:
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    // Speed limits, INFINITY for none:
+    pub max_linear_velocity: f32,  // [m/s]
+    pub max_angular_velocity: f32, // [rad/s]
+    pub locked_axes: LockedAxes,
+}
+
+impl RigidBody {
+    /// Create a new body at `position` with its center of mass at the origin and without any inertia, set them with `set_mass_properties`.
+    /// Bodies with infinite mass become `BodyType::Static`, all others `BodyType::Dynamic`.
+    pub fn new(
+        position: Isometry3<f32>,
+        velocity: [f32; 3],
+        angular_velocity: [f32; 3],
+        mass: f32,
+    ) -> RigidBody {
+        let inverse_mass = inverse_mass(mass);
+        let body_type = if inverse_mass == 0. {
+            BodyType::Static
+        } else {
+            BodyType::Dynamic
+        };
+
+        RigidBody {
+            body_type,
+            inverse_mass,
+            mass_properties: MassProperties::new(mass, Point3::origin(), Vector3::zeros()),
+            world_inv_inertia: Matrix3::zeros(),
+            position,
+            velocity: Vector3::from(velocity),
+            acceleration: Vector3::new(0., 0., 0.),
+            force_accum: Vector3::new(0., 0., 0.),
+            angular_velocity: Vector3::from(angular_velocity),
+            angular_acceleration: Vector3::new(0., 0., 0.),
+            torque_accum: Vector3::new(0., 0., 0.),
+            next_position: None,
+            gyroscopic: GyroscopicMode::default(),
+            linear_damping: DAMPING,
+            angular_damping: DAMPING,
+            max_linear_velocity: INFINITY,
+            max_angular_velocity: INFINITY,
+            locked_axes: LockedAxes::NONE,
+        }
+    }
+
+    /// How the object is moved by the PhysicsScene
+    pub fn body_type(&self) -> BodyType {
+        self.body_type
+    }
+
+    /// Change how the object is moved by the PhysicsScene.
+    /// Fails without changing anything when making a body dynamic whose mass isn't positive and finite.
+    pub fn set_body_type(&mut self, body_type: BodyType) -> Result<(), GameObjectError> {
+        let mass = self.mass();
+        if body_type == BodyType::Dynamic && !(mass > 0. && mass.is_finite()) {
+            return Err(GameObjectError::InvalidMass(mass));
+        }
+        self.body_type = body_type;
+        self.next_position = None;
+        Ok(())
+    }
+
+    /// True if the object is moved by forces and collisions
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic
+    }
+
+    /// True if the object is moved by the user through its velocity or target pose
+    pub fn is_kinematic(&self) -> bool {
+        matches!(
+            self.body_type,
+            BodyType::KinematicVelocityBased | BodyType::KinematicPositionBased
+        )
+    }
+
+    /// Set the pose a `BodyType::KinematicPositionBased` object reaches at the end of the next update.
+    /// Does nothing for other body types.
+    pub fn set_next_kinematic_position(&mut self, position: Isometry3<f32>) {
+        if self.body_type == BodyType::KinematicPositionBased {
+            self.next_position = Some(position);
+        }
+    }
+
+    /// Sets the velocities of a position based kinematic object so that it reaches its target pose in `dt`.
+    /// Without a target pose the object stands still.
+    pub(crate) fn infer_kinematic_velocity(&mut self, dt: f32) {
+        if self.body_type != BodyType::KinematicPositionBased {
+            return;
+        }
+        match self.next_position {
+            Some(target) if dt > 0. => {
+                // The velocity is the one of the center of mass
+                let center_of_mass = &self.mass_properties.local_center_of_mass;
+                self.velocity = (target * center_of_mass - self.center_of_mass()) / dt;
+                self.angular_velocity =
+                    (target.rotation * self.position.rotation.inverse()).scaled_axis() / dt;
+            }
+            _ => {
+                self.velocity = Vector3::zeros();
+                self.angular_velocity = Vector3::zeros();
+            }
+        }
+    }
+
+    /// Linear + rotational velocity of the point at offset `r` from the center of mass, static objects never move
+    pub fn velocity_at_point(&self, r: &Vector3<f32>) -> Vector3<f32> {
+        if self.body_type == BodyType::Static {
+            return Vector3::zeros();
+        }
+        self.velocity + self.angular_velocity.cross(r)
+    }
+
+    /// Changes the velocity by `impulse`, except along locked axes
+    pub fn add_linear_impulse(&mut self, impulse: Vector3<f32>) {
+        self.velocity += impulse.component_mul(&self.locked_axes.translation_mask());
+    }
+
+    pub fn add_rotational_impulse(&mut self, contact_point: &Point3<f32>, impulse: &Vector3<f32>) {
+        let center_of_mass = self.center_of_mass();
+        let torque: Vector3<f32> = (contact_point - center_of_mass).cross(&impulse);
+        let angular_acceleration = &self.inv_tensor() * torque;
+        self.angular_velocity += angular_acceleration; // NOT SURE IF THIS IS NECESSARY
+    }
+
+    /// Inverse of the inertia tensor around the center of mass in world space, zero for objects that aren't dynamic
+    /// and around locked axes. It follows the orientation of the body as of the last step or `update_world_inertia`.
+    pub fn inv_tensor(&self) -> Matrix3<f32> {
+        if self.is_dynamic() {
+            let free = Matrix3::from_diagonal(&self.locked_axes.rotation_mask());
+            return free * self.world_inv_inertia * free;
+        }
+        Matrix3::zeros()
+    }
+
+    /// Recomputes the world space inverse inertia tensor from the body's current orientation
+    pub fn update_world_inertia(&mut self) {
+        self.world_inv_inertia = self
+            .mass_properties
+            .world_inv_inertia(&self.position.rotation);
+    }
+
+    /// The body's mass properties in body space
+    pub fn mass_properties(&self) -> &MassProperties {
+        &self.mass_properties
+    }
+
+    /// The center of mass in world space
+    pub fn center_of_mass(&self) -> Point3<f32> {
+        self.position * self.mass_properties.local_center_of_mass
+    }
+
+    /// Apply an angular impulse [Nms] around the center of mass
+    pub fn add_angular_impulse(&mut self, impulse: &Vector3<f32>) {
+        self.angular_velocity += self.inv_tensor() * impulse;
+    }
+
+    /// Set the mass, center of mass and inertia in body space.
+    /// Infinite mass makes a dynamic body static.
+    pub fn set_mass_properties(&mut self, mass_properties: MassProperties) {
+        self.inverse_mass = inverse_mass(mass_properties.mass);
+        self.mass_properties = mass_properties;
+        self.update_world_inertia();
+        if self.inverse_mass == 0. && self.body_type == BodyType::Dynamic {
+            self.body_type = BodyType::Static;
+        }
+    }
+
+    /// All accumulated forces acting on the Object
+    pub fn force_accum(&self) -> &Vector3<f32> {
+        &self.force_accum
+    }
+
+    /// Add a force acting on the object to it's force accumulator
+    pub fn add_force(&mut self, force: Vector3<f32>) {
+        self.force_accum = self.force_accum + force;
+    }
+
+    /// All accumulated angular forces acting on the Object
+    pub fn torque_accum(&self) -> &Vector3<f32> {
+        &self.torque_accum
+    }
+
+    /// Add an angular force acting on the object to it's torque accumulator
+    pub fn add_angular_force(&mut self, force: Vector3<f32>) {
+        self.torque_accum = self.torque_accum + force;
+    }
+
+    /// Remove all accumulated forces acting on the Object
+    fn clear_accum(&mut self) {
+        // maybe don't have to create a new Vector3 idk yet
+        self.force_accum = Vector3::new(0., 0., 0.);
+        self.torque_accum = Vector3::new(0., 0., 0.);
+    }
+
+    // Pretty much just Explicit Euler, might want to change to something like Verlet
+    pub fn integrate(&mut self, dt: f32) {
+        match self.body_type {
+            BodyType::Static => {
+                self.clear_accum();
+                return;
+            }
+            BodyType::KinematicVelocityBased => {
+                self.move_center_of_mass(dt);
+                self.clear_accum();
+                return;
+            }
+            BodyType::KinematicPositionBased => {
+                // Snap to the target so that no error accumulates from the inferred velocity
+                if let Some(target) = self.next_position.take() {
+                    self.position = target;
+                    self.update_world_inertia();
+                }
+                self.clear_accum();
+                return;
+            }
+            BodyType::Dynamic => {}
+        }
+
+        self.apply_locks();
+        // A free body keeps its angular momentum, not its angular velocity
+        let momentum = self.angular_momentum();
+        self.angular_velocity = self.gyroscopic_velocity(dt);
+        self.apply_locks();
+
+        // Update position and rotation around the center of mass
+        self.move_center_of_mass(dt);
+        if self.has_gyroscopic_torque() {
+            self.angular_velocity = self.world_inv_inertia * momentum;
+        }
+
+        // Calculate acceleration from force
+        self.acceleration += self.inverse_mass * self.force_accum;
+        self.angular_acceleration += self.inv_tensor() * self.torque_accum;
+
+        // Calculate new velocity, damped the same amount per second whatever the time step
+        self.velocity =
+            (-dt * self.linear_damping).exp() * (self.velocity + dt * self.acceleration);
+        self.angular_velocity = (-dt * self.angular_damping).exp()
+            * (self.angular_velocity + dt * self.angular_acceleration);
+        self.velocity = self.velocity.cap_magnitude(self.max_linear_velocity);
+        self.angular_velocity = self
+            .angular_velocity
+            .cap_magnitude(self.max_angular_velocity);
+        self.apply_locks();
+
+        // (NOT SURE IF HAVE TO MAKE NEW ZERO VECTOR)
+        self.clear_accum();
+    }
+
+    /// The mass of the Object (1/inverse_mass)
+    pub fn mass(&self) -> f32 {
+        if self.inverse_mass != 0. {
+            return 1. / self.inverse_mass;
+        }
+        return INFINITY;
+    }
+
+    /// The inverse mass of the Object, 0 for objects that aren't dynamic
+    pub fn inv_mass(&self) -> f32 {
+        if self.is_dynamic() {
+            return self.inverse_mass;
+        }
+        return 0.;
+    }
+
+    /// The inverse mass felt by an impulse along the unit vector `direction`, locked axes don't give way
+    pub fn inv_mass_along(&self, direction: &Vector3<f32>) -> f32 {
+        let free = direction.component_mul(&self.locked_axes.translation_mask());
+        self.inv_mass() * free.dot(direction)
+    }
+
+    /// Convenience function to add to the object's velocity
+    pub fn add_velocity(&mut self, velocity: [f32; 3]) {
+        self.velocity += Vector3::from(velocity);
+    }
+
+    /// Convenience function to add to the object's acceleration
+    pub fn add_acceleration(&mut self, acceleration: [f32; 3]) {
+        self.acceleration += Vector3::from(acceleration);
+    }
+
+    /// Convenience function to add to the object's angular velocity
+    pub fn add_angularvelocity(&mut self, velocity: [f32; 3]) {
+        self.angular_velocity += Vector3::from(velocity);
+    }
+
+    /// Convenience function to add to the object's angular acceleration
+    pub fn add_angularacceleration(&mut self, acceleration: [f32; 3]) {
+        self.angular_acceleration += Vector3::from(acceleration);
+    }
+
+    /// Convenience function to set the object's translational position
+    pub fn set_translation(&mut self, position: [f32; 3]) {
+        self.position.translation = Translation3::new(position[0], position[1], position[2]);
+    }
+
+    /// Convenience function to set the object's rotation
+    pub fn set_rotation(&mut self, position: [f32; 3]) {
+        self.position.rotation = UnitQuaternion::new(Vector3::from(position));
+        self.update_world_inertia();
+    }
+
+    /// Angular momentum around the center of mass in world space [kg m^2/s]
+    pub fn angular_momentum(&self) -> Vector3<f32> {
+        let frame = (self.position.rotation * self.mass_properties.principal_inertia_frame)
+            .to_rotation_matrix();
+        let inertia = frame.matrix()
+            * Matrix3::from_diagonal(&self.mass_properties.principal_inertia)
+            * frame.matrix().transpose();
+        inertia * self.angular_velocity
+    }
+
+    /// True if the body's angular velocity changes through gyroscopic torque
+    fn has_gyroscopic_torque(&self) -> bool {
+        self.gyroscopic != GyroscopicMode::Disabled
+            && self.mass_properties.mass.is_finite()
+            && self
+                .mass_properties
+                .principal_inertia
+                .iter()
+                .all(|inertia| *inertia > f32::EPSILON)
+    }
+
+    /// The angular velocity to turn the body with during the next `dt`, including the gyroscopic torque.
+    /// Solves I dω/dt = -ω × Iω in the principal axes, where the inertia is diagonal.
+    fn gyroscopic_velocity(&self, dt: f32) -> Vector3<f32> {
+        if !self.has_gyroscopic_torque() {
+            return self.angular_velocity;
+        }
+        let inertia = self.mass_properties.principal_inertia;
+        let frame = self.position.rotation * self.mass_properties.principal_inertia_frame;
+        let omega = frame.inverse() * self.angular_velocity;
+        let momentum = inertia.component_mul(&omega);
+        let gyroscopic = dt * omega.cross(&momentum);
+
+        let omega = match self.gyroscopic {
+            GyroscopicMode::Implicit => {
+                // One Newton step on f(ω') = I(ω' - ω) + dt ω' × Iω' = 0, starting from ω
+                let jacobian = Matrix3::from_diagonal(&inertia)
+                    + dt * (omega.cross_matrix() * Matrix3::from_diagonal(&inertia)
+                        - momentum.cross_matrix());
+                match jacobian.try_inverse() {
+                    Some(inverse) => omega - inverse * gyroscopic,
+                    None => omega,
+                }
+            }
+            _ => omega - gyroscopic.component_div(&inertia),
+        };
+        frame * omega
+    }
+
+    /// Feeds the bits of everything that changes while simulating to `hasher`
+    pub(crate) fn hash_state(&self, hasher: &mut impl Hasher) {
+        let values = self
+            .position
+            .translation
+            .vector
+            .iter()
+            .chain(self.position.rotation.coords.iter())
+            .chain(self.velocity.iter())
+            .chain(self.angular_velocity.iter())
+            .chain(self.acceleration.iter())
+            .chain(self.angular_acceleration.iter())
+            .chain(self.force_accum.iter())
+            .chain(self.torque_accum.iter());
+        for value in values {
+            hasher.write_u32(value.to_bits());
+        }
+        match &self.next_position {
+            Some(target) => {
+                hasher.write_u8(1);
+                let target = target
+                    .translation
+                    .vector
+                    .iter()
+                    .chain(target.rotation.coords.iter());
+                for value in target {
+                    hasher.write_u32(value.to_bits());
+                }
+            }
+            None => hasher.write_u8(0),
+        }
+    }
+
+    /// Removes the velocities along and around the locked axes
+    fn apply_locks(&mut self) {
+        self.velocity
+            .component_mul_assign(&self.locked_axes.translation_mask());
+        self.angular_velocity
+            .component_mul_assign(&self.locked_axes.rotation_mask());
+    }
+
+    /// Moves the center of mass with the velocity and turns the body around it with the (world space) angular velocity for `dt`
+    fn move_center_of_mass(&mut self, dt: f32) {
+        let center_of_mass = self.center_of_mass() + dt * self.velocity;
+        self.position.rotation =
+            UnitQuaternion::new(dt * self.angular_velocity) * self.position.rotation;
+        self.position.translation.vector = center_of_mass.coords
+            - self.position.rotation * self.mass_properties.local_center_of_mass.coords;
+        self.update_world_inertia();
+    }
+}
+
+/// 0 where `locked` is true and 1 elsewhere
+fn mask(locked: &[bool; 3]) -> Vector3<f32> {
+    Vector3::from_iterator(locked.iter().map(|locked| if *locked { 0. } else { 1. }))
+}
+
+/// 1/mass, where infinite mass gives 0
+fn inverse_mass(mass: f32) -> f32 {
+    if mass >= INFINITY - f32::EPSILON {
+        0.
+    } else if mass <= f32::EPSILON {
+        // How should mass = 0 be handled?
+        // - It shouldn't be a thing
+        INFINITY
+    } else {
+        1. / mass
+    }
+}
@@ -0,0 +1,95 @@
+use super::{
+    game_object::{GameObject, INFINITY},
+    PhysicsScene,
+};
+
+/// Names of the built-in scenes, see `scenario`
+pub const SCENARIOS: [&str; 3] = ["spheres_plane", "demo", "demo_many"];
+
+/// The built-in scene called `name`, the examples of the same name show it in a window.
+/// Returns `None` for unknown names.
+pub fn scenario(name: &str) -> Option<PhysicsScene> {
+    let mut scene = PhysicsScene::new();
+    match name {
+        "spheres_plane" => {
+            scene.add(
+                GameObject::sphere_default(0.4, [0, 0, 0], [1.0, 2.0, 10.], 10., 1.0, 0.01)
+                    .unwrap(),
+            );
+            let mut sphere =
+                GameObject::sphere_default(0.4, [255, 0, 0], [0.0, 10.0, 10.], 10., 1.0, 0.01)
+                    .unwrap();
+            sphere.add_velocity([1.0, -10.0, 0.0]);
+            scene.add(sphere);
+            scene.add(
+                GameObject::plane_default(
+                    [0., 1., 0.],
+                    [255, 255, 255],
+                    [0.0, -1.0, 0.0],
+                    INFINITY,
+                    1.,
+                    0.1,
+                )
+                .unwrap(),
+            );
+        }
+        "demo" => {
+            let spheres = [
+                (0.4, [0, 0, 0], [1.0, 2.0, 10.], 10., [10.0, 0.0, 0.0]),
+                (0.4, [255, 0, 0], [0.0, 10.0, 10.], 8., [8.0, -5.0, 0.0]),
+                (0.5, [255, 100, 0], [2.0, 10.0, 10.], 10., [8.0, -5.0, 1.0]),
+                (0.1, [255, 0, 255], [-1.0, 10.0, 10.], 2., [8.0, 0.0, 0.0]),
+                (0.2, [255, 40, 0], [0.0, 5.0, 5.], 6., [3.0, -1.0, 0.0]),
+            ];
+            for (radius, color, position, mass, velocity) in spheres {
+                let mut sphere =
+                    GameObject::sphere_default(radius, color, position, mass, 1., 0.01).unwrap();
+                sphere.add_velocity(velocity);
+                scene.add(sphere);
+            }
+            add_box(&mut scene, 3.);
+        }
+        "demo_many" => {
+            // A cube of spheres thrown at the same corner
+            let width = 4;
+            for x in 0..width {
+                for y in 0..width {
+                    for z in 0..width {
+                        let mut sphere = GameObject::sphere_default(
+                            0.15 + 0.001 * x as f32 + 0.001 * y as f32 + 0.001 * z as f32,
+                            [
+                                ((255 / width) * x) as u8,
+                                ((255 / width) * y) as u8,
+                                ((255 / width) * z) as u8,
+                            ],
+                            [((x - 2) as f32), (y as f32), ((z + 3) as f32)],
+                            10.,
+                            1.,
+                            0.02,
+                        )
+                        .unwrap();
+                        sphere.add_velocity([x as f32 - 11.5, y as f32 - 12.3, z as f32 - 11.2]);
+                        scene.add(sphere);
+                    }
+                }
+            }
+            add_box(&mut scene, 5.);
+        }
+        _ => return None,
+    }
+    Some(scene)
+}
+
+/// A floor and four walls, the wall in +x at `right`
+fn add_box(scene: &mut PhysicsScene, right: f32) {
+    let planes = [
+        ([0., 1., 0.], [255, 255, 255], [0.0, -1.0, 0.0]),
+        ([1., 0., 0.], [0, 255, 255], [-4.0, 0.0, 0.0]),
+        ([-1., 0., 0.], [0, 255, 255], [right, 0.0, 0.0]),
+        ([0., 0., -1.], [0, 255, 0], [0.0, 0.0, 12.0]),
+        ([0., 0., 1.], [0, 255, 0], [0.0, 0.0, -2.0]),
+    ];
+    for (normal, color, position) in planes {
+        scene.add(GameObject::plane_default(normal, color, position, INFINITY, 1., 0.1).unwrap());
+    }
+}
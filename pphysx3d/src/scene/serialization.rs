@@ -0,0 +1,452 @@
+use std::{fmt, fs, io, path::Path, sync::Arc};
+
+use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion, UnitVector3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    builder::{self, validate_shape, GameObjectBuilder, GameObjectError},
+    collider::{Collider, CollisionGroups},
+    game_object::{BodyType, DAMPING, INFINITY},
+    material::PhysicsMaterial,
+    rigid_body::{GyroscopicMode, LockedAxes},
+    PhysicsScene,
+};
+use crate::shapes::{capsule::Capsule, plane::Plane, shape::Shape, sphere::Sphere};
+
+/// Version of the scene format written by this crate
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Reasons a scene can't be saved or loaded
+#[derive(Debug)]
+pub enum SceneFileError {
+    /// Reading or writing the file failed
+    Io(io::Error),
+    /// The JSON is malformed or doesn't describe a scene
+    Json(serde_json::Error),
+    /// The binary data is malformed or doesn't describe a scene
+    Binary(bincode::Error),
+    /// The scene was written by a newer version of the format
+    UnsupportedVersion(u32),
+    /// The object at `index` has an invalid property
+    InvalidObject {
+        index: usize,
+        error: GameObjectError,
+    },
+    /// A collider of the object at `index` refers to a material that isn't in the scene
+    MissingMaterial { index: usize, material: String },
+    /// Several materials have the same name
+    DuplicateMaterial(String),
+    /// A collider has a shape the format can't describe, like a shape defined outside this crate
+    UnsupportedShape,
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io(error) => write!(f, "couldn't access the scene file: {}", error),
+            SceneFileError::Json(error) => write!(f, "invalid JSON scene: {}", error),
+            SceneFileError::Binary(error) => write!(f, "invalid binary scene: {}", error),
+            SceneFileError::UnsupportedVersion(version) => write!(
+                f,
+                "the scene has format version {}, only up to {} is supported",
+                version, FORMAT_VERSION
+            ),
+            SceneFileError::InvalidObject { index, error } => {
+                write!(f, "object {} is invalid: {}", index, error)
+            }
+            SceneFileError::MissingMaterial { index, material } => write!(
+                f,
+                "object {} uses material \"{}\" which doesn't exist",
+                index, material
+            ),
+            SceneFileError::DuplicateMaterial(name) => {
+                write!(f, "there are several materials called \"{}\"", name)
+            }
+            SceneFileError::UnsupportedShape => {
+                write!(f, "a collider's shape isn't supported by the scene format")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {}
+
+impl From<io::Error> for SceneFileError {
+    fn from(error: io::Error) -> Self {
+        SceneFileError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SceneFileError {
+    fn from(error: serde_json::Error) -> Self {
+        SceneFileError::Json(error)
+    }
+}
+
+impl From<bincode::Error> for SceneFileError {
+    fn from(error: bincode::Error) -> Self {
+        SceneFileError::Binary(error)
+    }
+}
+
+/// Everything needed to rebuild a PhysicsScene, in a form that can be written to and read from files.
+/// Infinite values (masses of static objects, missing velocity limits) are stored as `None` since JSON can't hold them.
+///
+/// The scene has no settings of its own to store: gravity is fixed and the time step is given to each `update`.
+/// Joints aren't part of the scene either, a `MouseJoint` belongs to whoever drags the object and isn't saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDescription {
+    pub version: u32,
+    /// Materials shared by the colliders, which refer to them by name, so every name must be different
+    pub materials: Vec<PhysicsMaterial>,
+    pub objects: Vec<ObjectDescription>,
+}
+
+/// A position and rotation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PoseDescription {
+    pub translation: [f32; 3],
+    /// Unit quaternion as [x, y, z, w]
+    pub rotation: [f32; 4],
+}
+
+/// A GameObject with its body and colliders.
+/// Fields marked `serde(default)` can be left out of hand written files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectDescription {
+    pub body_type: BodyType,
+    pub color: [u8; 3],
+    pub position: PoseDescription,
+    #[serde(default)]
+    pub velocity: [f32; 3],
+    #[serde(default)]
+    pub angular_velocity: [f32; 3],
+    #[serde(default)]
+    pub acceleration: [f32; 3],
+    #[serde(default)]
+    pub angular_acceleration: [f32; 3],
+    #[serde(default = "default_damping")]
+    pub linear_damping: f32,
+    #[serde(default = "default_damping")]
+    pub angular_damping: f32,
+    #[serde(default)]
+    pub max_linear_velocity: Option<f32>,
+    #[serde(default)]
+    pub max_angular_velocity: Option<f32>,
+    #[serde(default)]
+    pub locked_axes: LockedAxes,
+    #[serde(default)]
+    pub gyroscopic: GyroscopicMode,
+    /// Never empty
+    pub colliders: Vec<ColliderDescription>,
+}
+
+/// A collider attached to an object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColliderDescription {
+    pub shape: ShapeDescription,
+    /// Pose relative to the object
+    #[serde(default)]
+    pub position: PoseDescription,
+    /// [kg], `None` for infinite mass
+    pub mass: Option<f32>,
+    /// Name of a material in `SceneDescription::materials`
+    pub material: String,
+    #[serde(default)]
+    pub sensor: bool,
+    #[serde(default)]
+    pub groups: CollisionGroups,
+}
+
+/// The shapes a collider can have
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ShapeDescription {
+    Sphere { radius: f32 },
+    Plane { normal: [f32; 3] },
+    Capsule { half_height: f32, radius: f32 },
+}
+
+impl PhysicsScene {
+    /// Describes all objects in the scene, shared materials are stored once.
+    /// Materials without a name, or with the name of another material, are given a new name.
+    /// Fails if a collider has a shape the format doesn't support.
+    pub fn to_description(&self) -> Result<SceneDescription, SceneFileError> {
+        let mut shared: Vec<Arc<PhysicsMaterial>> = Vec::new();
+        let mut materials: Vec<PhysicsMaterial> = Vec::new();
+        let mut objects = Vec::with_capacity(self.objects.len());
+        for object in &self.objects {
+            let mut colliders = Vec::with_capacity(object.colliders().len());
+            for collider in object.colliders() {
+                let material = match shared
+                    .iter()
+                    .position(|material| Arc::ptr_eq(material, collider.material()))
+                {
+                    Some(index) => materials[index].name.clone(),
+                    None => {
+                        let mut material = collider.material().as_ref().clone();
+                        material.name = unique_name(&material.name, &materials);
+                        shared.push(collider.material().clone());
+                        materials.push(material);
+                        materials[materials.len() - 1].name.clone()
+                    }
+                };
+                colliders.push(ColliderDescription {
+                    shape: ShapeDescription::try_from(collider.shape())?,
+                    position: PoseDescription::from(&collider.position),
+                    mass: finite(collider.mass()),
+                    material,
+                    sensor: collider.sensor,
+                    groups: collider.groups,
+                });
+            }
+            objects.push(ObjectDescription {
+                body_type: object.body_type(),
+                color: object.color(),
+                position: PoseDescription::from(&object.position),
+                velocity: object.velocity.into(),
+                angular_velocity: object.angular_velocity.into(),
+                acceleration: object.acceleration.into(),
+                angular_acceleration: object.angular_acceleration.into(),
+                linear_damping: object.linear_damping,
+                angular_damping: object.angular_damping,
+                max_linear_velocity: finite(object.max_linear_velocity),
+                max_angular_velocity: finite(object.max_angular_velocity),
+                locked_axes: object.locked_axes,
+                gyroscopic: object.gyroscopic,
+                colliders,
+            });
+        }
+
+        Ok(SceneDescription {
+            version: FORMAT_VERSION,
+            materials,
+            objects,
+        })
+    }
+
+    /// Builds a scene from a description, checking every object like `GameObjectBuilder` does
+    pub fn from_description(
+        description: &SceneDescription,
+    ) -> Result<PhysicsScene, SceneFileError> {
+        if description.version > FORMAT_VERSION {
+            return Err(SceneFileError::UnsupportedVersion(description.version));
+        }
+        let mut materials: Vec<Arc<PhysicsMaterial>> =
+            Vec::with_capacity(description.materials.len());
+        for material in &description.materials {
+            if materials.iter().any(|other| other.name == material.name) {
+                return Err(SceneFileError::DuplicateMaterial(material.name.clone()));
+            }
+            materials.push(Arc::new(material.clone()));
+        }
+        let material = |index: usize, name: &str| {
+            materials
+                .iter()
+                .find(|material| material.name == name)
+                .cloned()
+                .ok_or_else(|| SceneFileError::MissingMaterial {
+                    index,
+                    material: name.to_string(),
+                })
+        };
+
+        let mut scene = PhysicsScene::new();
+        for (index, object) in description.objects.iter().enumerate() {
+            let invalid = |error| SceneFileError::InvalidObject { index, error };
+            let (first, others) = object
+                .colliders
+                .split_first()
+                .ok_or(invalid(GameObjectError::InvalidMass(0.)))?;
+
+            let mut builder = GameObjectBuilder::new(first.shape.to_shape().map_err(invalid)?)
+                .body_type(object.body_type)
+                .color(object.color)
+                .position(object.position.to_isometry().map_err(invalid)?)
+                .shape_position(first.position.to_isometry().map_err(invalid)?)
+                .velocity(object.velocity)
+                .angular_velocity(object.angular_velocity)
+                .mass(first.mass.unwrap_or(INFINITY))
+                .material(material(index, &first.material)?)
+                .sensor(first.sensor)
+                .collision_groups(first.groups)
+                .linear_damping(object.linear_damping)
+                .angular_damping(object.angular_damping)
+                .max_linear_velocity(object.max_linear_velocity.unwrap_or(INFINITY))
+                .max_angular_velocity(object.max_angular_velocity.unwrap_or(INFINITY))
+                .locked_axes(object.locked_axes)
+                .gyroscopic(object.gyroscopic);
+            for collider in others {
+                builder = builder.collider(
+                    Collider::new(
+                        collider.shape.to_shape().map_err(invalid)?,
+                        collider.mass.unwrap_or(INFINITY),
+                    )
+                    .with_position(collider.position.to_isometry().map_err(invalid)?)
+                    .with_material(material(index, &collider.material)?)
+                    .with_sensor(collider.sensor)
+                    .with_groups(collider.groups),
+                );
+            }
+            let mut game_object = builder.build().map_err(invalid)?;
+
+            builder::finite("acceleration", object.acceleration.iter()).map_err(invalid)?;
+            builder::finite("angular acceleration", object.angular_acceleration.iter())
+                .map_err(invalid)?;
+            game_object.acceleration = Vector3::from(object.acceleration);
+            game_object.angular_acceleration = Vector3::from(object.angular_acceleration);
+            scene.add(game_object);
+        }
+        Ok(scene)
+    }
+
+    /// The scene as pretty printed JSON
+    pub fn to_json(&self) -> Result<String, SceneFileError> {
+        Ok(serde_json::to_string_pretty(&self.to_description()?)?)
+    }
+
+    /// Builds a scene from JSON written by `to_json` or by hand
+    pub fn from_json(json: &str) -> Result<PhysicsScene, SceneFileError> {
+        PhysicsScene::from_description(&serde_json::from_str(json)?)
+    }
+
+    /// The scene in a compact binary format
+    pub fn to_binary(&self) -> Result<Vec<u8>, SceneFileError> {
+        Ok(bincode::serialize(&self.to_description()?)?)
+    }
+
+    /// Builds a scene from data written by `to_binary`
+    pub fn from_binary(bytes: &[u8]) -> Result<PhysicsScene, SceneFileError> {
+        PhysicsScene::from_description(&bincode::deserialize(bytes)?)
+    }
+
+    /// Writes the scene to a file, as JSON if the path ends with `.json` and in the binary format otherwise
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneFileError> {
+        let path = path.as_ref();
+        if is_json(path) {
+            fs::write(path, self.to_json()?)?;
+        } else {
+            fs::write(path, self.to_binary()?)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a scene written by `save`, the format is chosen from the extension in the same way
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PhysicsScene, SceneFileError> {
+        let path = path.as_ref();
+        if is_json(path) {
+            PhysicsScene::from_json(&fs::read_to_string(path)?)
+        } else {
+            PhysicsScene::from_binary(&fs::read(path)?)
+        }
+    }
+}
+
+/// At the origin without rotation
+impl Default for PoseDescription {
+    fn default() -> Self {
+        PoseDescription::from(&Isometry3::identity())
+    }
+}
+
+impl From<&Isometry3<f32>> for PoseDescription {
+    fn from(position: &Isometry3<f32>) -> Self {
+        PoseDescription {
+            translation: position.translation.vector.into(),
+            rotation: position.rotation.coords.into(),
+        }
+    }
+}
+
+impl PoseDescription {
+    /// The pose, with the rotation normalized
+    pub fn to_isometry(&self) -> Result<Isometry3<f32>, GameObjectError> {
+        let [x, y, z, w] = self.rotation;
+        let rotation = Quaternion::new(w, x, y, z);
+        if !self.translation.iter().all(|value| value.is_finite()) {
+            return Err(GameObjectError::NotFinite { name: "position" });
+        }
+        if !(rotation.norm() > f32::EPSILON && rotation.norm().is_finite()) {
+            return Err(GameObjectError::NotFinite { name: "rotation" });
+        }
+        Ok(Isometry3::from_parts(
+            Translation3::from(Vector3::from(self.translation)),
+            UnitQuaternion::from_quaternion(rotation),
+        ))
+    }
+}
+
+impl TryFrom<&dyn Shape> for ShapeDescription {
+    type Error = SceneFileError;
+
+    fn try_from(shape: &dyn Shape) -> Result<Self, SceneFileError> {
+        if let Some(sphere) = shape.as_sphere() {
+            Ok(ShapeDescription::Sphere {
+                radius: sphere.radius,
+            })
+        } else if let Some(capsule) = shape.as_capsule() {
+            Ok(ShapeDescription::Capsule {
+                half_height: capsule.half_height,
+                radius: capsule.radius,
+            })
+        } else if let Some(plane) = shape.as_plane() {
+            Ok(ShapeDescription::Plane {
+                normal: plane.normal().into_inner().into(),
+            })
+        } else {
+            Err(SceneFileError::UnsupportedShape)
+        }
+    }
+}
+
+impl ShapeDescription {
+    /// The shape, if its dimensions are valid
+    pub fn to_shape(&self) -> Result<Box<dyn Shape>, GameObjectError> {
+        let shape: Box<dyn Shape> = match *self {
+            ShapeDescription::Sphere { radius } => Box::new(Sphere::new(radius)),
+            ShapeDescription::Capsule {
+                half_height,
+                radius,
+            } => Box::new(Capsule::new(half_height, radius)),
+            ShapeDescription::Plane { normal } => {
+                let normal = Vector3::from(normal);
+                if normal.norm().is_nan() || normal.norm() <= f32::EPSILON {
+                    return Err(GameObjectError::NotFinite { name: "normal" });
+                }
+                Box::new(Plane::new(UnitVector3::new_normalize(normal)))
+            }
+        };
+        validate_shape(shape.as_ref())?;
+        Ok(shape)
+    }
+}
+
+fn default_damping() -> f32 {
+    DAMPING
+}
+
+/// `name`, or "material" if it's empty, made different from the names in `materials`
+fn unique_name(name: &str, materials: &[PhysicsMaterial]) -> String {
+    let base = if name.is_empty() { "material" } else { name };
+    let taken = |name: &str| materials.iter().any(|material| material.name == name);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} {}", base, n))
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+/// `None` for infinite values
+fn finite(value: f32) -> Option<f32> {
+    if value.is_finite() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
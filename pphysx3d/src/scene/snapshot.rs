@@ -0,0 +1,249 @@
+use std::{fmt, hash::Hasher};
+
+use nalgebra::Vector3;
+
+use super::{
+    game_object::{BodyType, GameObject},
+    rigid_body::RigidBody,
+    BodyHandle, PhysicsScene,
+};
+use crate::{collision::CollisionManifold, shapes::shape::Shape};
+
+/// The simulation state of a PhysicsScene at one moment: the poses, velocities, accelerations,
+/// accumulated forces and kinematic targets of its bodies and the sensor overlaps and contacts of the last update.
+/// Colliders, shapes and materials aren't part of it, they're expected to stay the same,
+/// `restore` only checks that every object still has the same body type and collider shapes.
+#[derive(Clone)]
+pub struct SceneSnapshot {
+    handles: Vec<BodyHandle>,
+    bodies: Vec<RigidBody>,
+    shapes: Vec<Vec<ShapeKey>>,
+    sensor_intersections: Vec<(BodyHandle, BodyHandle)>,
+    contacts: Vec<(BodyHandle, BodyHandle, CollisionManifold)>,
+}
+
+impl SceneSnapshot {
+    /// Number of objects in the scene the snapshot was taken of
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    /// True if the snapshot was taken of an empty scene
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+}
+
+/// Reasons a snapshot can't be restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreError {
+    /// Objects were added or removed since the snapshot was taken
+    ObjectCountMismatch { snapshot: usize, scene: usize },
+    /// The object at `index` isn't the one the snapshot was taken of, it was removed and another one added
+    HandleMismatch { index: usize },
+    /// The body type of the object was changed since the snapshot was taken
+    BodyTypeMismatch {
+        handle: BodyHandle,
+        snapshot: BodyType,
+        scene: BodyType,
+    },
+    /// Colliders were added to the object or their shapes were changed since the snapshot was taken
+    ColliderMismatch { handle: BodyHandle },
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreError::ObjectCountMismatch { snapshot, scene } => write!(
+                f,
+                "the snapshot has {} objects but the scene has {}",
+                snapshot, scene
+            ),
+            RestoreError::HandleMismatch { index } => write!(
+                f,
+                "object {} isn't the object the snapshot was taken of",
+                index
+            ),
+            RestoreError::BodyTypeMismatch {
+                handle,
+                snapshot,
+                scene,
+            } => write!(
+                f,
+                "object {:?} was {:?} in the snapshot but is {:?} in the scene",
+                handle, snapshot, scene
+            ),
+            RestoreError::ColliderMismatch { handle } => write!(
+                f,
+                "the colliders of object {:?} changed since the snapshot was taken",
+                handle
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl PhysicsScene {
+    /// Saves the state of the simulation, stepping after `restore` gives bit-identical results to stepping now
+    pub fn snapshot(&self) -> SceneSnapshot {
+        SceneSnapshot {
+            handles: self.handles.clone(),
+            bodies: self
+                .objects
+                .iter()
+                .map(|object| object.body().clone())
+                .collect(),
+            shapes: self.objects.iter().map(shape_keys).collect(),
+            sensor_intersections: self.sensor_intersections.clone(),
+            contacts: self.contacts.clone(),
+        }
+    }
+
+    /// Hash of the state saved by `snapshot`, equal for bit-identical states.
+    /// It's the same on every platform, so runs on different machines can be compared frame by frame.
+    /// Bodies never sleep, so there is no sleep state to hash.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.objects.len() as u64);
+        for object in &self.objects {
+            object.body().hash_state(&mut hasher);
+        }
+        hasher.write_u64(self.sensor_intersections.len() as u64);
+        for (handle_1, handle_2) in &self.sensor_intersections {
+            hasher.write_u64(handle_1.0 as u64);
+            hasher.write_u64(handle_2.0 as u64);
+        }
+        hasher.write_u64(self.contacts.len() as u64);
+        for (handle_1, handle_2, manifold) in &self.contacts {
+            hasher.write_u64(handle_1.0 as u64);
+            hasher.write_u64(handle_2.0 as u64);
+            hash_manifold(manifold, &mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Puts the scene back into the state saved by `snapshot`.
+    /// Fails without changing anything if the scene doesn't have the objects the snapshot was taken of,
+    /// or if their body types or collider shapes were changed.
+    pub fn restore(&mut self, snapshot: &SceneSnapshot) -> Result<(), RestoreError> {
+        if snapshot.bodies.len() != self.objects.len() {
+            return Err(RestoreError::ObjectCountMismatch {
+                snapshot: snapshot.bodies.len(),
+                scene: self.objects.len(),
+            });
+        }
+        for (index, object) in self.objects.iter().enumerate() {
+            let handle = self.handles[index];
+            if snapshot.handles[index] != handle {
+                return Err(RestoreError::HandleMismatch { index });
+            }
+            if snapshot.bodies[index].body_type() != object.body_type() {
+                return Err(RestoreError::BodyTypeMismatch {
+                    handle,
+                    snapshot: snapshot.bodies[index].body_type(),
+                    scene: object.body_type(),
+                });
+            }
+            if snapshot.shapes[index] != shape_keys(object) {
+                return Err(RestoreError::ColliderMismatch { handle });
+            }
+        }
+        for (object, body) in self.objects.iter_mut().zip(&snapshot.bodies) {
+            *object.body_mut() = body.clone();
+        }
+        self.sensor_intersections = snapshot.sensor_intersections.clone();
+        self.contacts = snapshot.contacts.clone();
+        self.outdate_bvh();
+        Ok(())
+    }
+}
+
+fn hash_manifold(manifold: &CollisionManifold, hasher: &mut impl Hasher) {
+    hasher.write_u8(manifold.colliding as u8);
+    hasher.write_u64(manifold.contacts.len() as u64);
+    let values = manifold
+        .normal
+        .iter()
+        .chain(std::iter::once(&manifold.depth))
+        .chain(
+            manifold
+                .contacts
+                .iter()
+                .flat_map(|point| point.coords.iter()),
+        );
+    for value in values {
+        hasher.write_u32(value.to_bits());
+    }
+}
+
+/// What a collider's shape is and how big, shapes that can't be told apart are compared by their volume
+#[derive(Clone, PartialEq)]
+enum ShapeKey {
+    Sphere { radius: f32 },
+    Plane { normal: Vector3<f32> },
+    Capsule { half_height: f32, radius: f32 },
+    Other { volume: f32 },
+}
+
+impl From<&dyn Shape> for ShapeKey {
+    fn from(shape: &dyn Shape) -> Self {
+        if let Some(sphere) = shape.as_sphere() {
+            ShapeKey::Sphere {
+                radius: sphere.radius,
+            }
+        } else if let Some(plane) = shape.as_plane() {
+            ShapeKey::Plane {
+                normal: plane.normal().into_inner(),
+            }
+        } else if let Some(capsule) = shape.as_capsule() {
+            ShapeKey::Capsule {
+                half_height: capsule.half_height,
+                radius: capsule.radius,
+            }
+        } else {
+            ShapeKey::Other {
+                volume: shape.volume(),
+            }
+        }
+    }
+}
+
+fn shape_keys(object: &GameObject) -> Vec<ShapeKey> {
+    object
+        .colliders()
+        .iter()
+        .map(|collider| ShapeKey::from(collider.shape()))
+        .collect()
+}
+
+/// FNV-1a, unlike the standard library's hasher its output is specified and doesn't change between Rust versions
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> StateHasher {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // Numbers are hashed as little endian, the default uses the platform's byte order
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+}
@@ -1,23 +1,51 @@
-use kiss3d::nalgebra::{Point3, Translation3, UnitVector3, Vector3};
+use nalgebra::{Isometry3, Point3, Translation3, UnitVector3, Vector3};
 
 use crate::{
     collision::CollisionManifold,
-    scene::{broad_phase, game_object::GameObject, narrow_phase, PhysicsScene},
-    shapes::{bounding_volume::BoundingVolume, sphere::Sphere},
+    scene::{
+        broad_phase,
+        builder::GameObjectBuilder,
+        game_object::{BodyType, GameObject},
+        narrow_phase, BodyHandle, PhysicsScene, QueryTree,
+    },
+    shapes::{bounding_volume::AABB, ray::Ray, sphere::Sphere},
 };
 
+#[cfg(test)]
+mod builder;
+#[cfg(test)]
+mod character_controller;
+#[cfg(test)]
+mod collider;
+#[cfg(test)]
+mod material;
+#[cfg(test)]
+mod mouse_joint;
+#[cfg(test)]
+mod rigid_body;
+#[cfg(test)]
+mod scenarios;
+#[cfg(all(test, feature = "serde"))]
+mod serialization;
+#[cfg(test)]
+mod snapshot;
+#[cfg(test)]
+mod trajectory;
+
 #[test]
 fn update_position_test() {
-    let mut sphere1 = GameObject::Sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2);
+    let mut sphere1 =
+        GameObject::sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2).unwrap();
     sphere1.add_velocity([1., 0., 0.]);
 
-    let mut sphere2 = GameObject::Sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2);
+    let mut sphere2 =
+        GameObject::sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2).unwrap();
     sphere2.add_velocity([0., 0., 1.]);
 
     let objects = vec![sphere1, sphere2];
     let mut scene = PhysicsScene::new();
     for object in objects {
-        scene.add(object)
+        scene.add(object);
     }
 
     scene.update_positions(1.);
@@ -33,11 +61,12 @@ fn update_position_test() {
 
 #[test]
 fn broad_phase_collision() {
-    let sphere1 = GameObject::Sphere_default(1., [100, 200, 0], [0.; 3], 10., 0.1, 0.2);
-    let sphere2 = GameObject::Sphere_default(1., [100, 200, 0], [1.99, 1.99, 0.], 10., 0.1, 0.2);
+    let sphere1 = GameObject::sphere_default(1., [100, 200, 0], [0.; 3], 10., 0.1, 0.2).unwrap();
+    let sphere2 =
+        GameObject::sphere_default(1., [100, 200, 0], [1.99, 1.99, 0.], 10., 0.1, 0.2).unwrap();
 
     let objects = vec![sphere1, sphere2];
-    let collisions = broad_phase(&objects);
+    let collisions = broad_phase(&objects, &QueryTree::new(&objects));
     assert_eq!(collisions.len(), 1);
     assert_eq!(0, collisions[0].0);
     assert_eq!(1, collisions[0].1);
@@ -45,14 +74,16 @@ fn broad_phase_collision() {
 
 #[test]
 fn narrow_phase_collision() {
-    let mut sphere1 = GameObject::Sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2);
+    let mut sphere1 =
+        GameObject::sphere_default(2., [100, 200, 0], [0.; 3], 10., 0.1, 0.2).unwrap();
     sphere1.add_velocity([10., 0., 0.]);
 
-    let mut sphere2 = GameObject::Sphere_default(2., [100, 200, 0], [3., 0., 0.], 10., 0.1, 0.2);
+    let mut sphere2 =
+        GameObject::sphere_default(2., [100, 200, 0], [3., 0., 0.], 10., 0.1, 0.2).unwrap();
     sphere2.add_velocity([0., 0., -10.]);
 
     let objects = vec![sphere1, sphere2];
-    let collisions = broad_phase(&objects);
+    let collisions = broad_phase(&objects, &QueryTree::new(&objects));
 
     let manifolds = narrow_phase(&objects, &collisions);
     let check = CollisionManifold {
@@ -63,3 +94,219 @@ fn narrow_phase_collision() {
     };
     assert_eq!(manifolds[0], check)
 }
+
+#[cfg(test)]
+fn query_scene() -> PhysicsScene {
+    let mut scene = PhysicsScene::new();
+    scene.add(GameObject::sphere_default(1., [0; 3], [5., 0., 0.], 10., 0.1, 0.2).unwrap());
+    scene.add(GameObject::sphere_default(1., [0; 3], [10., 0., 0.], 10., 0.1, 0.2).unwrap());
+    scene.add(
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0., -2., 0.], f32::INFINITY, 0.1, 0.2)
+            .unwrap(),
+    );
+    scene
+}
+
+#[test]
+fn scene_cast_ray() {
+    let scene = query_scene();
+    let ray = Ray::new(
+        Point3::new(0., 0., 0.),
+        UnitVector3::new_normalize(Vector3::new(1., 0., 0.)),
+    );
+
+    let (handle, hit) = scene.cast_ray(&ray, 100., None).unwrap();
+    assert_eq!(handle, BodyHandle(0));
+    assert_eq!(hit.distance, 4.);
+
+    let all = scene.cast_ray_all(&ray, 100., None);
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1].0, BodyHandle(1));
+    assert_eq!(all[1].1.distance, 9.);
+
+    // Too short to reach anything
+    assert!(scene.cast_ray(&ray, 3.9, None).is_none());
+
+    // Skip the closest sphere
+    let skip_first = |handle: BodyHandle, _: &GameObject| handle != BodyHandle(0);
+    let (handle, _) = scene.cast_ray(&ray, 100., Some(&skip_first)).unwrap();
+    assert_eq!(handle, BodyHandle(1));
+
+    // Downwards hits the plane
+    let down = Ray::new(
+        Point3::new(0., 0., 0.),
+        UnitVector3::new_normalize(Vector3::new(0., -1., 0.)),
+    );
+    let (handle, hit) = scene.cast_ray(&down, 100., None).unwrap();
+    assert_eq!(handle, BodyHandle(2));
+    assert_eq!(hit.distance, 2.);
+}
+
+#[test]
+fn scene_shape_queries() {
+    let scene = query_scene();
+
+    assert_eq!(
+        scene.intersections_with_point(&Point3::new(5.5, 0., 0.), None),
+        vec![BodyHandle(0)]
+    );
+    assert_eq!(
+        scene.intersections_with_point(&Point3::new(0., -3., 0.), None),
+        vec![BodyHandle(2)]
+    );
+    assert!(scene
+        .intersections_with_point(&Point3::new(0., 0., 0.), None)
+        .is_empty());
+
+    let region = AABB::new(Point3::new(4., -1., -1.), Point3::new(11., 1., 1.));
+    assert_eq!(
+        scene.intersections_with_aabb(&region, None),
+        vec![BodyHandle(0), BodyHandle(1)]
+    );
+
+    assert_eq!(
+        scene.intersections_with_sphere(&Point3::new(7.5, 0., 0.), 1.6, None),
+        vec![BodyHandle(0), BodyHandle(1)]
+    );
+    assert_eq!(
+        scene.intersections_with_sphere(&Point3::new(0., 0., 0.), 2.5, None),
+        vec![BodyHandle(2)]
+    );
+}
+
+#[test]
+fn scene_remove() {
+    let mut scene = query_scene();
+    let removed = scene.remove(BodyHandle(0)).unwrap();
+    assert_eq!(removed.center_of_mass(), Point3::new(5., 0., 0.));
+    assert!(scene.remove(BodyHandle(0)).is_none());
+    assert!(scene.remove(BodyHandle(3)).is_none());
+
+    // The other objects keep their handles, and the queries find them
+    assert_eq!(scene.objects().len(), 2);
+    assert_eq!(scene.handles(), &[BodyHandle(1), BodyHandle(2)][..]);
+    assert!(scene.get(BodyHandle(0)).is_none());
+    assert_eq!(
+        scene.get(BodyHandle(1)).unwrap().center_of_mass(),
+        Point3::new(10., 0., 0.)
+    );
+    assert_eq!(
+        scene.intersections_with_point(&Point3::new(10., 0., 0.), None),
+        vec![BodyHandle(1)]
+    );
+    assert!(scene
+        .intersections_with_point(&Point3::new(5., 0., 0.), None)
+        .is_empty());
+
+    // Objects moved or added are seen by the next query, without an update.
+    // Added objects get a new handle, never the one of a removed object.
+    scene.get_mut(BodyHandle(1)).unwrap().position = Isometry3::translation(20., 0., 0.);
+    let added = scene.add(
+        GameObjectBuilder::sphere(1.)
+            .translation([5., 0., 0.])
+            .build()
+            .unwrap(),
+    );
+    assert_eq!(added, BodyHandle(3));
+    assert_eq!(
+        scene.intersections_with_point(&Point3::new(20., 0., 0.), None),
+        vec![BodyHandle(1)]
+    );
+    assert_eq!(
+        scene.intersections_with_point(&Point3::new(5., 0., 0.), None),
+        vec![added]
+    );
+}
+
+#[test]
+fn scene_queries_below_plane() {
+    // The plane is solid below y = -2, so shapes entirely below it overlap it in every query
+    let scene = query_scene();
+    let center = Point3::new(0., -10., 0.);
+    assert_eq!(scene.intersections_with_point(&center, None), vec![BodyHandle(2)]);
+    assert_eq!(scene.intersections_with_sphere(&center, 1., None), vec![BodyHandle(2)]);
+    let below = AABB::new(Point3::new(-1., -11., -1.), Point3::new(1., -9., 1.));
+    assert_eq!(scene.intersections_with_aabb(&below, None), vec![BodyHandle(2)]);
+
+    let mut probe = PhysicsScene::new();
+    let plane = probe.add(
+        GameObjectBuilder::plane([0., 1., 0.])
+            .translation([0., -2., 0.])
+            .build()
+            .unwrap(),
+    );
+    let sphere = probe.add(
+        GameObjectBuilder::sphere(1.)
+            .translation([0., -10., 0.])
+            .build()
+            .unwrap(),
+    );
+    assert!(probe.distance(sphere, plane).unwrap().is_overlapping());
+    assert_eq!(
+        probe.intersections_with_sphere(&center, 1., None),
+        vec![plane, sphere]
+    );
+}
+
+#[test]
+fn scene_cast_shape() {
+    let scene = query_scene();
+    let probe = Sphere::new(0.5);
+    let start = Isometry3::translation(0., 0., 0.);
+
+    // Sweeping along x hits the first sphere after 3.5 units
+    let (handle, hit) = scene
+        .cast_shape(&probe, &start, &Vector3::new(1., 0., 0.), 100., None)
+        .unwrap();
+    assert_eq!(handle, BodyHandle(0));
+    assert_eq!(hit.toi, 3.5);
+
+    // Sweeping down hits the floor after 1.5 units
+    let (handle, hit) = scene
+        .cast_shape(&probe, &start, &Vector3::new(0., -1., 0.), 100., None)
+        .unwrap();
+    assert_eq!(handle, BodyHandle(2));
+    assert_eq!(hit.toi, 1.5);
+    assert_eq!(hit.contact_point, Point3::new(0., -2., 0.));
+
+    // Sweeping up hits nothing
+    assert!(scene
+        .cast_shape(&probe, &start, &Vector3::new(0., 1., 0.), 100., None)
+        .is_none());
+}
+
+#[test]
+fn scene_contacts() {
+    let mut scene = PhysicsScene::new();
+    let ball = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .translation([0., 0.45, 0.])
+            .velocity([0., -1., 0.])
+            .build()
+            .unwrap(),
+    );
+    let floor = scene.add(GameObjectBuilder::plane([0., 1., 0.]).build().unwrap());
+    scene.add(
+        GameObjectBuilder::sphere(1.)
+            .sensor(true)
+            .body_type(BodyType::Static)
+            .build()
+            .unwrap(),
+    );
+    assert!(scene.contacts().is_empty());
+
+    scene.update(0.01);
+    assert_eq!(scene.contacts().len(), 1);
+    let (handle_1, handle_2, manifold) = &scene.contacts()[0];
+    assert_eq!((*handle_1, *handle_2), (ball, floor));
+    assert!(manifold.colliding && !manifold.contacts.is_empty());
+    assert!(manifold.normal.y.abs() > 0.99);
+
+    // Restored with the rest of the state
+    let snapshot = scene.snapshot();
+    scene.get_mut(ball).unwrap().position.translation.y = 5.;
+    scene.update(0.01);
+    assert!(scene.contacts().is_empty());
+    scene.restore(&snapshot).unwrap();
+    assert_eq!(scene.contacts().len(), 1);
+}
@@ -0,0 +1,115 @@
+use nalgebra::Vector3;
+
+use crate::scene::{
+    builder::{GameObjectBuilder, GameObjectError},
+    game_object::{BodyType, GameObject},
+};
+
+#[test]
+fn constructor_helpers_validate() {
+    // The helpers build through GameObjectBuilder, so they refuse what it refuses
+    let floor =
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], f32::INFINITY, 0.1, 0.2).unwrap();
+    assert_eq!(floor.body_type(), BodyType::Static);
+    let ball = GameObject::sphere_default(0.5, [0; 3], [0.; 3], 2., 0.1, 0.2).unwrap();
+    assert_eq!(ball.mass(), 2.);
+    for (radius, mass) in [(0., 1.), (0.5, f32::NAN), (0.5, -1.)] {
+        let built = GameObject::sphere_default(radius, [0; 3], [0.; 3], mass, 0.1, 0.2);
+        assert!(built.is_err(), "radius {} mass {}", radius, mass);
+    }
+
+    // Planes with a finite mass are dynamic
+    let board = GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], 3., 0.1, 0.2).unwrap();
+    assert_eq!(board.body_type(), BodyType::Dynamic);
+    assert_eq!(board.mass(), 3.);
+    assert_eq!(
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], 0., 0.1, 0.2).err(),
+        Some(GameObjectError::InvalidMass(0.))
+    );
+
+    // The old names are kept for a while, unchecked like before
+    #[allow(deprecated)]
+    let old = GameObject::Plane_default([0., 1., 0.], [0; 3], [0.; 3], 3., 0.1, 0.2);
+    assert_eq!(old.body_type(), BodyType::Dynamic);
+    #[allow(deprecated)]
+    let old = GameObject::Sphere_default(0.5, [0; 3], [0.; 3], f32::INFINITY, 0.1, 0.2);
+    assert_eq!(old.body_type(), BodyType::Static);
+}
+
+#[test]
+fn builder_defaults_and_density() {
+    let ball = GameObjectBuilder::sphere(1.)
+        .translation([1., 2., 3.])
+        .density(3.)
+        .build()
+        .unwrap();
+    assert!(ball.is_dynamic());
+    assert_eq!(ball.position.translation.vector, Vector3::new(1., 2., 3.));
+    assert!((ball.mass() - 4. * std::f32::consts::PI).abs() < 1e-4);
+    assert_eq!(ball.bounciness(), 0.5);
+    assert_eq!(ball.friction(), 0.5);
+    assert_eq!(ball.color(), [255, 255, 255]);
+
+    // An explicit mass wins over the density
+    let ball = GameObjectBuilder::sphere(1.).mass(2.).build().unwrap();
+    assert_eq!(ball.mass(), 2.);
+
+    let ground = GameObjectBuilder::plane([0., 1., 0.]).build().unwrap();
+    assert_eq!(ground.body_type(), BodyType::Static);
+    assert_eq!(ground.inv_mass(), 0.);
+
+    let capsule = GameObjectBuilder::capsule(1., 0.5).build().unwrap();
+    let volume = std::f32::consts::PI * 0.25 * 2. + 4. / 3. * std::f32::consts::PI * 0.125;
+    assert!((capsule.mass() - volume).abs() < 1e-4);
+}
+
+#[test]
+fn builder_validation() {
+    assert_eq!(
+        GameObjectBuilder::sphere(0.).build().err(),
+        Some(GameObjectError::InvalidDimension {
+            name: "radius",
+            value: 0.
+        })
+    );
+    assert!(matches!(
+        GameObjectBuilder::capsule(-1., 0.5).build(),
+        Err(GameObjectError::InvalidDimension {
+            name: "half height",
+            ..
+        })
+    ));
+    assert_eq!(
+        GameObjectBuilder::sphere(1.)
+            .velocity([f32::NAN, 0., 0.])
+            .build()
+            .err(),
+        Some(GameObjectError::NotFinite { name: "velocity" })
+    );
+    assert_eq!(
+        GameObjectBuilder::sphere(1.).mass(0.).build().err(),
+        Some(GameObjectError::InvalidMass(0.))
+    );
+    assert!(matches!(
+        GameObjectBuilder::sphere(1.).mass(f32::NAN).build(),
+        Err(GameObjectError::InvalidMass(_))
+    ));
+    assert_eq!(
+        GameObjectBuilder::sphere(1.).density(-1.).build().err(),
+        Some(GameObjectError::InvalidDensity(-1.))
+    );
+    assert!(matches!(
+        GameObjectBuilder::sphere(1.).friction(-0.1).build(),
+        Err(GameObjectError::InvalidCoefficient {
+            name: "friction",
+            ..
+        })
+    ));
+    // A plane has infinite volume and can't be dynamic
+    assert!(matches!(
+        GameObjectBuilder::plane([0., 1., 0.])
+            .body_type(BodyType::Dynamic)
+            .build(),
+        Err(GameObjectError::InvalidMass(_))
+    ));
+}
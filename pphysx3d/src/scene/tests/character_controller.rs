@@ -0,0 +1,131 @@
+use nalgebra::{Isometry3, Vector3};
+
+use crate::scene::{
+    builder::GameObjectBuilder,
+    character_controller::CharacterController,
+    game_object::{BodyType, GameObject},
+    BodyHandle, PhysicsScene,
+};
+
+fn character_scene() -> (PhysicsScene, BodyHandle) {
+    let mut scene = PhysicsScene::new();
+    scene.add(
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], f32::INFINITY, 0., 0.).unwrap(),
+    );
+    let mut character = GameObjectBuilder::capsule(0.5, 0.3)
+        .color([0; 3])
+        .translation([0., 0.81, 0.])
+        .mass(80.)
+        .bounciness(0.)
+        .friction(0.)
+        .build()
+        .unwrap();
+    character
+        .set_body_type(BodyType::KinematicPositionBased)
+        .unwrap();
+    let handle = scene.add(character);
+    (scene, handle)
+}
+
+#[test]
+fn character_walks_and_stays_grounded() {
+    let (mut scene, character) = character_scene();
+    let controller = CharacterController::new();
+
+    // Walking forward while falling stays on the floor
+    let movement = controller
+        .move_character(&mut scene, character, &Vector3::new(1., -0.5, 0.))
+        .unwrap();
+    assert!(movement.grounded);
+    assert!((movement.translation.x - 1.).abs() < 1.0e-3);
+    assert!(movement.translation.y.abs() < 1.0e-3);
+
+    // Jumping isn't grounded
+    let movement = controller
+        .move_character(&mut scene, character, &Vector3::new(0., 1., 0.))
+        .unwrap();
+    assert!(!movement.grounded);
+
+    // Dynamic objects aren't teleported
+    let dynamic = scene.add(GameObjectBuilder::capsule(0.5, 0.3).build().unwrap());
+    assert!(controller
+        .move_character(&mut scene, dynamic, &Vector3::new(1., 0., 0.))
+        .is_none());
+    assert_eq!(scene.get(dynamic).unwrap().position, Isometry3::identity());
+}
+
+#[test]
+fn character_slides_along_walls_and_slopes() {
+    let (mut scene, character) = character_scene();
+    // A wall facing -x at x = 1
+    scene.add(
+        GameObject::plane_default([-1., 0., 0.], [0; 3], [1., 0., 0.], f32::INFINITY, 0., 0.)
+            .unwrap(),
+    );
+    let controller = CharacterController::new();
+    let movement = controller
+        .move_character(&mut scene, character, &Vector3::new(2., 0., 2.))
+        .unwrap();
+    // Stops at the wall but keeps going along it
+    assert!((movement.translation.x - 0.69).abs() < 1.0e-3);
+    assert!((movement.translation.z - 2.).abs() < 1.0e-3);
+
+    // A 60° slope is too steep to walk up
+    let (mut scene, character) = character_scene();
+    let angle = std::f32::consts::FRAC_PI_3;
+    scene.add(
+        GameObject::plane_default(
+            [-angle.sin(), angle.cos(), 0.],
+            [0; 3],
+            [1., 0., 0.],
+            f32::INFINITY,
+            0.,
+            0.,
+        )
+        .unwrap(),
+    );
+    let movement = controller
+        .move_character(&mut scene, character, &Vector3::new(2., 0., 0.))
+        .unwrap();
+    assert!(movement.translation.x < 1.);
+    assert!(movement.translation.y < 1.0e-3);
+}
+
+#[test]
+fn character_climbs_steps() {
+    // A curb 0.2 high lying across the way at x = 1
+    let curb = || {
+        GameObjectBuilder::capsule(5., 0.1)
+            .body_type(BodyType::Static)
+            .color([0; 3])
+            .position(Isometry3::new(
+                Vector3::new(1., 0.1, 0.),
+                Vector3::x() * std::f32::consts::FRAC_PI_2,
+            ))
+            .bounciness(0.)
+            .friction(0.)
+            .build()
+            .unwrap()
+    };
+    let walk = Vector3::new(2., -0.1, 0.);
+
+    let (mut scene, character) = character_scene();
+    scene.add(curb());
+    let movement = CharacterController::new()
+        .move_character(&mut scene, character, &walk)
+        .unwrap();
+    assert!((movement.translation.x - 2.).abs() < 1.0e-3);
+    assert!(movement.grounded);
+
+    // Without climbing it gets stuck in front of the curb
+    let (mut scene, character) = character_scene();
+    scene.add(curb());
+    let controller = CharacterController {
+        max_step_height: 0.,
+        ..CharacterController::new()
+    };
+    let movement = controller
+        .move_character(&mut scene, character, &walk)
+        .unwrap();
+    assert!(movement.translation.x < 1.);
+}
@@ -0,0 +1,97 @@
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+
+use crate::{
+    scene::{
+        broad_phase,
+        builder::GameObjectBuilder,
+        collider::{Collider, CollisionGroups},
+        PhysicsScene, QueryTree,
+    },
+    shapes::{ray::Ray, sphere::Sphere},
+};
+
+#[test]
+fn compound_body() {
+    // A dumbbell: two spheres of 1 kg, one at the body's origin and one 2 m along x
+    let mut dumbbell = GameObjectBuilder::sphere(0.5)
+        .mass(1.)
+        .collider(
+            Collider::new(Box::new(Sphere::new(0.5)), 1.)
+                .with_position(Isometry3::translation(2., 0., 0.)),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(dumbbell.colliders().len(), 2);
+    assert_eq!(dumbbell.mass(), 2.);
+    // Turning around y or z swings the second sphere around, turning around x doesn't
+    let inv_tensor = dumbbell.inv_tensor();
+    assert!(inv_tensor[(0, 0)] > 2. * inv_tensor[(1, 1)]);
+    assert!((inv_tensor[(1, 1)] - inv_tensor[(2, 2)]).abs() < 1e-6);
+
+    // Sensors don't add any mass
+    dumbbell.add_collider(Collider::new(Box::new(Sphere::new(1.)), 5.).with_sensor(true));
+    assert_eq!(dumbbell.mass(), 2.);
+
+    // The object's own colliders overlap but are never paired, and queries report it once
+    let mut scene = PhysicsScene::new();
+    let handle = scene.add(dumbbell);
+    assert!(broad_phase(scene.objects(), &QueryTree::new(scene.objects())).is_empty());
+    let ray = Ray::new(
+        Point3::new(-5., 0., 0.),
+        UnitVector3::new_normalize(Vector3::new(1., 0., 0.)),
+    );
+    let hits = scene.cast_ray_all(&ray, 100., None);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].0, handle);
+    assert!((hits[0].1.distance - 4.5).abs() < 1e-4);
+    let far_sphere = scene.intersections_with_point(&Point3::new(2.2, 0., 0.), None);
+    assert_eq!(far_sphere, vec![handle]);
+}
+
+#[test]
+fn collision_groups_and_sensors() {
+    let group = |memberships, filter| CollisionGroups::new(memberships, filter);
+    assert!(CollisionGroups::ALL.interacts_with(&group(0b01, 0b01)));
+    assert!(!group(0b01, 0b10).interacts_with(&group(0b01, 0b10)));
+    assert!(group(0b01, 0b10).interacts_with(&group(0b10, 0b01)));
+
+    // Overlapping spheres in groups that ignore each other aren't paired
+    let objects = vec![
+        GameObjectBuilder::sphere(1.)
+            .collision_groups(group(0b01, 0b01))
+            .build()
+            .unwrap(),
+        GameObjectBuilder::sphere(1.)
+            .translation([1., 0., 0.])
+            .collision_groups(group(0b10, 0b10))
+            .build()
+            .unwrap(),
+    ];
+    assert!(broad_phase(&objects, &QueryTree::new(&objects)).is_empty());
+
+    // A falling ball passes through a sensor, which reports the overlap
+    let mut scene = PhysicsScene::new();
+    let ball = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .velocity([0., -1., 0.])
+            .build()
+            .unwrap(),
+    );
+    let trigger = scene.add(
+        GameObjectBuilder::plane([0., 1., 0.])
+            .translation([0., 0.2, 0.])
+            .sensor(true)
+            .build()
+            .unwrap(),
+    );
+    scene.update(0.01);
+    assert_eq!(scene.sensor_intersections(), &[(ball, trigger)]);
+    // Not bounced back up
+    assert!(scene.get(ball).unwrap().velocity.y < -0.9);
+    // Queries that look for solid objects don't see sensors
+    let down = Ray::new(
+        Point3::new(0., 5., 0.),
+        UnitVector3::new_normalize(Vector3::new(0., -1., 0.)),
+    );
+    assert_eq!(scene.cast_ray(&down, 10., None).unwrap().0, ball);
+}
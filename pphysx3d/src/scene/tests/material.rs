@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use crate::scene::{
+    builder::GameObjectBuilder,
+    game_object::GameObject,
+    material::{CombineRule, PhysicsMaterial},
+    PhysicsScene,
+};
+
+#[test]
+fn material_combine_rules() {
+    let ice = PhysicsMaterial::new(0.1, 0.2).with_name("ice");
+    let rubber = PhysicsMaterial::new(0.9, 0.8).with_name("rubber");
+    assert_eq!(ice.name, "ice");
+    // The geometric mean of the frictions and the smallest bounciness are used by default
+    let combined = ice.combine(&rubber);
+    assert!((combined.dynamic_friction - 0.3).abs() < 1e-6);
+    assert_eq!(combined.bounciness, 0.2);
+    let averaged = rubber.with_combine_rules(CombineRule::Average, CombineRule::Min);
+    assert!((ice.combine(&averaged).dynamic_friction - 0.5).abs() < 1e-6);
+
+    // The rule with the highest precedence wins, no matter the order
+    let sticky = PhysicsMaterial::new(0.9, 0.8)
+        .with_combine_rules(CombineRule::Max, CombineRule::Multiply)
+        .with_frictions(1.2, 0.9);
+    for combined in [ice.combine(&sticky), sticky.combine(&ice)] {
+        assert_eq!(combined.static_friction, 1.2);
+        assert_eq!(combined.dynamic_friction, 0.9);
+        assert!((combined.bounciness - 0.16).abs() < 1e-6);
+    }
+    assert_eq!(
+        CombineRule::Average.resolve(CombineRule::Min),
+        CombineRule::Min
+    );
+    assert_eq!(
+        CombineRule::GeometricMean.resolve(CombineRule::Average),
+        CombineRule::Average
+    );
+    assert_eq!(
+        CombineRule::Max.resolve(CombineRule::Multiply),
+        CombineRule::Max
+    );
+}
+
+/// A ball hitting a floor while sliding along x, after one step
+fn slide_on(material: PhysicsMaterial, angular_velocity: [f32; 3]) -> GameObject {
+    let material = Arc::new(material);
+    let mut scene = PhysicsScene::new();
+    let ball = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .translation([0., 0.45, 0.])
+            .velocity([1., -1., 0.])
+            .angular_velocity(angular_velocity)
+            .mass(1.)
+            .material(material.clone())
+            .build()
+            .unwrap(),
+    );
+    scene.add(
+        GameObjectBuilder::plane([0., 1., 0.])
+            .material(material)
+            .build()
+            .unwrap(),
+    );
+    scene.update(0.01);
+    scene.objects.swap_remove(ball.0)
+}
+
+#[test]
+fn static_and_dynamic_friction() {
+    let frictionless = slide_on(PhysicsMaterial::new(0., 0.), [0.; 3]);
+    assert!(frictionless.velocity.x > 0.99);
+
+    // Dynamic friction slows the sliding by at most its share of the normal impulse
+    let sliding = slide_on(
+        PhysicsMaterial::new(0., 0.).with_frictions(0., 0.1),
+        [0.; 3],
+    );
+    assert!(sliding.velocity.x < frictionless.velocity.x - 0.05);
+    assert!(sliding.velocity.x > 0.5);
+
+    // Enough static friction stops the sliding, no matter the dynamic friction
+    let sticking = slide_on(PhysicsMaterial::new(0., 0.).with_frictions(2., 0.), [0.; 3]);
+    assert!(sticking.velocity.x < sliding.velocity.x);
+}
+
+#[test]
+fn rolling_friction() {
+    let spin = [0., 10., 0.];
+    let free = slide_on(PhysicsMaterial::new(0., 0.), spin);
+    let rolling = slide_on(
+        PhysicsMaterial::new(0., 0.).with_rolling_friction(0.1),
+        spin,
+    );
+    assert!(free.angular_velocity.y > 9.9);
+    assert!(rolling.angular_velocity.y < free.angular_velocity.y - 0.1);
+    assert!(rolling.angular_velocity.y >= 0.);
+}
@@ -0,0 +1,67 @@
+use nalgebra::{Point3, Vector3};
+
+use crate::scene::{
+    builder::GameObjectBuilder, game_object::BodyType, mouse_joint::MouseJoint, BodyHandle,
+    PhysicsScene,
+};
+
+#[test]
+fn mouse_joint() {
+    let mut scene = PhysicsScene::new();
+    let ball = scene.add(GameObjectBuilder::sphere(0.5).mass(5.).build().unwrap());
+    let post = scene.add(
+        GameObjectBuilder::capsule(1., 0.2)
+            .translation([3., 0., 0.])
+            .body_type(BodyType::Static)
+            .build()
+            .unwrap(),
+    );
+    assert!(MouseJoint::new(&scene, BodyHandle(2), Point3::origin()).is_none());
+
+    // Grabbed at the side and pulled up and away, against gravity
+    let mut joint = MouseJoint::new(&scene, ball, Point3::new(0.5, 0., 0.)).unwrap();
+    assert_eq!(joint.local_anchor, Point3::new(0.5, 0., 0.));
+    joint.target = Point3::new(0.5, 2., -1.);
+    for _ in 0..150 {
+        joint.apply(&mut scene, 0.01);
+        scene.update(0.01);
+    }
+    // Held close to the target by the spring
+    let anchor = joint.anchor(&scene).unwrap();
+    assert!((anchor - joint.target).norm() < 0.1, "{}", anchor);
+    // Free to swing around the grabbed point
+    let center = scene.get(ball).unwrap().center_of_mass();
+    assert!((center - anchor).norm() - 0.5 < 1e-3 && center.y > 1.);
+
+    // Static objects aren't moved
+    let mut joint = MouseJoint::new(&scene, post, Point3::new(3., 1., 0.)).unwrap();
+    joint.target = Point3::new(5., 1., 0.);
+    joint.apply(&mut scene, 0.01);
+    scene.update(0.01);
+    assert_eq!(scene.get(post).unwrap().velocity, Vector3::zeros());
+    assert_eq!(joint.anchor(&scene).unwrap(), Point3::new(3., 1., 0.));
+}
+
+#[test]
+fn mouse_joint_mass() {
+    // Light and heavy objects grabbed at their centre are pulled the same way
+    let mut scene = PhysicsScene::new();
+    let light = scene.add(GameObjectBuilder::sphere(0.5).mass(1.).build().unwrap());
+    let heavy = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .mass(100.)
+            .translation([5., 0., 0.])
+            .build()
+            .unwrap(),
+    );
+    for (body, x) in [(light, 0.), (heavy, 5.)] {
+        let mut joint = MouseJoint::new(&scene, body, Point3::new(x, 0., 0.)).unwrap();
+        joint.target = Point3::new(x, 0., 1.);
+        joint.apply(&mut scene, 0.01);
+    }
+    let light_velocity = scene.get(light).unwrap().velocity;
+    let heavy_velocity = scene.get(heavy).unwrap().velocity;
+    assert!((light_velocity - heavy_velocity).norm() < 1e-5);
+    // 100 1/s^2 per metre for 0.01 s
+    assert!((light_velocity - Vector3::new(0., 0., 1.)).norm() < 1e-5);
+}
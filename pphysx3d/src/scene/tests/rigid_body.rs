@@ -0,0 +1,302 @@
+use nalgebra::{Isometry3, Point3, Translation3, Vector3};
+
+use crate::{
+    scene::{
+        builder::{GameObjectBuilder, GameObjectError},
+        collider::Collider,
+        game_object::{BodyType, GameObject},
+        rigid_body::{GyroscopicMode, LockedAxes},
+        PhysicsScene,
+    },
+    shapes::sphere::Sphere,
+};
+
+#[test]
+fn dynamic_body_type_needs_mass() {
+    // An object with infinite mass can't be made dynamic, it keeps its body type
+    let mut floor =
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], f32::INFINITY, 0.1, 0.2).unwrap();
+    assert_eq!(
+        floor.set_body_type(BodyType::Dynamic),
+        Err(GameObjectError::InvalidMass(f32::INFINITY))
+    );
+    assert_eq!(floor.body_type(), BodyType::Static);
+    assert!(floor.inv_mass().is_finite());
+
+    floor
+        .set_body_type(BodyType::KinematicVelocityBased)
+        .unwrap();
+    assert!(floor.is_kinematic());
+}
+
+#[test]
+fn kinematic_velocity_based() {
+    let mut scene = PhysicsScene::new();
+    let mut platform = GameObject::sphere_default(1., [0; 3], [0.; 3], 10., 0.1, 0.2).unwrap();
+    platform
+        .set_body_type(BodyType::KinematicVelocityBased)
+        .unwrap();
+    platform.add_velocity([0., 1., 0.]);
+    platform.add_force(Vector3::new(100., 0., 0.));
+    let handle = scene.add(platform);
+
+    scene.update(0.5);
+    // Neither gravity nor forces affect it
+    let platform = scene.get(handle).unwrap();
+    assert_eq!(platform.velocity, Vector3::new(0., 1., 0.));
+    assert_eq!(
+        platform.position.translation,
+        Translation3::new(0., 0.5, 0.)
+    );
+}
+
+#[test]
+fn kinematic_position_based() {
+    let mut scene = PhysicsScene::new();
+    let mut platform = GameObject::sphere_default(1., [0; 3], [0.; 3], 10., 0.1, 0.2).unwrap();
+    platform
+        .set_body_type(BodyType::KinematicPositionBased)
+        .unwrap();
+    let handle = scene.add(platform);
+
+    let target = Isometry3::translation(1., 2., 0.);
+    scene
+        .get_mut(handle)
+        .unwrap()
+        .set_next_kinematic_position(target);
+    scene.update(0.5);
+    let platform = scene.get(handle).unwrap();
+    assert_eq!(platform.position, target);
+    assert_eq!(platform.velocity, Vector3::new(2., 4., 0.));
+
+    // Without a new target it stops
+    scene.update(0.5);
+    let platform = scene.get(handle).unwrap();
+    assert_eq!(platform.position, target);
+    assert_eq!(platform.velocity, Vector3::zeros());
+}
+
+#[test]
+fn kinematic_pushes_dynamic() {
+    let mut scene = PhysicsScene::new();
+    let ball =
+        scene.add(GameObject::sphere_default(0.5, [0; 3], [0., 0.45, 0.], 1., 0., 0.).unwrap());
+    let mut floor =
+        GameObject::plane_default([0., 1., 0.], [0; 3], [0.; 3], f32::INFINITY, 0., 0.).unwrap();
+    floor
+        .set_body_type(BodyType::KinematicPositionBased)
+        .unwrap();
+    let floor = scene.add(floor);
+
+    // Lift the floor, which carries the ball upwards with it
+    scene
+        .get_mut(floor)
+        .unwrap()
+        .set_next_kinematic_position(Isometry3::translation(0., 0.01, 0.));
+    scene.update(0.01);
+    assert!(scene.get(ball).unwrap().velocity.y > 0.5);
+    // The floor isn't pushed back
+    assert_eq!(
+        scene.get(floor).unwrap().position,
+        Isometry3::translation(0., 0.01, 0.)
+    );
+}
+
+#[test]
+fn center_of_mass_offset() {
+    // A heavy sphere 1 m to the side of the body's origin, next to a light one at the origin
+    let mut body = GameObjectBuilder::sphere(0.5)
+        .mass(1.)
+        .collider(
+            Collider::new(Box::new(Sphere::new(0.5)), 3.)
+                .with_position(Isometry3::translation(1., 0., 0.)),
+        )
+        .build()
+        .unwrap();
+    assert!((body.center_of_mass() - Point3::new(0.75, 0., 0.)).norm() < 1e-6);
+
+    // Spinning turns the body around its center of mass, which stays put
+    body.angular_velocity = Vector3::new(0., 0., 1.);
+    for _ in 0..10 {
+        body.integrate(0.1);
+    }
+    assert!((body.center_of_mass() - Point3::new(0.75, 0., 0.)).norm() < 1e-4);
+    assert!(body.position.translation.vector.norm() > 0.1);
+
+    // A push through the center of mass doesn't make it spin
+    let mut body = GameObjectBuilder::sphere(0.5)
+        .mass(1.)
+        .collider(
+            Collider::new(Box::new(Sphere::new(0.5)), 3.)
+                .with_position(Isometry3::translation(1., 0., 0.)),
+        )
+        .build()
+        .unwrap();
+    body.add_rotational_impulse(&Point3::new(0.75, 1., 0.), &Vector3::new(0., 1., 0.));
+    assert!(body.angular_velocity.norm() < 1e-6);
+    body.add_rotational_impulse(&Point3::new(0., 1., 0.), &Vector3::new(0., 1., 0.));
+    assert!(body.angular_velocity.z < 0.);
+}
+
+/// Spins a free undamped capsule around a tilted axis, returning its angular momentum before and after and its final angular velocity
+fn spin_capsule(mode: GyroscopicMode, steps: i32) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+    let mut top = GameObjectBuilder::capsule(1., 0.3)
+        .mass(1.)
+        .angular_velocity([1., 5., 0.])
+        .angular_damping(0.)
+        .build()
+        .unwrap();
+    top.gyroscopic = mode;
+    let before = top.angular_momentum();
+    for _ in 0..steps {
+        top.integrate(0.01);
+    }
+    let after = top.angular_momentum();
+    (before, after, top.angular_velocity)
+}
+
+#[test]
+fn gyroscopic_precession() {
+    for mode in [GyroscopicMode::Implicit, GyroscopicMode::Explicit] {
+        let (before, after, angular_velocity) = spin_capsule(mode, 200);
+        // The angular momentum of a free body doesn't change
+        assert!(
+            (after - before).norm() < 0.001 * before.norm(),
+            "{:?}: {} != {}",
+            mode,
+            after,
+            before
+        );
+        // But its angular velocity does, as the capsule wobbles
+        let initial = Vector3::new(1., 5., 0.).normalize();
+        assert!(angular_velocity.normalize().dot(&initial) < 0.999);
+    }
+
+    // Treating the inertia as if it were the same around all axes doesn't keep the momentum
+    let (before, after, _) = spin_capsule(GyroscopicMode::Disabled, 200);
+    assert!((after - before).norm() > 0.05 * before.norm());
+}
+
+/// The velocity of a ball flying for 1 second without gravity, integrated with `steps` steps
+fn coast(steps: i32) -> Vector3<f32> {
+    let mut ball = GameObjectBuilder::sphere(0.5)
+        .velocity([10., 0., 0.])
+        .angular_velocity([0., 10., 0.])
+        .linear_damping(0.5)
+        .angular_damping(2.)
+        .build()
+        .unwrap();
+    for _ in 0..steps {
+        ball.integrate(1. / steps as f32);
+    }
+    Vector3::new(ball.velocity.x, ball.angular_velocity.y, 0.)
+}
+
+#[test]
+fn damping() {
+    let coarse = coast(30);
+    let fine = coast(300);
+    // The same amount of damping per second whatever the time step, 10 / e^(c * 1s)
+    assert!((coarse - fine).norm() < 1e-3);
+    assert!((fine.x - 10. * (-0.5f32).exp()).abs() < 0.02);
+    assert!((fine.y - 10. * (-2f32).exp()).abs() < 0.02);
+
+    let undamped = GameObjectBuilder::sphere(0.5)
+        .velocity([1., 0., 0.])
+        .linear_damping(0.)
+        .build()
+        .unwrap();
+    assert_eq!(undamped.linear_damping, 0.);
+    assert_eq!(
+        GameObjectBuilder::sphere(0.5)
+            .angular_damping(-1.)
+            .build()
+            .err(),
+        Some(GameObjectError::InvalidCoefficient {
+            name: "angular damping",
+            value: -1.
+        })
+    );
+}
+
+#[test]
+fn max_velocity() {
+    let mut ball = GameObjectBuilder::sphere(0.5)
+        .max_linear_velocity(2.)
+        .max_angular_velocity(1.)
+        .build()
+        .unwrap();
+    for _ in 0..100 {
+        ball.add_force(Vector3::new(0., -100., 0.));
+        ball.add_angular_force(Vector3::new(100., 0., 0.));
+        ball.integrate(0.01);
+    }
+    assert!((ball.velocity.norm() - 2.).abs() < 1e-4);
+    assert!((ball.angular_velocity.norm() - 1.).abs() < 1e-4);
+    assert!(ball.velocity.y < 0. && ball.angular_velocity.x > 0.);
+
+    assert!(GameObjectBuilder::sphere(0.5)
+        .max_linear_velocity(f32::NAN)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn axis_locks() {
+    let mut body = GameObjectBuilder::sphere(0.5)
+        .velocity([1., 1., 1.])
+        .angular_velocity([1., 1., 1.])
+        .locked_axes(LockedAxes::PLANE_XY)
+        .build()
+        .unwrap();
+    body.add_force(Vector3::new(0., 0., 10.));
+    body.add_angular_force(Vector3::new(10., 0., 0.));
+    body.integrate(0.01);
+    assert_eq!(body.position.translation.z, 0.);
+    assert_eq!(body.velocity.z, 0.);
+    assert!(body.velocity.x > 0.9 && body.velocity.y > 0.9);
+    assert_eq!((body.angular_velocity.x, body.angular_velocity.y), (0., 0.));
+    assert!(body.angular_velocity.z > 0.9);
+
+    // A ball hitting a ball that can't move along the normal bounces back as if it hit a wall
+    let mut scene = PhysicsScene::new();
+    let ball = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .translation([-0.95, 0., 0.])
+            .velocity([1., 0., 0.])
+            .bounciness(1.)
+            .build()
+            .unwrap(),
+    );
+    let wall = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .locked_axes(LockedAxes::TRANSLATION)
+            .bounciness(1.)
+            .build()
+            .unwrap(),
+    );
+    scene.update(0.01);
+    assert!(scene.objects[ball.0].velocity.x < -0.9);
+    assert_eq!(scene.objects[wall.0].velocity, Vector3::zeros());
+
+    // A tilted capsule landing on one end starts to turn, unless its rotation is locked
+    let land = |locked_axes: LockedAxes| {
+        let mut scene = PhysicsScene::new();
+        let capsule = scene.add(
+            GameObjectBuilder::capsule(0.5, 0.2)
+                .translation([0., 0.63, 0.])
+                .rotation([0., 0., 0.5])
+                .velocity([0., -1., 0.])
+                .locked_axes(locked_axes)
+                .build()
+                .unwrap(),
+        );
+        scene.add(GameObjectBuilder::plane([0., 1., 0.]).build().unwrap());
+        scene.update(0.01);
+        scene.objects.swap_remove(capsule.0)
+    };
+    assert!(land(LockedAxes::NONE).angular_velocity.z.abs() > 0.1);
+    let locked = land(LockedAxes::ROTATION);
+    assert_eq!(locked.angular_velocity, Vector3::zeros());
+    assert!(locked.velocity.y > -0.5);
+}
@@ -0,0 +1,12 @@
+use crate::scene::scenarios::{scenario, SCENARIOS};
+
+#[test]
+fn scenarios() {
+    for name in SCENARIOS {
+        let mut scene = scenario(name).unwrap();
+        assert!(!scene.objects().is_empty());
+        scene.update(0.01);
+    }
+    assert_eq!(scenario("spheres_plane").unwrap().objects().len(), 3);
+    assert!(scenario("no such scene").is_none());
+}
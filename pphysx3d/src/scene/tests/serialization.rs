@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use nalgebra::{Isometry3, Point3};
+
+use crate::{
+    scene::{
+        builder::{GameObjectBuilder, GameObjectError},
+        collider::{Collider, CollisionGroups},
+        game_object::BodyType,
+        material::{CombineRule, PhysicsMaterial},
+        rigid_body::{GyroscopicMode, LockedAxes},
+        serialization::{SceneFileError, ShapeDescription},
+        PhysicsScene,
+    },
+    shapes::{
+        bounding_volume::{BoundingSphere, AABB},
+        mass_properties::MassProperties,
+        ray::Ray,
+        raycast::{RayCast, RayCastResult},
+        shape::Shape,
+        sphere::Sphere,
+    },
+};
+
+/// A scene using every property the scene format stores
+fn described_scene() -> PhysicsScene {
+    let rubber = Arc::new(
+        PhysicsMaterial::new(0.8, 0.9)
+            .with_name("rubber")
+            .with_rolling_friction(0.01)
+            .with_combine_rules(CombineRule::Max, CombineRule::Multiply),
+    );
+    let mut scene = PhysicsScene::new();
+    scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .translation([0., 2., 0.])
+            .rotation([0.1, 0.2, 0.3])
+            .velocity([1., 0., 0.])
+            .angular_velocity([0., 1., 0.])
+            .color([255, 0, 0])
+            .material(rubber.clone())
+            .max_linear_velocity(20.)
+            .build()
+            .unwrap(),
+    );
+    scene.add(
+        GameObjectBuilder::capsule(0.5, 0.2)
+            .translation([2., 1., 0.])
+            .mass(3.)
+            .material(rubber)
+            .collision_groups(CollisionGroups::new(0b01, 0b10))
+            .collider(
+                Collider::new(Box::new(Sphere::new(0.3)), 1.)
+                    .with_position(Isometry3::translation(0., 0.7, 0.))
+                    .with_sensor(true),
+            )
+            .linear_damping(0.3)
+            .locked_axes(LockedAxes::PLANE_XY)
+            .build()
+            .unwrap(),
+    );
+    scene.add(
+        GameObjectBuilder::sphere(0.3)
+            .body_type(BodyType::KinematicVelocityBased)
+            .velocity([0., 0., 1.])
+            .build()
+            .unwrap(),
+    );
+    let mut floor = GameObjectBuilder::plane([0., 1., 0.])
+        .translation([0., -1., 0.])
+        .build()
+        .unwrap();
+    floor.gyroscopic = GyroscopicMode::Disabled;
+    scene.add(floor);
+    scene
+}
+
+#[test]
+fn scene_round_trip() {
+    let mut scene = described_scene();
+    let description = scene.to_description().unwrap();
+    // Shared materials are stored once, the sphere and the capsule use the same one.
+    // Materials without a name are given one
+    let names: Vec<&str> = description
+        .materials
+        .iter()
+        .map(|material| material.name.as_str())
+        .collect();
+    assert_eq!(names, ["rubber", "material", "material 2", "material 3"]);
+    assert_eq!(description.objects[1].colliders[0].material, "rubber");
+
+    let mut from_json = PhysicsScene::from_json(&scene.to_json().unwrap()).unwrap();
+    let from_binary = PhysicsScene::from_binary(&scene.to_binary().unwrap()).unwrap();
+    assert_eq!(from_json.to_description().unwrap(), description);
+    assert_eq!(from_binary.to_description().unwrap(), description);
+    assert!(Arc::ptr_eq(
+        from_json.objects()[0].material(),
+        from_json.objects()[1].material()
+    ));
+    assert_eq!(
+        from_json.objects()[1].mass_properties(),
+        scene.objects()[1].mass_properties()
+    );
+
+    // The loaded scene behaves exactly like the original
+    for _ in 0..50 {
+        scene.update(0.01);
+        from_json.update(0.01);
+    }
+    assert_eq!(
+        from_json.to_description().unwrap(),
+        scene.to_description().unwrap()
+    );
+
+    let path = std::env::temp_dir().join("pphysx3d_scene_round_trip.json");
+    scene.save(&path).unwrap();
+    assert_eq!(
+        PhysicsScene::load(&path).unwrap().to_description().unwrap(),
+        scene.to_description().unwrap()
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn scene_file_errors() {
+    assert!(matches!(
+        PhysicsScene::from_json("{ not a scene"),
+        Err(SceneFileError::Json(_))
+    ));
+    assert!(matches!(
+        PhysicsScene::from_binary(&[1, 2, 3]),
+        Err(SceneFileError::Binary(_))
+    ));
+    assert!(matches!(
+        PhysicsScene::load("/nonexistent/scene.json"),
+        Err(SceneFileError::Io(_))
+    ));
+
+    let mut description = described_scene().to_description().unwrap();
+    description.version += 1;
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::UnsupportedVersion(_))
+    ));
+
+    let mut description = described_scene().to_description().unwrap();
+    description.objects[1].colliders[0].shape = ShapeDescription::Sphere { radius: -1. };
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::InvalidObject {
+            index: 1,
+            error: GameObjectError::InvalidDimension { .. }
+        })
+    ));
+
+    let mut description = described_scene().to_description().unwrap();
+    description.objects[2].colliders[0].material = "steel".to_string();
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::MissingMaterial { index: 2, material }) if material == "steel"
+    ));
+
+    let mut description = described_scene().to_description().unwrap();
+    description.materials[1].name = "rubber".to_string();
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::DuplicateMaterial(name)) if name == "rubber"
+    ));
+
+    // Descriptions are checked like GameObjectBuilder checks its properties
+    let mut description = described_scene().to_description().unwrap();
+    description.objects[0].linear_damping = -1.;
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::InvalidObject {
+            index: 0,
+            error: GameObjectError::InvalidCoefficient { .. }
+        })
+    ));
+    let mut description = described_scene().to_description().unwrap();
+    description.materials[0].bounciness = f32::NAN;
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::InvalidObject {
+            index: 0,
+            error: GameObjectError::InvalidCoefficient { .. }
+        })
+    ));
+    let mut description = described_scene().to_description().unwrap();
+    description.objects[0].colliders[0].mass = Some(0.);
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::InvalidObject {
+            index: 0,
+            error: GameObjectError::InvalidMass(_)
+        })
+    ));
+    // An infinite mass collider on a dynamic object
+    let mut description = described_scene().to_description().unwrap();
+    description.objects[1].colliders[1].mass = None;
+    description.objects[1].colliders[1].sensor = false;
+    assert!(matches!(
+        PhysicsScene::from_description(&description),
+        Err(SceneFileError::InvalidObject {
+            index: 1,
+            error: GameObjectError::InvalidMass(_)
+        })
+    ));
+
+    // Shapes defined outside the crate can't be saved
+    struct Custom(Sphere);
+    impl RayCast for Custom {
+        fn ray_cast(&self, pos: &Isometry3<f32>, ray: &Ray) -> RayCastResult {
+            self.0.ray_cast(pos, ray)
+        }
+    }
+    impl Shape for Custom {
+        fn compute_aabb(&self, pos: &Isometry3<f32>) -> AABB {
+            self.0.compute_aabb(pos)
+        }
+        fn compute_bounding_sphere(&self, pos: &Isometry3<f32>) -> BoundingSphere {
+            self.0.compute_bounding_sphere(pos)
+        }
+        fn contains_point(&self, pos: &Isometry3<f32>, point: &Point3<f32>) -> bool {
+            self.0.contains_point(pos, point)
+        }
+        fn volume(&self) -> f32 {
+            self.0.volume()
+        }
+        fn mass_properties(&self, mass: f32) -> MassProperties {
+            self.0.mass_properties(mass)
+        }
+    }
+    let mut scene = PhysicsScene::new();
+    scene.add(
+        GameObjectBuilder::new(Box::new(Custom(Sphere::new(1.))))
+            .build()
+            .unwrap(),
+    );
+    assert!(matches!(
+        scene.to_description(),
+        Err(SceneFileError::UnsupportedShape)
+    ));
+}
+
+#[test]
+fn example_scene_loads() {
+    // Hand written, leaving out the optional properties
+    let scene = PhysicsScene::load("examples/scene.json").unwrap();
+    assert_eq!(scene.objects().len(), 4);
+    assert!(scene.objects()[2].shape().as_capsule().is_some());
+    assert_eq!(scene.objects()[3].body_type(), BodyType::Static);
+}
@@ -0,0 +1,194 @@
+use nalgebra::{Isometry3, Translation3, Vector3};
+
+use crate::{
+    collision::CollisionManifold,
+    scene::{
+        builder::GameObjectBuilder, collider::Collider, game_object::BodyType,
+        snapshot::RestoreError, PhysicsScene,
+    },
+    shapes::sphere::Sphere,
+};
+
+/// Poses and velocities of all objects in the scene
+fn scene_state(scene: &PhysicsScene) -> Vec<(Isometry3<f32>, Vector3<f32>, Vector3<f32>)> {
+    scene
+        .objects()
+        .iter()
+        .map(|object| (object.position, object.velocity, object.angular_velocity))
+        .collect()
+}
+
+#[test]
+fn snapshot_restore() {
+    let mut scene = PhysicsScene::new();
+    for i in 0..4 {
+        scene.add(
+            GameObjectBuilder::sphere(0.3)
+                .translation([0.5 * i as f32, 1. + 0.7 * i as f32, 0.1 * i as f32])
+                .velocity([-(i as f32), 0., 0.5])
+                .angular_velocity([0., i as f32, 0.])
+                .build()
+                .unwrap(),
+        );
+    }
+    scene.add(
+        GameObjectBuilder::capsule(0.4, 0.2)
+            .translation([1., 0.5, 0.])
+            .rotation([0.3, 0., 0.6])
+            .build()
+            .unwrap(),
+    );
+    let pusher = scene.add(
+        GameObjectBuilder::sphere(0.5)
+            .body_type(BodyType::KinematicPositionBased)
+            .translation([-1., 0., 0.])
+            .build()
+            .unwrap(),
+    );
+    scene.add(GameObjectBuilder::plane([0., 1., 0.]).build().unwrap());
+
+    let step = |scene: &mut PhysicsScene, steps: usize| {
+        for _ in 0..steps {
+            let pusher = scene.get_mut(pusher).unwrap();
+            let target = pusher.position * Translation3::new(0.01, 0., 0.);
+            pusher.set_next_kinematic_position(target);
+            scene.update(0.01);
+        }
+    };
+    step(&mut scene, 20);
+    let snapshot = scene.snapshot();
+    assert_eq!(snapshot.len(), 7);
+    let before = scene_state(&scene);
+    step(&mut scene, 50);
+    let original = scene_state(&scene);
+    assert_ne!(original, before);
+
+    // Replaying from the snapshot gives exactly the same results
+    scene.restore(&snapshot).unwrap();
+    assert_eq!(scene_state(&scene), before);
+    step(&mut scene, 50);
+    assert_eq!(scene_state(&scene), original);
+
+    // A snapshot only fits the objects it was taken of
+    scene.add(GameObjectBuilder::sphere(1.).build().unwrap());
+    assert_eq!(
+        scene.restore(&snapshot),
+        Err(RestoreError::ObjectCountMismatch {
+            snapshot: 7,
+            scene: 8
+        })
+    );
+    assert_eq!(scene.objects().len(), 8);
+    let added = scene.handles()[7];
+    scene.remove(added);
+
+    // The objects must also still have their body types and colliders
+    scene
+        .get_mut(pusher)
+        .unwrap()
+        .set_body_type(BodyType::Static)
+        .unwrap();
+    assert_eq!(
+        scene.restore(&snapshot),
+        Err(RestoreError::BodyTypeMismatch {
+            handle: pusher,
+            snapshot: BodyType::KinematicPositionBased,
+            scene: BodyType::Static
+        })
+    );
+    scene
+        .get_mut(pusher)
+        .unwrap()
+        .set_body_type(BodyType::KinematicPositionBased)
+        .unwrap();
+    scene.get_mut(pusher).unwrap().colliders_mut()[0] =
+        Collider::new(Box::new(Sphere::new(0.6)), 1.);
+    assert_eq!(
+        scene.restore(&snapshot),
+        Err(RestoreError::ColliderMismatch { handle: pusher })
+    );
+    scene.get_mut(pusher).unwrap().colliders_mut()[0] =
+        Collider::new(Box::new(Sphere::new(0.5)), 1.);
+    scene.restore(&snapshot).unwrap();
+
+    // Or be the same objects
+    let first = scene.handles()[0];
+    scene.remove(first);
+    scene.add(GameObjectBuilder::sphere(0.3).build().unwrap());
+    assert_eq!(
+        scene.restore(&snapshot),
+        Err(RestoreError::HandleMismatch { index: 0 })
+    );
+}
+
+/// A pile of spheres and capsules falling onto a floor and each other, built the same way every time
+fn busy_scene() -> PhysicsScene {
+    let mut scene = PhysicsScene::new();
+    for i in 0..12 {
+        let (x, y, z) = (
+            (i % 3) as f32 * 0.5,
+            0.5 + i as f32 * 0.3,
+            (i % 2) as f32 * 0.4,
+        );
+        let builder = if i % 4 == 0 {
+            GameObjectBuilder::capsule(0.3, 0.2).rotation([0.4, 0., 0.2 * i as f32])
+        } else {
+            GameObjectBuilder::sphere(0.25)
+        };
+        scene.add(
+            builder
+                .translation([x, y, z])
+                .velocity([0.3 - 0.1 * i as f32, 0., 0.2])
+                .angular_velocity([0., 0.5 * i as f32, 0.])
+                .build()
+                .unwrap(),
+        );
+    }
+    scene.add(
+        GameObjectBuilder::sphere(1.)
+            .translation([0.5, 1., 0.])
+            .sensor(true)
+            .body_type(BodyType::Static)
+            .build()
+            .unwrap(),
+    );
+    scene.add(GameObjectBuilder::plane([0., 1., 0.]).build().unwrap());
+    scene
+}
+
+/// Steps `scene` `steps` times, returning its state hash after every step
+fn frame_hashes(scene: &mut PhysicsScene, steps: usize) -> Vec<u64> {
+    (0..steps)
+        .map(|_| {
+            scene.update(0.01);
+            scene.state_hash()
+        })
+        .collect()
+}
+
+#[test]
+fn deterministic_runs() {
+    let first = frame_hashes(&mut busy_scene(), 120);
+    let second = frame_hashes(&mut busy_scene(), 120);
+    assert_eq!(first, second);
+    assert_ne!(first[0], first[119]);
+
+    // Restoring a snapshot replays the same frames
+    let mut scene = busy_scene();
+    frame_hashes(&mut scene, 60);
+    let snapshot = scene.snapshot();
+    let hash = scene.state_hash();
+    frame_hashes(&mut scene, 30);
+    assert_ne!(scene.state_hash(), hash);
+    scene.restore(&snapshot).unwrap();
+    assert_eq!(scene.state_hash(), hash);
+    assert_eq!(frame_hashes(&mut scene, 60), first[60..]);
+
+    // The contacts are part of the state
+    scene.restore(&snapshot).unwrap();
+    let (handle_1, handle_2) = (scene.handles()[0], scene.handles()[1]);
+    scene
+        .contacts
+        .push((handle_1, handle_2, CollisionManifold::new()));
+    assert_ne!(scene.state_hash(), hash);
+}
@@ -0,0 +1,41 @@
+use crate::scene::{
+    builder::GameObjectBuilder,
+    trajectory::{TrajectoryFormat, TrajectoryWriter},
+    PhysicsScene,
+};
+
+#[test]
+fn trajectory_output() {
+    let mut scene = PhysicsScene::new();
+    let mut sphere = GameObjectBuilder::sphere(1.)
+        .translation([1., 2., 3.])
+        .build()
+        .unwrap();
+    sphere.add_velocity([0.5, 0., 0.]);
+    scene.add(sphere);
+
+    let mut csv = TrajectoryWriter::new(Vec::new(), TrajectoryFormat::Csv);
+    csv.write_frame(0, 0., &scene).unwrap();
+    csv.write_frame(1, 0.5, &scene).unwrap();
+    let csv = String::from_utf8(csv.into_inner()).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        rows,
+        [
+            "frame,time,body,x,y,z,qx,qy,qz,qw,vx,vy,vz,wx,wy,wz",
+            "0,0,0,1,2,3,0,0,0,1,0.5,0,0,0,0,0",
+            "1,0.5,0,1,2,3,0,0,0,1,0.5,0,0,0,0,0",
+        ]
+    );
+
+    let mut jsonl = TrajectoryWriter::new(Vec::new(), TrajectoryFormat::JsonLines);
+    jsonl.write_frame(3, 0.25, &scene).unwrap();
+    assert_eq!(
+        String::from_utf8(jsonl.into_inner()).unwrap(),
+        "{\"frame\":3,\"time\":0.25,\"bodies\":[{\"body\":0,\"position\":[1,2,3],\"rotation\":[0,0,0,1],\"velocity\":[0.5,0,0],\"angular_velocity\":[0,0,0]}]}\n"
+    );
+
+    assert_eq!("csv".parse(), Ok(TrajectoryFormat::Csv));
+    assert_eq!("jsonl".parse(), Ok(TrajectoryFormat::JsonLines));
+    assert!("xml".parse::<TrajectoryFormat>().is_err());
+}
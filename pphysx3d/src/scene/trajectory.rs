@@ -0,0 +1,129 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use super::PhysicsScene;
+
+/// How `TrajectoryWriter` writes the frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    /// One row per object and frame, after a header row
+    Csv,
+    /// One JSON object per frame and line, with the objects in an array
+    JsonLines,
+}
+
+impl FromStr for TrajectoryFormat {
+    type Err = String;
+
+    /// Parses `csv` or `jsonl`
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "csv" => Ok(TrajectoryFormat::Csv),
+            "jsonl" | "json" => Ok(TrajectoryFormat::JsonLines),
+            _ => Err(format!(
+                "unknown trajectory format '{}', expected csv or jsonl",
+                format
+            )),
+        }
+    }
+}
+
+/// Writes the poses and velocities of all objects in a scene, frame by frame.
+/// Rotations are unit quaternions as x, y, z, w.
+pub struct TrajectoryWriter<W: Write> {
+    writer: W,
+    format: TrajectoryFormat,
+    header_written: bool,
+}
+
+impl<W: Write> TrajectoryWriter<W> {
+    pub fn new(writer: W, format: TrajectoryFormat) -> TrajectoryWriter<W> {
+        TrajectoryWriter {
+            writer,
+            format,
+            header_written: false,
+        }
+    }
+
+    /// Writes the state of every object in `scene` as frame number `frame` at `time` [s]
+    pub fn write_frame(&mut self, frame: usize, time: f32, scene: &PhysicsScene) -> io::Result<()> {
+        match self.format {
+            TrajectoryFormat::Csv => {
+                if !self.header_written {
+                    writeln!(
+                        self.writer,
+                        "frame,time,body,x,y,z,qx,qy,qz,qw,vx,vy,vz,wx,wy,wz"
+                    )?;
+                    self.header_written = true;
+                }
+                for (body, object) in scene.objects().iter().enumerate() {
+                    let values = object
+                        .position
+                        .translation
+                        .vector
+                        .iter()
+                        .chain(object.position.rotation.coords.iter())
+                        .chain(object.velocity.iter())
+                        .chain(object.angular_velocity.iter());
+                    let mut row = format!("{},{},{}", frame, time, body);
+                    for value in values {
+                        write!(row, ",{}", value).unwrap();
+                    }
+                    writeln!(self.writer, "{}", row)?;
+                }
+            }
+            TrajectoryFormat::JsonLines => {
+                let bodies: Vec<String> = scene
+                    .objects()
+                    .iter()
+                    .enumerate()
+                    .map(|(body, object)| {
+                        format!(
+                            "{{\"body\":{},\"position\":{},\"rotation\":{},\"velocity\":{},\"angular_velocity\":{}}}",
+                            body,
+                            json_array(object.position.translation.vector.iter()),
+                            json_array(object.position.rotation.coords.iter()),
+                            json_array(object.velocity.iter()),
+                            json_array(object.angular_velocity.iter()),
+                        )
+                    })
+                    .collect();
+                writeln!(
+                    self.writer,
+                    "{{\"frame\":{},\"time\":{},\"bodies\":[{}]}}",
+                    frame,
+                    json_number(time),
+                    bodies.join(",")
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes everything buffered so far
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// The writer the frames are written to
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// JSON has no NaN or infinity, they're written as null
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_array<'a>(values: impl Iterator<Item = &'a f32>) -> String {
+    let values: Vec<String> = values.map(|value| json_number(*value)).collect();
+    format!("[{}]", values.join(","))
+}
@@ -1,10 +1,13 @@
-use kiss3d::nalgebra::{self as na, Point3, Vector3};
+use nalgebra::{self as na, Point3, Vector3};
+
+use super::ray::Ray;
 
 pub trait BoundingVolume {
     fn interects(&self, other: &Self) -> bool;
     fn contains(&self, other: &Self) -> bool;
 }
 ///Axis-Alligned Bounding box for collision checking
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct AABB {
     //two points is all it takes to represent a box
     mins: Point3<f32>,
@@ -34,6 +37,56 @@ impl AABB {
         self.maxs += translation;
         self.mins += translation;
     }
+
+    /// The center point of the box
+    pub fn center(&self) -> Point3<f32> {
+        // Halving before adding keeps the huge boxes of planes from overflowing
+        Point3::from(self.mins.coords * 0.5 + self.maxs.coords * 0.5)
+    }
+
+    /// Returns the smallest axis-aligned bounding box containing both `self` and `other`
+    pub fn merged(&self, other: &AABB) -> AABB {
+        AABB {
+            mins: self.mins.inf(&other.mins),
+            maxs: self.maxs.sup(&other.maxs),
+        }
+    }
+
+    /// Checks if the point lies inside or on the border of the box
+    pub fn contains_point(&self, point: &Point3<f32>) -> bool {
+        na::partial_le(&self.mins, point) && na::partial_ge(&self.maxs, point)
+    }
+
+    /// Returns the distance along the ray where it enters the box, or `None` if the box is not hit before `max_toi`.
+    /// A ray starting inside the box gets distance 0.
+    pub fn cast_ray(&self, ray: &Ray, max_toi: f32) -> Option<f32> {
+        // Slab test, intersecting the ray with each pair of axis-aligned planes
+        let mut t_min: f32 = 0.0;
+        let mut t_max: f32 = max_toi;
+        for i in 0..3 {
+            let origin = ray.origin()[i];
+            let direction = ray.direction()[i];
+            if direction.abs() < f32::EPSILON {
+                // Parallel to the slab, so the origin has to be between the planes
+                if origin < self.mins[i] || origin > self.maxs[i] {
+                    return None;
+                }
+            } else {
+                let inv_direction = 1.0 / direction;
+                let mut t_1 = (self.mins[i] - origin) * inv_direction;
+                let mut t_2 = (self.maxs[i] - origin) * inv_direction;
+                if t_1 > t_2 {
+                    std::mem::swap(&mut t_1, &mut t_2);
+                }
+                t_min = t_min.max(t_1);
+                t_max = t_max.min(t_2);
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+        Some(t_min)
+    }
 }
 
 impl BoundingVolume for AABB {
@@ -48,6 +101,7 @@ impl BoundingVolume for AABB {
 }
 
 ///Bounding sphere for collision checking
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BoundingSphere {
     center: Point3<f32>,
     radius: f32,
@@ -58,6 +112,12 @@ impl BoundingSphere {
     pub fn new(radius: f32, center: Point3<f32>) -> BoundingSphere {
         BoundingSphere { center, radius }
     }
+    pub fn center(&self) -> Point3<f32> {
+        self.center
+    }
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
 }
 
 impl BoundingVolume for BoundingSphere {
@@ -0,0 +1,153 @@
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+
+use super::{
+    bounding_volume::{BoundingSphere, AABB},
+    mass_properties::MassProperties,
+    ray::Ray,
+    raycast::{RayCast, RayCastResult},
+    shape::Shape,
+    utils::closest_point_on_segment,
+};
+
+/// A cylinder with half spheres at both ends, the axis going along the local y axis.
+/// Every point within `radius` of the segment from `-half_height` to `half_height` is inside it.
+#[derive(Debug, PartialEq)]
+pub struct Capsule {
+    pub half_height: f32,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub fn new(half_height: f32, radius: f32) -> Capsule {
+        Capsule {
+            half_height,
+            radius,
+        }
+    }
+
+    /// Returns the end points of the capsule's inner segment with the position given by `pos`
+    pub fn segment(&self, pos: &Isometry3<f32>) -> (Point3<f32>, Point3<f32>) {
+        (
+            pos * Point3::new(0.0, -self.half_height, 0.0),
+            pos * Point3::new(0.0, self.half_height, 0.0),
+        )
+    }
+
+    /// Returns the axis-aligned bounding box of the capsule with the position given by `pos`
+    pub fn aabb(&self, pos: &Isometry3<f32>) -> AABB {
+        let (a, b) = self.segment(pos);
+        let radius = Vector3::repeat(self.radius);
+        AABB::new(a.inf(&b) - radius, a.sup(&b) + radius)
+    }
+
+    /// Returns the bounding sphere of the capsule with the position given by `pos`
+    pub fn bounding_sphere(&self, pos: &Isometry3<f32>) -> BoundingSphere {
+        let center = Point3::from(pos.translation.vector);
+        BoundingSphere::new(self.half_height + self.radius, center)
+    }
+}
+
+impl Shape for Capsule {
+    fn compute_aabb(&self, pos: &Isometry3<f32>) -> AABB {
+        self.aabb(pos)
+    }
+    fn compute_bounding_sphere(&self, pos: &Isometry3<f32>) -> BoundingSphere {
+        self.bounding_sphere(pos)
+    }
+
+    fn contains_point(&self, pos: &Isometry3<f32>, point: &Point3<f32>) -> bool {
+        let (a, b) = self.segment(pos);
+        let closest = closest_point_on_segment(&a, &b, point);
+        (point - closest).norm_squared() <= self.radius * self.radius
+    }
+
+    fn volume(&self) -> f32 {
+        // A cylinder and the two halves of a sphere
+        let cylinder = std::f32::consts::PI * self.radius.powi(2) * 2.0 * self.half_height;
+        let sphere = 4.0 / 3.0 * std::f32::consts::PI * self.radius.powi(3);
+        cylinder + sphere
+    }
+
+    fn mass_properties(&self, mass: f32) -> MassProperties {
+        MassProperties::capsule(mass, self.half_height, self.radius)
+    }
+
+    fn as_capsule(&self) -> Option<&Capsule> {
+        Some(self)
+    }
+}
+
+/// Distances along the ray where it crosses a sphere's surface, if it does
+fn sphere_crossings(ray: &Ray, center: &Point3<f32>, radius: f32) -> Option<(f32, f32)> {
+    let offset: Vector3<f32> = ray.origin() - center;
+    let b = offset.dot(ray.direction());
+    let c = offset.norm_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
+
+impl RayCast for Capsule {
+    /// Does a raycast test on `self` with transform `pos: &Isometry3<f32>`
+    fn ray_cast(&self, pos: &Isometry3<f32>, ray: &Ray) -> RayCastResult {
+        let mut result = RayCastResult::new();
+        let (a, b) = self.segment(pos);
+        let axis: UnitVector3<f32> = pos.rotation * Vector3::y_axis();
+        let height = 2.0 * self.half_height;
+
+        // Every place the ray crosses the surface of the cylinder or one of the end spheres is a candidate,
+        // the closest one in front of the ray that lies on the capsule's surface is the hit
+        let mut candidates: Vec<f32> = Vec::with_capacity(6);
+
+        // The infinite cylinder around the axis
+        let offset: Vector3<f32> = ray.origin() - a;
+        let offset_perp = offset - axis.scale(offset.dot(&axis));
+        let direction_perp = ray.direction().into_inner() - axis.scale(ray.direction().dot(&axis));
+        let qa = direction_perp.norm_squared();
+        if qa > f32::EPSILON {
+            let qb = offset_perp.dot(&direction_perp);
+            let qc = offset_perp.norm_squared() - self.radius * self.radius;
+            let discriminant = qb * qb - qa * qc;
+            if discriminant >= 0.0 {
+                let root = discriminant.sqrt();
+                for toi in [(-qb - root) / qa, (-qb + root) / qa] {
+                    // Only the part of the cylinder between the end spheres is surface
+                    let along_axis = (ray.point_at(toi) - a).dot(&axis);
+                    if (0.0..=height).contains(&along_axis) {
+                        candidates.push(toi);
+                    }
+                }
+            }
+        }
+
+        // The end spheres, only the outer halves are surface
+        for (center, outwards) in [(a, -1.0), (b, 1.0)] {
+            if let Some((toi_1, toi_2)) = sphere_crossings(ray, &center, self.radius) {
+                for toi in [toi_1, toi_2] {
+                    if outwards * (ray.point_at(toi) - center).dot(&axis) >= 0.0 {
+                        candidates.push(toi);
+                    }
+                }
+            }
+        }
+
+        let toi = match candidates
+            .into_iter()
+            .filter(|toi| *toi >= 0.0)
+            .min_by(|toi_1, toi_2| toi_1.total_cmp(toi_2))
+        {
+            Some(toi) => toi,
+            None => return result,
+        };
+
+        result.distance = toi;
+        result.hit = true;
+        result.contact_point = ray.point_at(toi);
+        let closest = closest_point_on_segment(&a, &b, &result.contact_point);
+        result.normal = UnitVector3::new_normalize(result.contact_point - closest);
+        result
+    }
+}
@@ -0,0 +1,150 @@
+use std::ops::Add;
+
+use nalgebra::{Isometry3, Matrix3, Point3, Rotation3, UnitQuaternion, Vector3};
+
+/// Mass, center of mass and inertia of a body or shape, in its local space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    /// [kg], may be infinite for shapes that never move
+    pub mass: f32,
+    /// Center of mass in local space
+    pub local_center_of_mass: Point3<f32>,
+    /// Moments of inertia around the principal axes through the center of mass [kg m^2]
+    pub principal_inertia: Vector3<f32>,
+    /// Rotation from the principal axes to local space
+    pub principal_inertia_frame: UnitQuaternion<f32>,
+}
+
+impl Default for MassProperties {
+    fn default() -> Self {
+        MassProperties::zero()
+    }
+}
+
+impl MassProperties {
+    /// No mass and no inertia
+    pub fn zero() -> MassProperties {
+        MassProperties::new(0., Point3::origin(), Vector3::zeros())
+    }
+
+    /// Mass properties whose principal axes are the local axes
+    pub fn new(
+        mass: f32,
+        local_center_of_mass: Point3<f32>,
+        principal_inertia: Vector3<f32>,
+    ) -> MassProperties {
+        MassProperties {
+            mass,
+            local_center_of_mass,
+            principal_inertia,
+            principal_inertia_frame: UnitQuaternion::identity(),
+        }
+    }
+
+    /// A solid sphere centered at the origin
+    pub fn sphere(mass: f32, radius: f32) -> MassProperties {
+        let inertia = 0.4 * mass * radius * radius;
+        MassProperties::new(mass, Point3::origin(), Vector3::repeat(inertia))
+    }
+
+    /// A solid capsule centered at the origin with its axis along y
+    pub fn capsule(mass: f32, half_height: f32, radius: f32) -> MassProperties {
+        let height = 2. * half_height;
+        let cylinder_volume = std::f32::consts::PI * radius * radius * height;
+        let sphere_volume = 4. / 3. * std::f32::consts::PI * radius.powi(3);
+        let cylinder_mass = mass * cylinder_volume / (cylinder_volume + sphere_volume);
+        let caps_mass = mass - cylinder_mass;
+
+        let r2 = radius * radius;
+        let axial = cylinder_mass * r2 / 2. + caps_mass * 0.4 * r2;
+        // The caps' halves of a sphere sit at the ends of the cylinder, moved there with the parallel axis theorem
+        let transverse = cylinder_mass * (height * height / 12. + r2 / 4.)
+            + caps_mass * (0.4 * r2 + height * height / 4. + 3. * height * radius / 8.);
+        MassProperties::new(
+            mass,
+            Point3::origin(),
+            Vector3::new(transverse, axial, transverse),
+        )
+    }
+
+    /// The inertia tensor around the center of mass in local space
+    pub fn inertia_tensor(&self) -> Matrix3<f32> {
+        let frame = self.principal_inertia_frame.to_rotation_matrix();
+        frame.matrix()
+            * Matrix3::from_diagonal(&self.principal_inertia)
+            * frame.matrix().transpose()
+    }
+
+    /// The inverse inertia tensor around the center of mass for a body with `rotation`, in world space.
+    /// Axes without inertia can't be turned and get 0.
+    pub fn world_inv_inertia(&self, rotation: &UnitQuaternion<f32>) -> Matrix3<f32> {
+        if !self.mass.is_finite() {
+            return Matrix3::zeros();
+        }
+        let inverse = self.principal_inertia.map(|inertia| {
+            if inertia > f32::EPSILON {
+                1. / inertia
+            } else {
+                0.
+            }
+        });
+        let frame = (rotation * self.principal_inertia_frame).to_rotation_matrix();
+        frame.matrix() * Matrix3::from_diagonal(&inverse) * frame.matrix().transpose()
+    }
+
+    /// The same mass properties seen from a space in which the local space has the pose `iso`
+    pub fn transformed(&self, iso: &Isometry3<f32>) -> MassProperties {
+        MassProperties {
+            local_center_of_mass: iso * self.local_center_of_mass,
+            principal_inertia_frame: iso.rotation * self.principal_inertia_frame,
+            ..*self
+        }
+    }
+
+    /// Mass properties with the principal axes of the inertia `tensor` around `center_of_mass`
+    fn from_tensor(mass: f32, center_of_mass: Point3<f32>, tensor: Matrix3<f32>) -> MassProperties {
+        let eigen = tensor.symmetric_eigen();
+        let mut axes = eigen.eigenvectors;
+        // The eigenvectors may form a reflection, flip one to make it a rotation
+        if axes.determinant() < 0. {
+            axes.set_column(2, &-axes.column(2));
+        }
+        MassProperties {
+            mass,
+            local_center_of_mass: center_of_mass,
+            principal_inertia: eigen.eigenvalues,
+            principal_inertia_frame: UnitQuaternion::from_rotation_matrix(
+                &Rotation3::from_matrix_unchecked(axes),
+            ),
+        }
+    }
+}
+
+/// Combines the mass properties of two parts of the same body
+impl Add for MassProperties {
+    type Output = MassProperties;
+
+    fn add(self, other: MassProperties) -> MassProperties {
+        let mass = self.mass + other.mass;
+        if mass <= f32::EPSILON {
+            return MassProperties::zero();
+        }
+        if !mass.is_finite() {
+            // Nothing moves an infinitely heavy body, so its inertia doesn't matter
+            return MassProperties::new(mass, Point3::origin(), Vector3::zeros());
+        }
+        let center_of_mass = Point3::from(
+            (self.local_center_of_mass.coords * self.mass
+                + other.local_center_of_mass.coords * other.mass)
+                / mass,
+        );
+        // Parallel axis theorem, moving each part's inertia to the common center of mass
+        let shifted = |part: &MassProperties| {
+            let offset = part.local_center_of_mass - center_of_mass;
+            part.inertia_tensor()
+                + (Matrix3::identity() * offset.norm_squared() - offset * offset.transpose())
+                    * part.mass
+        };
+        MassProperties::from_tensor(mass, center_of_mass, shifted(&self) + shifted(&other))
+    }
+}
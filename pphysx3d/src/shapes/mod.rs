@@ -1,8 +1,10 @@
 pub mod bounding_volume;
+pub mod capsule;
+pub mod mass_properties;
 pub mod plane;
 pub mod ray;
 pub mod raycast;
 pub mod shape;
 pub mod sphere;
 mod tests;
-mod utils;
+pub(crate) mod utils;
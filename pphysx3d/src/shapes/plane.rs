@@ -1,11 +1,11 @@
-use kiss3d::nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
 
 use super::{
     bounding_volume::{BoundingSphere, AABB},
+    mass_properties::MassProperties,
     ray::Ray,
     raycast::{RayCast, RayCastResult},
     shape::Shape,
-    sphere::Sphere,
 };
 ///The shape of a plane, defined by its normal
 pub struct Plane {
@@ -42,12 +42,21 @@ impl Shape for Plane {
         let center: Point3<f32> = Point3::new(0.0, 0.0, 0.0);
         return BoundingSphere::new(f32::MAX / 2.0, center);
     }
-    fn as_sphere(&self) -> Result<&Sphere, ()> {
-        return Err(());
+    fn contains_point(&self, pos: &Isometry3<f32>, point: &Point3<f32>) -> bool {
+        // The plane is treated as a solid half-space behind its normal
+        let normal: UnitVector3<f32> = pos.rotation * self.normal;
+        normal.dot(&(point.coords - pos.translation.vector)) <= 0.0
     }
-
-    fn as_plane(&self) -> Result<&Plane, ()> {
-        Ok(&self)
+    fn volume(&self) -> f32 {
+        // Everything behind the plane
+        f32::INFINITY
+    }
+    fn mass_properties(&self, mass: f32) -> MassProperties {
+        // Can't be turned around its infinite extent
+        MassProperties::new(mass, Point3::origin(), Vector3::zeros())
+    }
+    fn as_plane(&self) -> Option<&Plane> {
+        Some(self)
     }
 }
 
@@ -73,7 +82,7 @@ impl RayCast for Plane {
         }
 
         result.contact_point = ray.origin() + ray.direction().scale(toi);
-        result.normal = self.normal;
+        result.normal = normal;
         result.hit = true;
         result.distance = toi;
 
@@ -1,5 +1,7 @@
-use kiss3d::nalgebra::{Point3, UnitVector3};
+use nalgebra::{Point3, UnitVector3};
 
+/// A half-line starting at `origin` going in `direction`
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Ray {
     origin: Point3<f32>,
     direction: UnitVector3<f32>,
@@ -15,4 +17,8 @@ impl Ray {
     pub fn direction(&self) -> &UnitVector3<f32> {
         return &self.direction;
     }
+    /// The point at distance `toi` along the ray
+    pub fn point_at(&self, toi: f32) -> Point3<f32> {
+        self.origin + self.direction.scale(toi)
+    }
 }
@@ -1,4 +1,4 @@
-use kiss3d::nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
 
 use super::ray::Ray;
 #[derive(Debug, PartialEq)]
@@ -9,7 +9,14 @@ pub struct RayCastResult {
     pub hit: bool,
 }
 
+impl Default for RayCastResult {
+    fn default() -> Self {
+        RayCastResult::new()
+    }
+}
+
 impl RayCastResult {
+    /// A result that didn't hit anything
     pub fn new() -> RayCastResult {
         RayCastResult {
             distance: -1.0,
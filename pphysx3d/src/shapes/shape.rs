@@ -1,14 +1,33 @@
-use kiss3d::nalgebra::Isometry3;
+use nalgebra::{Isometry3, Point3};
 
 use super::{
     bounding_volume::{BoundingSphere, AABB},
+    capsule::Capsule,
+    mass_properties::MassProperties,
     plane::Plane,
+    raycast::RayCast,
     sphere::Sphere,
 };
 ///Main trait for all shapes
-pub trait Shape {
+pub trait Shape: RayCast {
     fn compute_aabb(&self, pos: &Isometry3<f32>) -> AABB;
     fn compute_bounding_sphere(&self, pos: &Isometry3<f32>) -> BoundingSphere;
-    fn as_sphere(&self) -> Result<&Sphere, ()>;
-    fn as_plane(&self) -> Result<&Plane, ()>;
+    /// Checks if the point lies inside the shape with transform `pos`
+    fn contains_point(&self, pos: &Isometry3<f32>, point: &Point3<f32>) -> bool;
+    /// The volume enclosed by the shape [m^3]
+    fn volume(&self) -> f32;
+    /// Mass properties of the shape in its local space when it has `mass` spread evenly over its volume
+    fn mass_properties(&self, mass: f32) -> MassProperties;
+    /// The shape as a sphere, `None` for other shapes
+    fn as_sphere(&self) -> Option<&Sphere> {
+        None
+    }
+    /// The shape as a plane, `None` for other shapes
+    fn as_plane(&self) -> Option<&Plane> {
+        None
+    }
+    /// The shape as a capsule, `None` for other shapes
+    fn as_capsule(&self) -> Option<&Capsule> {
+        None
+    }
 }
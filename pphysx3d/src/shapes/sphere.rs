@@ -1,12 +1,13 @@
 use super::{
     bounding_volume::BoundingSphere,
+    mass_properties::MassProperties,
     ray::Ray,
     raycast::{RayCast, RayCastResult},
     shape::Shape,
 };
 use crate::shapes::bounding_volume::AABB;
 use core::f32;
-use kiss3d::nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
+use nalgebra::{Isometry3, Point3, UnitVector3, Vector3};
 
 #[derive(Debug, PartialEq)]
 pub struct Sphere {
@@ -40,12 +41,20 @@ impl Shape for Sphere {
         self.bounding_sphere(pos)
     }
 
-    fn as_sphere(&self) -> Result<&Sphere, ()> {
-        Ok(self)
+    fn contains_point(&self, pos: &Isometry3<f32>, point: &Point3<f32>) -> bool {
+        (point.coords - pos.translation.vector).norm_squared() <= self.radius * self.radius
     }
 
-    fn as_plane(&self) -> Result<&super::plane::Plane, ()> {
-        Err(())
+    fn volume(&self) -> f32 {
+        4. / 3. * f32::consts::PI * self.radius.powi(3)
+    }
+
+    fn mass_properties(&self, mass: f32) -> MassProperties {
+        MassProperties::sphere(mass, self.radius)
+    }
+
+    fn as_sphere(&self) -> Option<&Sphere> {
+        Some(self)
     }
 }
 
@@ -53,28 +62,30 @@ impl RayCast for Sphere {
     /// Does a raycast test on `self` with transform `pos: &Isometry3<f32>`
     fn ray_cast(&self, pos: &Isometry3<f32>, ray: &Ray) -> RayCastResult {
         let mut result = RayCastResult::new();
-        let distance_to_center: Vector3<f32> = Point3::from(pos.translation.vector) - ray.origin();
-        // project the distance vector to the direction of the ray
-        let proj: Vector3<f32> = ray
-            .direction()
-            .scale(ray.direction().dot(&distance_to_center));
-        let distance_to_proj: Vector3<f32> = proj - pos.translation.vector;
-        if self.radius * self.radius < distance_to_proj.norm_squared() {
+        let center = Point3::from(pos.translation.vector);
+        let distance_to_center: Vector3<f32> = center - ray.origin();
+        // project the distance vector onto the direction of the ray
+        let proj = ray.direction().dot(&distance_to_center);
+        let closest_distance_squared = distance_to_center.norm_squared() - proj * proj;
+        let radius_squared = self.radius * self.radius;
+        if radius_squared < closest_distance_squared {
             return result; // not intersecting
         }
-        let penetration_depth =
-            (self.radius * self.radius - distance_to_proj.norm_squared()).sqrt();
-        let mut toi = proj.norm() - penetration_depth;
+        let penetration_depth = (radius_squared - closest_distance_squared).sqrt();
+        let mut toi = proj - penetration_depth;
         // Reverse time of impact if ray starts inside sphere
-        if distance_to_center.norm_squared() < self.radius * self.radius {
+        if distance_to_center.norm_squared() < radius_squared {
             toi += penetration_depth * 2.0;
         }
+        // The sphere is behind the ray
+        if toi < 0.0 {
+            return result;
+        }
 
         result.distance = toi;
         result.hit = true;
         result.contact_point = ray.origin() + ray.direction().scale(toi);
-        result.normal =
-            UnitVector3::new_normalize(result.contact_point - Point3::from(pos.translation.vector));
+        result.normal = UnitVector3::new_normalize(result.contact_point - center);
         return result;
     }
 }
@@ -1,21 +1,18 @@
 use std::f32::consts::FRAC_1_PI;
 
 #[cfg(test)]
-use super::bounding_volume::BoundingVolume;
+use super::{bounding_volume::BoundingVolume, bounding_volume::AABB, shape::Shape};
 use super::{
     plane::Plane,
     ray::Ray,
     raycast::{RayCast, RayCastResult},
 };
 #[cfg(test)]
-use crate::shapes::sphere::Sphere;
+use crate::shapes::{capsule::Capsule, mass_properties::MassProperties, sphere::Sphere};
 
 #[cfg(test)]
-use kiss3d::nalgebra::{Isometry3, Vector3};
-use kiss3d::{
-    nalgebra::{Point3, Translation3, UnitQuaternion, UnitVector3},
-    ncollide3d::math::Vector,
-};
+use nalgebra::{Isometry3, Vector3};
+use nalgebra::{Point3, Translation3, UnitQuaternion, UnitVector3};
 
 #[test]
 fn test_bounding_sphere_around_sphere() {
@@ -104,3 +101,132 @@ fn plane_raycast() {
     facit.hit = true;
     assert_eq!(facit, result);
 }
+
+#[test]
+fn sphere_raycast_off_origin() {
+    let sphere = Sphere::new(1.0);
+    let position = Isometry3::translation(0.0, 5.0, 5.0);
+    let direction = UnitVector3::new_normalize(Vector3::new(0.0, 0.0, 1.0));
+
+    let result = sphere.ray_cast(&position, &Ray::new(Point3::new(0.0, 5.0, 0.0), direction));
+    assert!(result.hit);
+    assert_eq!(result.distance, 4.0);
+    assert_eq!(result.contact_point, Point3::new(0.0, 5.0, 4.0));
+
+    // Sphere behind the ray
+    let result = sphere.ray_cast(&position, &Ray::new(Point3::new(0.0, 5.0, 7.0), direction));
+    assert!(!result.hit);
+}
+
+#[test]
+fn shape_contains_point() {
+    let sphere = Sphere::new(1.0);
+    let position = Isometry3::translation(1.0, 0.0, 0.0);
+    assert!(sphere.contains_point(&position, &Point3::new(1.5, 0.5, 0.0)));
+    assert!(!sphere.contains_point(&position, &Point3::new(-0.5, 0.0, 0.0)));
+
+    let plane = Plane::new(UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)));
+    let position = Isometry3::translation(0.0, 1.0, 0.0);
+    assert!(plane.contains_point(&position, &Point3::new(10.0, 0.5, 0.0)));
+    assert!(!plane.contains_point(&position, &Point3::new(0.0, 1.5, 0.0)));
+}
+
+#[test]
+fn aabb_raycast() {
+    let aabb = AABB::new(Point3::new(1.0, -1.0, -1.0), Point3::new(3.0, 1.0, 1.0));
+    let ray = Ray::new(
+        Point3::new(0.0, 0.0, 0.0),
+        UnitVector3::new_normalize(Vector3::new(1.0, 0.0, 0.0)),
+    );
+    assert_eq!(aabb.cast_ray(&ray, 10.0), Some(1.0));
+    assert_eq!(aabb.cast_ray(&ray, 0.5), None);
+
+    let ray = Ray::new(
+        Point3::new(0.0, 2.0, 0.0),
+        UnitVector3::new_normalize(Vector3::new(1.0, 0.0, 0.0)),
+    );
+    assert_eq!(aabb.cast_ray(&ray, 10.0), None);
+}
+
+#[test]
+fn capsule_raycast() {
+    let capsule = Capsule::new(1.0, 0.5);
+    let position = Isometry3::translation(0.0, 0.0, 5.0);
+    let direction = UnitVector3::new_normalize(Vector3::new(0.0, 0.0, 1.0));
+
+    // Hitting the side of the cylinder
+    let result = capsule.ray_cast(&position, &Ray::new(Point3::new(0.0, 0.5, 0.0), direction));
+    assert!(result.hit);
+    assert_eq!(result.distance, 4.5);
+    assert_eq!(result.normal, UnitVector3::new_normalize(Vector3::new(0.0, 0.0, -1.0)));
+
+    // Hitting the top cap from above
+    let down = UnitVector3::new_normalize(Vector3::new(0.0, -1.0, 0.0));
+    let result = capsule.ray_cast(&position, &Ray::new(Point3::new(0.0, 5.0, 5.0), down));
+    assert!(result.hit);
+    assert_eq!(result.distance, 3.5);
+    assert_eq!(result.contact_point, Point3::new(0.0, 1.5, 5.0));
+
+    // Passing above it
+    let result = capsule.ray_cast(&position, &Ray::new(Point3::new(0.0, 1.6, 0.0), direction));
+    assert!(!result.hit);
+}
+
+#[test]
+fn capsule_contains_point() {
+    // Lying along the x axis
+    let capsule = Capsule::new(1.0, 0.5);
+    let position = Isometry3::new(
+        Vector3::zeros(),
+        Vector3::z() * std::f32::consts::FRAC_PI_2,
+    );
+    assert!(capsule.contains_point(&position, &Point3::new(1.4, 0.0, 0.0)));
+    assert!(capsule.contains_point(&position, &Point3::new(-0.5, 0.4, 0.0)));
+    assert!(!capsule.contains_point(&position, &Point3::new(0.0, 1.2, 0.0)));
+    assert!(!capsule.contains_point(&position, &Point3::new(1.6, 0.0, 0.0)));
+}
+
+#[test]
+fn shape_mass_properties() {
+    let sphere = Sphere::new(2.).mass_properties(5.);
+    assert_eq!(sphere.mass, 5.);
+    assert_eq!(sphere.local_center_of_mass, Point3::origin());
+    assert_eq!(sphere.principal_inertia, Vector3::repeat(8.));
+
+    // A capsule is easier to turn around its own axis than around the others
+    let capsule = Capsule::new(1., 0.5).mass_properties(2.);
+    assert!(capsule.principal_inertia.y < capsule.principal_inertia.x);
+    assert_eq!(capsule.principal_inertia.x, capsule.principal_inertia.z);
+    // Between a sphere and a rod of the same length
+    let rod = 2. * 3. * 3. / 12.;
+    assert!(capsule.principal_inertia.x > 0.4 * 2. * 0.25 && capsule.principal_inertia.x < rod);
+
+    // Planes can't be turned
+    let plane = Plane::new(UnitVector3::new_normalize(Vector3::y())).mass_properties(1.);
+    assert_eq!(plane.principal_inertia, Vector3::zeros());
+}
+
+#[test]
+fn combined_mass_properties() {
+    // Two spheres of 1 kg at x = 0 and x = 2
+    let sphere = MassProperties::sphere(1., 0.5);
+    let moved = sphere.transformed(&Isometry3::translation(2., 0., 0.));
+    assert_eq!(moved.local_center_of_mass, Point3::new(2., 0., 0.));
+    let combined = sphere + moved;
+    assert_eq!(combined.mass, 2.);
+    assert!((combined.local_center_of_mass - Point3::new(1., 0., 0.)).norm() < 1e-6);
+
+    // Each sphere is 1 m from the center of mass, which only matters when turning around y and z
+    let tensor = combined.inertia_tensor();
+    let own = 2. * 0.4 * 0.25;
+    assert!((tensor[(0, 0)] - own).abs() < 1e-5);
+    assert!((tensor[(1, 1)] - (own + 2.)).abs() < 1e-5);
+    assert!((tensor[(2, 2)] - (own + 2.)).abs() < 1e-5);
+    assert!(tensor[(0, 1)].abs() < 1e-5);
+
+    // Turning the body turns its inertia in world space
+    let turned = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f32::consts::FRAC_PI_2);
+    let world = combined.world_inv_inertia(&turned);
+    assert!((world[(1, 1)] - 1. / own).abs() < 1e-3);
+    assert!((world[(0, 0)] - 1. / (own + 2.)).abs() < 1e-3);
+}
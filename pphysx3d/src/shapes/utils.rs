@@ -1,4 +1,4 @@
-use kiss3d::nalgebra::{Isometry3, Vector3};
+use nalgebra::{Isometry3, Point3, Vector3};
 
 pub trait IsometryOperations {
     fn global_vector(&self, vector: &Vector3<f32>) -> Vector3<f32>;
@@ -10,3 +10,63 @@ impl IsometryOperations for &Isometry3<f32> {
         self.rotation.to_rotation_matrix().into_inner() * vector //Via basis matrix of the rotation
     }
 }
+
+/// The point on the segment from `a` to `b` closest to `point`
+pub fn closest_point_on_segment(a: &Point3<f32>, b: &Point3<f32>, point: &Point3<f32>) -> Point3<f32> {
+    let ab: Vector3<f32> = b - a;
+    let length_squared = ab.norm_squared();
+    if length_squared <= f32::EPSILON {
+        return *a;
+    }
+    let t = ((point - a).dot(&ab) / length_squared).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// The closest pair of points between the segments from `p_1` to `q_1` and from `p_2` to `q_2`.
+/// Follows Ericson's "Real-Time Collision Detection" chapter 5.1.9.
+pub fn closest_points_segments(
+    p_1: &Point3<f32>,
+    q_1: &Point3<f32>,
+    p_2: &Point3<f32>,
+    q_2: &Point3<f32>,
+) -> (Point3<f32>, Point3<f32>) {
+    let d_1: Vector3<f32> = q_1 - p_1;
+    let d_2: Vector3<f32> = q_2 - p_2;
+    let r: Vector3<f32> = p_1 - p_2;
+    let a = d_1.norm_squared();
+    let e = d_2.norm_squared();
+    let f = d_2.dot(&r);
+
+    // Both segments are points
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (*p_1, *p_2);
+    }
+    let (s, t) = if a <= f32::EPSILON {
+        // The first segment is a point
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d_1.dot(&r);
+        if e <= f32::EPSILON {
+            // The second segment is a point
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d_1.dot(&d_2);
+            let denominator = a * e - b * b;
+            // Parallel segments have no unique closest pair, any point on the first one will do
+            let s = if denominator > f32::EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p_1 + d_1 * s, p_2 + d_2 * t)
+}